@@ -0,0 +1,221 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering::Relaxed};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+// Live bytes can go below zero, freeing memory allocated before counting was
+// enabled.
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+/// System allocator keeping count of the allocations once `enable` is
+/// called. Counts are global, so they only add up for one day at a time.
+pub struct Counting;
+
+fn allocated(size: usize) {
+    ALLOCATIONS.fetch_add(1, Relaxed);
+    BYTES.fetch_add(size, Relaxed);
+    let live = LIVE.fetch_add(size as isize, Relaxed) + size as isize;
+    PEAK.fetch_max(live, Relaxed);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && ENABLED.load(Relaxed) {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() && ENABLED.load(Relaxed) {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        if ENABLED.load(Relaxed) {
+            LIVE.fetch_sub(layout.size() as isize, Relaxed);
+        }
+    }
+
+    // Counted as freeing the old block and allocating the new one.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = System.realloc(ptr, layout, new_size);
+        if !new.is_null() && ENABLED.load(Relaxed) {
+            LIVE.fetch_sub(layout.size() as isize, Relaxed);
+            allocated(new_size);
+        }
+        new
+    }
+}
+
+/// Starts counting, for binaries with `Counting` as their global allocator.
+pub fn enable() {
+    ENABLED.store(true, Relaxed);
+}
+
+/// Allocations made over a span of time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    pub allocations: usize,
+    pub bytes: usize,
+    /// Most bytes live at once, above the ones live when the span started.
+    pub peak: usize,
+    /// Bytes left live at the end of the span.
+    pub retained: isize,
+}
+
+impl AllocStats {
+    /// Stats of `self` followed by `next`.
+    pub fn then(self, next: AllocStats) -> AllocStats {
+        AllocStats {
+            allocations: self.allocations + next.allocations,
+            bytes: self.bytes + next.bytes,
+            peak: self
+                .peak
+                .max((self.retained + next.peak as isize).max(0) as usize),
+            retained: self.retained + next.retained,
+        }
+    }
+}
+
+/// `bytes` in the largest binary unit keeping the number above 1.
+pub fn size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocs, {} allocated, {} peak",
+            self.allocations,
+            size(self.bytes),
+            size(self.peak)
+        )
+    }
+}
+
+/// Counters at the start of a span.
+pub struct Span {
+    allocations: usize,
+    bytes: usize,
+    live: isize,
+}
+
+impl Span {
+    /// Starts a span, unless counting isn't enabled. Spans can't overlap, as
+    /// starting one resets the peak.
+    pub fn start() -> Option<Span> {
+        if !ENABLED.load(Relaxed) {
+            return None;
+        }
+        let live = LIVE.load(Relaxed);
+        PEAK.store(live, Relaxed);
+        Some(Span {
+            allocations: ALLOCATIONS.load(Relaxed),
+            bytes: BYTES.load(Relaxed),
+            live,
+        })
+    }
+
+    pub fn stop(self) -> AllocStats {
+        AllocStats {
+            allocations: ALLOCATIONS.load(Relaxed) - self.allocations,
+            bytes: BYTES.load(Relaxed) - self.bytes,
+            peak: (PEAK.load(Relaxed) - self.live).max(0) as usize,
+            retained: LIVE.load(Relaxed) - self.live,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting() {
+        // Not the global allocator of the tests, so only these calls count.
+        enable();
+        let span = Span::start().unwrap();
+        let small = Layout::from_size_align(16, 8).unwrap();
+        let large = Layout::from_size_align(1000, 8).unwrap();
+        let zeroed = unsafe {
+            let a = Counting.alloc(small);
+            let b = Counting.alloc(large);
+            Counting.dealloc(b, large);
+            let a = Counting.realloc(a, small, 100);
+            Counting.dealloc(a, Layout::from_size_align(100, 8).unwrap());
+            Counting.alloc_zeroed(small)
+        };
+        let stats = span.stop();
+        unsafe { Counting.dealloc(zeroed, small) };
+        assert_eq!(
+            stats,
+            AllocStats {
+                allocations: 4,
+                bytes: 1132,
+                peak: 1016,
+                retained: 16,
+            }
+        );
+    }
+
+    #[test]
+    fn test_then() {
+        let parse = AllocStats {
+            allocations: 3,
+            bytes: 300,
+            peak: 200,
+            retained: 100,
+        };
+        let part = AllocStats {
+            allocations: 2,
+            bytes: 150,
+            peak: 150,
+            retained: -50,
+        };
+        assert_eq!(
+            parse.then(part),
+            AllocStats {
+                allocations: 5,
+                bytes: 450,
+                peak: 250,
+                retained: 50,
+            }
+        );
+        assert_eq!(AllocStats::default().then(parse), parse);
+    }
+
+    #[test]
+    fn test_display() {
+        let stats = AllocStats {
+            allocations: 12,
+            bytes: 3 * 1024 * 1024 + 512 * 1024,
+            peak: 900,
+            retained: 0,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "12 allocs, 3.5 MiB allocated, 900 B peak"
+        );
+        assert_eq!(size(1536), "1.5 KiB");
+    }
+}
@@ -0,0 +1,182 @@
+use crate::cli::Part;
+use crate::parse::{lines, Line, ParseError, ParseResult};
+use crate::report::DayRun;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const ANSWERS_FILE: &str = "src/inputs/answers.txt";
+
+/// Expected answers keyed by day, part and the name of the input file, so the
+/// same file can hold the answers of the real inputs and of the samples.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(usize, usize, String), String>);
+
+// Answers are single lines, so line breaks are written as '\n'.
+fn unescape(line: &Line, s: &str) -> ParseResult<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some((_, 'n')) => out.push('\n'),
+            Some((_, '\\')) => out.push('\\'),
+            _ => return Err(line.error(&s[i..], "invalid escape, expected '\\n' or '\\\\'")),
+        }
+    }
+    Ok(out)
+}
+
+fn parse_entry(line: &Line) -> ParseResult<((usize, usize, String), String)> {
+    let mut fields = line.text.splitn(4, ' ');
+    let mut field = |name| {
+        fields
+            .next()
+            .filter(|f| !f.is_empty())
+            .ok_or_else(|| line.error_at(line.text.len(), format!("missing {name}")))
+    };
+    let (day, part, input, answer) = (
+        field("day")?,
+        field("part")?,
+        field("input")?,
+        field("answer")?,
+    );
+    let day = line.number(day)?;
+    let part = match line.number(part)? {
+        part @ 1..=2 => part,
+        _ => return Err(line.error(part, format!("invalid part '{part}', expected 1 or 2"))),
+    };
+    Ok(((day, part, input.to_string()), unescape(line, answer)?))
+}
+
+impl TryFrom<&str> for Answers {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        lines(s)
+            .filter(|line| !line.text.trim().is_empty() && !line.text.starts_with('#'))
+            .map(|line| parse_entry(&line))
+            .collect::<ParseResult<_>>()
+            .map(Answers)
+    }
+}
+
+impl Answers {
+    pub fn load(path: &str) -> Result<Answers, String> {
+        let s = fs::read_to_string(path).map_err(|e| format!("can't read answers: {e}"))?;
+        Answers::try_from(s.as_str()).map_err(|e| e.in_file(path).to_string())
+    }
+
+    pub fn get(&self, day: usize, part: Part, input: &str) -> Option<&str> {
+        self.0
+            .get(&(day, part.number(), input.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Checks the answers of `run` against the ones expected for its input.
+    /// Only the file name of the input is used, wherever it was read from.
+    pub fn verify(&self, run: &mut DayRun) {
+        let input = Path::new(&run.input)
+            .file_name()
+            .map_or(run.input.as_str(), |name| name.to_str().unwrap_or_default());
+        for record in &mut run.records {
+            record.verify(self.get(record.day, record.part, input));
+        }
+    }
+}
+
+/// Asserts `answer` is the expected answer of the input file `input`, as
+/// found in the answers file.
+#[cfg(test)]
+#[track_caller]
+pub fn assert_answer(day: usize, part: Part, input: &str, answer: impl ToString) {
+    let answers = Answers::load(ANSWERS_FILE).unwrap();
+    match answers.get(day, part, input) {
+        Some(expected) => assert_eq!(answer.to_string(), expected),
+        None => panic!(
+            "no answer for day {day} part {} of {input} in {ANSWERS_FILE}",
+            part.number()
+        ),
+    }
+}
+
+/// Like `assert_answer`, but passes while the answers file has no answer for
+/// `input` yet, as for a day that was just scaffolded.
+#[cfg(test)]
+#[track_caller]
+pub fn assert_known_answer(day: usize, part: Part, input: &str, answer: impl ToString) {
+    let answers = Answers::load(ANSWERS_FILE).unwrap();
+    if let Some(expected) = answers.get(day, part, input) {
+        assert_eq!(answer.to_string(), expected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Record, Status};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::try_from(
+            "# comment\n\n10 2 aoc_10.input \\n##.\\n.#\\\\\n25 1 aoc_25.input 2=-1=0\n",
+        )
+        .unwrap();
+        assert_eq!(
+            answers.get(10, Part::Two, "aoc_10.input"),
+            Some("\n##.\n.#\\")
+        );
+        assert_eq!(answers.get(25, Part::One, "aoc_25.input"), Some("2=-1=0"));
+        assert_eq!(answers.get(25, Part::Two, "aoc_25.input"), None);
+        assert_eq!(answers.get(25, Part::One, "aoc_25_sample.input"), None);
+        assert!(Answers::load(ANSWERS_FILE).is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Answers::try_from("1 1 aoc_1.input 3\n1 3 aoc_1.input 4"),
+            Err(ParseError::new(2, 3, "invalid part '3', expected 1 or 2"))
+        );
+        assert_eq!(
+            Answers::try_from("1 1 aoc_1.input"),
+            Err(ParseError::new(1, 16, "missing answer"))
+        );
+        assert_eq!(
+            Answers::try_from("1 1 aoc_1.input a\\tb"),
+            Err(ParseError::new(
+                1,
+                18,
+                "invalid escape, expected '\\n' or '\\\\'"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers = Answers::try_from("1 1 aoc_1.input 24000\n1 2 aoc_1.input 45000").unwrap();
+        let mut run = DayRun {
+            day: 1,
+            title: "Calorie Counting",
+            input: "../elsewhere/aoc_1.input".to_string(),
+            parse_time: Duration::ZERO,
+            total_time: Duration::ZERO,
+            records: vec![
+                Record::answer(1, Part::One, "24000".to_string(), Duration::ZERO),
+                Record::answer(1, Part::Two, "41000".to_string(), Duration::ZERO),
+            ],
+            parse_alloc: None,
+        };
+        answers.verify(&mut run);
+        assert_eq!(run.records[0].status, Status::Pass);
+        assert_eq!(run.records[1].status, Status::Fail);
+        assert_eq!(run.records[1].expected, Some("45000".to_string()));
+        run.input = "<stdin>".to_string();
+        answers.verify(&mut run);
+        assert_eq!(run.records[0].status, Status::Missing);
+    }
+}
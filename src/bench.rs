@@ -0,0 +1,165 @@
+use crate::cli::{Args, Part};
+use crate::context::Context;
+use crate::parse::ParseError;
+use crate::solver::{DynSolver, PartError};
+use std::fmt;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn new(timings: &[Duration]) -> Option<Self> {
+        let mut sorted = timings.to_vec();
+        sorted.sort();
+        let n = sorted.len();
+        let min = *sorted.first()?;
+        let median = match n % 2 {
+            0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+            _ => sorted[n / 2],
+        };
+        Some(Stats {
+            min,
+            median,
+            // Nearest rank.
+            p95: sorted[(n * 95).div_ceil(100) - 1],
+            max: sorted[n - 1],
+        })
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, p95 {:?}, max {:?}",
+            self.min, self.median, self.p95, self.max
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub runs: usize,
+    pub parse: Stats,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+/// Why a day couldn't be benchmarked: its input doesn't parse, or a part
+/// has no answer for it.
+#[derive(Debug)]
+pub enum BenchError {
+    Parse(ParseError),
+    Part(PartError),
+}
+
+impl From<ParseError> for BenchError {
+    fn from(e: ParseError) -> Self {
+        BenchError::Parse(e)
+    }
+}
+
+impl From<PartError> for BenchError {
+    fn from(e: PartError) -> Self {
+        BenchError::Part(e)
+    }
+}
+
+fn timed<T>(timings: &mut Vec<Duration>, f: impl FnOnce() -> T) -> T {
+    let now = Instant::now();
+    let r = black_box(f());
+    timings.push(now.elapsed());
+    r
+}
+
+/// Runs the selected parts of `solver` `runs` times, one after another. Each
+/// run parses the input again, so parsing gets its own statistics.
+pub fn bench(
+    solver: &dyn DynSolver,
+    input: &str,
+    runs: usize,
+    args: &Args,
+) -> Result<Report, BenchError> {
+    let mut parse = Vec::with_capacity(runs);
+    let mut part1 = Vec::with_capacity(runs);
+    let mut part2 = Vec::with_capacity(runs);
+    let ctx = Context::default();
+    for _ in 0..runs {
+        let model = timed(&mut parse, || solver.parse(black_box(input)))?;
+        if args.runs_part(Part::One) {
+            timed(&mut part1, || solver.part1(model.as_ref(), &ctx))?;
+        }
+        if args.runs_part(Part::Two) {
+            timed(&mut part2, || solver.part2(model.as_ref(), &ctx))?;
+        }
+    }
+    Ok(Report {
+        runs,
+        parse: Stats::new(&parse).expect("at least one run"),
+        part1: Stats::new(&part1),
+        part2: Stats::new(&part2),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(timings: &[u64]) -> Vec<Duration> {
+        timings.iter().map(|t| Duration::from_millis(*t)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(Stats::new(&[]), None);
+        assert_eq!(
+            Stats::new(&ms(&[7])),
+            Some(Stats {
+                min: Duration::from_millis(7),
+                median: Duration::from_millis(7),
+                p95: Duration::from_millis(7),
+                max: Duration::from_millis(7),
+            })
+        );
+        let stats = Stats::new(&ms(&[9, 1, 4, 2])).unwrap();
+        assert_eq!(
+            (stats.min, stats.median, stats.p95, stats.max),
+            (
+                Duration::from_millis(1),
+                Duration::from_millis(3),
+                Duration::from_millis(9),
+                Duration::from_millis(9)
+            )
+        );
+        let stats = Stats::new(&ms(&(1..=100).rev().collect::<Vec<u64>>())).unwrap();
+        assert_eq!(stats.p95, Duration::from_millis(95));
+        assert_eq!(stats.median, Duration::from_micros(50_500));
+    }
+
+    #[test]
+    fn test_bench() {
+        let args = crate::cli::parse(["1", "--part", "2"].map(String::from)).unwrap();
+        let solver = &crate::solver::registry()[&1];
+        let report = bench(solver.as_ref(), "1\n\n2\n\n3\n", 5, &args).unwrap();
+        assert_eq!(report.runs, 5);
+        assert!(report.part1.is_none());
+        assert!(report.part2.unwrap().min <= report.part2.unwrap().max);
+        assert!(matches!(
+            bench(solver.as_ref(), "x\n", 5, &args),
+            Err(BenchError::Parse(_))
+        ));
+        let args = crate::cli::parse(["6"].map(String::from)).unwrap();
+        let solver = &crate::solver::registry()[&6];
+        assert!(matches!(
+            bench(solver.as_ref(), "abcabcabc\n", 2, &args),
+            Err(BenchError::Part(PartError::Unsolvable(_)))
+        ));
+    }
+}
@@ -0,0 +1,685 @@
+use crate::visualize::MAX_FPS;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::time::Duration;
+
+pub const USAGE: &str =
+    "usage: aoc2022 [DAYS] [--part 1|2] [--exclude DAYS] [--input PATH] [--bench N]
+               [--format text|json|csv] [--verify] [--jobs N] [--timeout SECS]
+               [--memory] [--watch] [--sample] [--trace PATH]
+               [--visualize SINK]
+       aoc2022 fetch [DAYS] [--exclude DAYS] [--session PATH] [--base-url URL]
+       aoc2022 submit --day N --part 1|2 [--input PATH] [--session PATH]
+               [--base-url URL]
+       aoc2022 scaffold N [--root DIR]
+
+  DAYS            comma separated list of days and ranges, e.g. 3,5,10-14
+                  (all days when omitted)
+  --part 1|2      only run the given part
+  --exclude DAYS  skip the given days, e.g. --exclude 15,16,19
+  --input PATH    read the puzzle input from PATH instead of
+                  src/inputs/aoc_N.input, '-' reads stdin (single day only)
+  --bench N       run every day N times, one day at a time, and print
+                  min/median/p95/max timings of parsing and both parts
+  --format FORMAT text (default), or json and csv with one record per day
+                  and part
+  --verify        check the answers against src/inputs/answers.txt, keyed by
+                  day, part and input file name
+  --jobs N        run on N threads, including the days' own parallel loops
+                  (one per CPU by default)
+  --timeout SECS  stop days still running after SECS seconds and report
+                  them as timed out (Ctrl-C stops the whole run the same way)
+  --memory        count the allocations, bytes allocated and peak live bytes
+                  of parsing and both parts, running one day at a time
+  --watch         solve the days again whenever their input file changes,
+                  showing what changed, until Ctrl-C
+  --sample        solve the examples of the puzzle texts instead of the
+                  inputs, checking them against their known answers
+  --trace PATH    write the intermediate steps the days trace, like the
+                  throws of day 11, to PATH as JSON lines
+  --visualize SINK
+                  show the grids of days 14, 17, 22, 23 and 24 as they change,
+                  animated on stderr with terminal[:FPS] (single day, 30
+                  frames a second by default, 1000 at most), or written to
+                  DIR as numbered images with ppm:DIR or pgm:DIR (greyscale)
+  fetch           download the inputs that aren't in src/inputs yet
+  submit          send the answer of the given day and part, unless it's
+                  known to be wrong already (see .submissions)
+  scaffold        create src/dayN.rs from src/template.rs, register it with
+                  the runner and add empty input and sample files
+  --root DIR      crate to scaffold the day in, like next year's copy of this
+                  one with its days removed (default .)
+  --day N         single day, same as DAYS
+  --session PATH  file with the session token, used when AOC_SESSION isn't
+                  set (default .session)
+  --base-url URL  site to talk to (default https://adventofcode.com)
+  -h, --help      print this message";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn number(&self) -> usize {
+        match self {
+            Part::One => 1,
+            Part::Two => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Pgm,
+}
+
+/// Where `--visualize` sends the frames.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visualize {
+    Terminal { fps: usize },
+    Images { format: ImageFormat, dir: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Solve,
+    Fetch,
+    Submit,
+    Scaffold,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub command: Command,
+    pub days: Vec<usize>,
+    pub part: Option<Part>,
+    pub input: Option<String>,
+    pub bench: Option<usize>,
+    pub format: Format,
+    pub verify: bool,
+    pub jobs: Option<usize>,
+    pub timeout: Option<Duration>,
+    pub memory: bool,
+    pub watch: bool,
+    pub sample: bool,
+    pub trace: Option<String>,
+    pub visualize: Option<Visualize>,
+    pub session: Option<String>,
+    pub base_url: Option<String>,
+    pub root: Option<String>,
+}
+
+impl Args {
+    pub fn runs_part(&self, part: Part) -> bool {
+        self.part.is_none() || self.part == Some(part)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CliError {
+    Help,
+    Usage(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Help => write!(f, "{USAGE}"),
+            CliError::Usage(msg) => write!(f, "error: {msg}\n\n{USAGE}"),
+        }
+    }
+}
+
+fn parse_day(s: &str) -> Result<usize, CliError> {
+    match s.trim().parse::<usize>() {
+        Ok(day @ 1..=25) => Ok(day),
+        _ => Err(CliError::Usage(format!("invalid day '{s}', expected 1-25"))),
+    }
+}
+
+fn parse_days(spec: &str) -> Result<BTreeSet<usize>, CliError> {
+    let mut days = BTreeSet::new();
+    for item in spec.split(',') {
+        match item.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                if from > to {
+                    return Err(CliError::Usage(format!("invalid day range '{item}'")));
+                }
+                days.extend(from..=to);
+            }
+            None => {
+                days.insert(parse_day(item)?);
+            }
+        }
+    }
+    Ok(days)
+}
+
+fn parse_part(s: &str) -> Result<Part, CliError> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(CliError::Usage(format!(
+            "invalid part '{s}', expected 1 or 2"
+        ))),
+    }
+}
+
+fn parse_format(s: &str) -> Result<Format, CliError> {
+    match s {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        "csv" => Ok(Format::Csv),
+        _ => Err(CliError::Usage(format!(
+            "invalid format '{s}', expected text, json or csv"
+        ))),
+    }
+}
+
+fn parse_count(option: &str, s: &str) -> Result<usize, CliError> {
+    match s.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(CliError::Usage(format!(
+            "invalid value '{s}' for {option}, expected a positive number"
+        ))),
+    }
+}
+
+fn parse_seconds(option: &str, s: &str) -> Result<Duration, CliError> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs > 0.0 && secs.is_finite() => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(CliError::Usage(format!(
+            "invalid value '{s}' for {option}, expected a positive number of seconds"
+        ))),
+    }
+}
+
+fn parse_visualize(s: &str) -> Result<Visualize, CliError> {
+    match s.split_once(':') {
+        None if s == "terminal" => Ok(Visualize::Terminal { fps: 30 }),
+        Some(("terminal", fps)) => match parse_count("--visualize terminal:FPS", fps)? {
+            n if n <= MAX_FPS => Ok(Visualize::Terminal { fps: n }),
+            _ => Err(CliError::Usage(format!(
+                "invalid value '{fps}' for --visualize terminal:FPS, expected at most {MAX_FPS}"
+            ))),
+        },
+        Some(("ppm", dir)) if !dir.is_empty() => Ok(Visualize::Images {
+            format: ImageFormat::Ppm,
+            dir: dir.to_string(),
+        }),
+        Some(("pgm", dir)) if !dir.is_empty() => Ok(Visualize::Images {
+            format: ImageFormat::Pgm,
+            dir: dir.to_string(),
+        }),
+        _ => Err(CliError::Usage(format!(
+            "invalid sink '{s}', expected terminal[:FPS], ppm:DIR or pgm:DIR"
+        ))),
+    }
+}
+
+fn option_value(option: &str, args: &mut impl Iterator<Item = String>) -> Result<String, CliError> {
+    args.next()
+        .ok_or_else(|| CliError::Usage(format!("missing value for {option}")))
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, CliError> {
+    let mut args = args.into_iter().peekable();
+    let command = match args.next_if(|arg| ["fetch", "submit", "scaffold"].contains(&arg.as_str()))
+    {
+        Some(arg) if arg == "fetch" => Command::Fetch,
+        Some(arg) if arg == "submit" => Command::Submit,
+        Some(_) => Command::Scaffold,
+        None => Command::Solve,
+    };
+    let mut days: Option<BTreeSet<usize>> = None;
+    let mut excluded = BTreeSet::new();
+    let mut part = None;
+    let mut input = None;
+    let mut bench = None;
+    let mut format = Format::Text;
+    let mut verify = false;
+    let mut jobs = None;
+    let mut timeout = None;
+    let mut memory = false;
+    let mut watch = false;
+    let mut sample = false;
+    let mut trace = None;
+    let mut visualize = None;
+    let mut session = None;
+    let mut base_url = None;
+    let mut root = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "--day" => {
+                let day = parse_day(&option_value(&arg, &mut args)?)?;
+                days.get_or_insert_with(BTreeSet::new).insert(day);
+            }
+            "--part" => part = Some(parse_part(&option_value(&arg, &mut args)?)?),
+            "--exclude" => excluded.extend(parse_days(&option_value(&arg, &mut args)?)?),
+            "--input" => input = Some(option_value(&arg, &mut args)?),
+            "--bench" => bench = Some(parse_count(&arg, &option_value(&arg, &mut args)?)?),
+            "--jobs" => jobs = Some(parse_count(&arg, &option_value(&arg, &mut args)?)?),
+            "--timeout" => timeout = Some(parse_seconds(&arg, &option_value(&arg, &mut args)?)?),
+            "--format" => format = parse_format(&option_value(&arg, &mut args)?)?,
+            "--verify" => verify = true,
+            "--memory" => memory = true,
+            "--watch" => watch = true,
+            "--sample" => sample = true,
+            "--trace" => trace = Some(option_value(&arg, &mut args)?),
+            "--visualize" => visualize = Some(parse_visualize(&option_value(&arg, &mut args)?)?),
+            "--session" => session = Some(option_value(&arg, &mut args)?),
+            "--base-url" => base_url = Some(option_value(&arg, &mut args)?),
+            "--root" => root = Some(option_value(&arg, &mut args)?),
+            a if a.starts_with('-') => {
+                return Err(CliError::Usage(format!("unknown option '{a}'")));
+            }
+            spec => days
+                .get_or_insert_with(BTreeSet::new)
+                .extend(parse_days(spec)?),
+        }
+    }
+
+    let days: Vec<usize> = days
+        .unwrap_or_else(|| (1..=25).collect())
+        .difference(&excluded)
+        .copied()
+        .collect();
+    if days.is_empty() {
+        return Err(CliError::Usage(
+            "--exclude leaves no days to run".to_string(),
+        ));
+    }
+    if input.is_some() && days.len() != 1 {
+        return Err(CliError::Usage("--input needs exactly one day".to_string()));
+    }
+    if bench.is_some() && format != Format::Text {
+        return Err(CliError::Usage(
+            "--bench only supports the text format".to_string(),
+        ));
+    }
+    let run_options = bench.is_some()
+        || format != Format::Text
+        || verify
+        || jobs.is_some()
+        || timeout.is_some()
+        || memory
+        || watch
+        || sample
+        || trace.is_some()
+        || visualize.is_some();
+    match command {
+        Command::Fetch if run_options || part.is_some() || input.is_some() => {
+            return Err(CliError::Usage(
+                "fetch only takes DAYS, --exclude, --session and --base-url".to_string(),
+            ));
+        }
+        Command::Submit if run_options || days.len() != 1 || part.is_none() => {
+            return Err(CliError::Usage(
+                "submit needs a single --day and --part, and only takes --input, --session \
+                 and --base-url besides"
+                    .to_string(),
+            ));
+        }
+        Command::Scaffold
+            if run_options
+                || part.is_some()
+                || input.is_some()
+                || session.is_some()
+                || base_url.is_some()
+                || days.len() != 1 =>
+        {
+            return Err(CliError::Usage(
+                "scaffold needs a single day and only takes --root".to_string(),
+            ));
+        }
+        Command::Solve if session.is_some() || base_url.is_some() => {
+            return Err(CliError::Usage(
+                "--session and --base-url only work with fetch and submit".to_string(),
+            ));
+        }
+        Command::Solve | Command::Fetch | Command::Submit if root.is_some() => {
+            return Err(CliError::Usage(
+                "--root only works with scaffold".to_string(),
+            ));
+        }
+        _ => {}
+    }
+    if bench.is_some() && (verify || memory || trace.is_some() || visualize.is_some()) {
+        return Err(CliError::Usage(
+            "--bench can't be combined with --verify, --memory, --trace or --visualize".to_string(),
+        ));
+    }
+    // Frames of days running side by side would be drawn over each other.
+    if matches!(visualize, Some(Visualize::Terminal { .. })) && days.len() != 1 {
+        return Err(CliError::Usage(
+            "--visualize terminal needs exactly one day".to_string(),
+        ));
+    }
+    if watch && (bench.is_some() || format != Format::Text || input.as_deref() == Some("-")) {
+        return Err(CliError::Usage(
+            "--watch only works with the text format and input files".to_string(),
+        ));
+    }
+    if sample && (input.is_some() || bench.is_some() || verify || memory || watch) {
+        return Err(CliError::Usage(
+            "--sample can't be combined with --input, --bench, --verify, --memory or --watch"
+                .to_string(),
+        ));
+    }
+
+    Ok(Args {
+        command,
+        days,
+        part,
+        input,
+        bench,
+        format,
+        verify,
+        jobs,
+        timeout,
+        memory,
+        watch,
+        sample,
+        trace,
+        visualize,
+        session,
+        base_url,
+        root,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(s: &str) -> Result<Args, CliError> {
+        parse(s.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(
+            parse_str("").unwrap().days,
+            (1..=25).collect::<Vec<usize>>()
+        );
+        assert_eq!(parse_str("7").unwrap().days, vec![7]);
+        assert_eq!(
+            parse_str("3,5,10-14").unwrap().days,
+            vec![3, 5, 10, 11, 12, 13, 14]
+        );
+        assert_eq!(parse_str("5 1-2").unwrap().days, vec![1, 2, 5]);
+        assert_eq!(
+            parse_str("10-20 --exclude 16,19,15").unwrap().days,
+            vec![10, 11, 12, 13, 14, 17, 18, 20]
+        );
+        assert_eq!(parse_str("--exclude 2-25").unwrap().days, vec![1]);
+        for bad in [
+            "--exclude 1-25",
+            "3,5 --exclude 1-10",
+            "fetch 7 --exclude 7",
+        ] {
+            assert!(matches!(parse_str(bad), Err(CliError::Usage(_))), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_parse_part() {
+        assert_eq!(parse_str("1").unwrap().part, None);
+        assert_eq!(parse_str("1 --part 2").unwrap().part, Some(Part::Two));
+        assert!(parse_str("--part 1").unwrap().runs_part(Part::One));
+        assert!(!parse_str("--part 1").unwrap().runs_part(Part::Two));
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!(parse_str("1").unwrap().input, None);
+        assert_eq!(
+            parse_str("12 --input src/inputs/aoc_12_sample.input")
+                .unwrap()
+                .input,
+            Some("src/inputs/aoc_12_sample.input".to_string())
+        );
+        assert_eq!(
+            parse_str("--input - 5").unwrap().input,
+            Some("-".to_string())
+        );
+        assert!(matches!(parse_str("--input x"), Err(CliError::Usage(_))));
+        assert!(matches!(
+            parse_str("1,2 --input x"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(parse_str("1 --input"), Err(CliError::Usage(_))));
+    }
+
+    #[test]
+    fn test_parse_bench() {
+        assert_eq!(parse_str("1").unwrap().bench, None);
+        assert_eq!(parse_str("--bench 10 16").unwrap().bench, Some(10));
+        assert!(matches!(parse_str("--bench 0"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("--bench x"), Err(CliError::Usage(_))));
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse_str("1").unwrap().format, Format::Text);
+        assert_eq!(parse_str("--format json").unwrap().format, Format::Json);
+        assert_eq!(parse_str("--format csv 3").unwrap().format, Format::Csv);
+        assert!(matches!(parse_str("--format xml"), Err(CliError::Usage(_))));
+        assert!(matches!(
+            parse_str("--format csv --bench 3"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_verify() {
+        assert!(!parse_str("1").unwrap().verify);
+        assert!(parse_str("1-5 --verify --format json").unwrap().verify);
+        assert!(matches!(
+            parse_str("--verify --bench 3"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_jobs_timeout() {
+        let args = parse_str("1").unwrap();
+        assert_eq!((args.jobs, args.timeout), (None, None));
+        let args = parse_str("--jobs 2 --timeout 1.5").unwrap();
+        assert_eq!(args.jobs, Some(2));
+        assert_eq!(args.timeout, Some(Duration::from_millis(1500)));
+        assert!(matches!(parse_str("--jobs 0"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("--timeout -1"), Err(CliError::Usage(_))));
+        assert!(matches!(
+            parse_str("--timeout inf"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("fetch 1 --jobs 2"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_memory() {
+        assert!(!parse_str("1").unwrap().memory);
+        assert!(parse_str("1-3 --memory --format csv").unwrap().memory);
+        assert!(matches!(
+            parse_str("--memory --bench 3"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("submit --day 1 --part 1 --memory"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert!(!parse_str("1").unwrap().watch);
+        assert!(parse_str("5 --watch --input x.txt --verify").unwrap().watch);
+        assert!(matches!(
+            parse_str("5 --watch --input -"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("--watch --format json"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("fetch --watch"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_sample() {
+        assert!(!parse_str("1").unwrap().sample);
+        assert!(parse_str("15-17 --sample --format json").unwrap().sample);
+        assert!(matches!(
+            parse_str("5 --sample --input x.txt"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("--sample --verify"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("scaffold 26 --sample"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_trace() {
+        assert_eq!(parse_str("1").unwrap().trace, None);
+        assert_eq!(
+            parse_str("11 --trace day11.jsonl").unwrap().trace,
+            Some("day11.jsonl".to_string())
+        );
+        assert!(matches!(parse_str("11 --trace"), Err(CliError::Usage(_))));
+        assert!(matches!(
+            parse_str("11 --trace x --bench 3"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_visualize() {
+        assert_eq!(parse_str("14").unwrap().visualize, None);
+        assert_eq!(
+            parse_str("14 --visualize terminal").unwrap().visualize,
+            Some(Visualize::Terminal { fps: 30 })
+        );
+        assert_eq!(
+            parse_str("17 --visualize terminal:60").unwrap().visualize,
+            Some(Visualize::Terminal { fps: 60 })
+        );
+        assert_eq!(
+            parse_str("14,23 --visualize pgm:frames").unwrap().visualize,
+            Some(Visualize::Images {
+                format: ImageFormat::Pgm,
+                dir: "frames".to_string()
+            })
+        );
+        for bad in [
+            "14 --visualize",
+            "14 --visualize gif:frames",
+            "14 --visualize ppm:",
+            "14 --visualize terminal:0",
+            "14 --visualize terminal:1001",
+            "14 --visualize terminal:4294967296",
+            "14,23 --visualize terminal",
+            "14 --visualize ppm:x --bench 3",
+        ] {
+            assert!(matches!(parse_str(bad), Err(CliError::Usage(_))), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_parse_fetch() {
+        assert_eq!(parse_str("1").unwrap().command, Command::Solve);
+        let args = parse_str("fetch 1-3 --session s.txt --base-url http://localhost:8000").unwrap();
+        assert_eq!(args.command, Command::Fetch);
+        assert_eq!(args.days, vec![1, 2, 3]);
+        assert_eq!(args.session, Some("s.txt".to_string()));
+        assert_eq!(args.base_url, Some("http://localhost:8000".to_string()));
+        assert_eq!(parse_str("fetch").unwrap().days.len(), 25);
+        assert!(matches!(
+            parse_str("fetch 1 --part 1"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("1 --base-url http://localhost"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(parse_str("1 fetch"), Err(CliError::Usage(_))));
+    }
+
+    #[test]
+    fn test_parse_submit() {
+        let args = parse_str("submit --day 7 --part 2 --input x.txt").unwrap();
+        assert_eq!(args.command, Command::Submit);
+        assert_eq!(args.days, vec![7]);
+        assert_eq!(args.part, Some(Part::Two));
+        assert_eq!(args.input, Some("x.txt".to_string()));
+        assert_eq!(parse_str("--day 3").unwrap().days, vec![3]);
+        assert!(matches!(
+            parse_str("submit --day 7"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("submit --day 7 --day 8 --part 1"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            parse_str("submit --day 7 --part 1 --verify"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_scaffold() {
+        let args = parse_str("scaffold 12").unwrap();
+        assert_eq!(args.command, Command::Scaffold);
+        assert_eq!(args.days, vec![12]);
+        assert_eq!(args.root, None);
+        let args = parse_str("scaffold 1 --root ../aoc2023").unwrap();
+        assert_eq!(args.root, Some("../aoc2023".to_string()));
+        assert!(matches!(
+            parse_str("1 --root ../aoc2023"),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(parse_str("scaffold"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("scaffold 1-2"), Err(CliError::Usage(_))));
+        assert!(matches!(
+            parse_str("scaffold 3 --part 1"),
+            Err(CliError::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_str("--help"), Err(CliError::Help));
+        assert!(matches!(parse_str("26"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("0"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("1x"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("5-3"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("--part 3"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("--part"), Err(CliError::Usage(_))));
+        assert!(matches!(parse_str("--verbose"), Err(CliError::Usage(_))));
+    }
+}
@@ -0,0 +1,244 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use ureq::http::Response;
+use ureq::{Agent, Body};
+
+pub const BASE_URL: &str = "https://adventofcode.com";
+pub const SESSION_VAR: &str = "AOC_SESSION";
+pub const SESSION_FILE: &str = ".session";
+
+const YEAR: usize = 2022;
+const USER_AGENT: &str = "github.com/rwfpl/rewolf-aoc2022";
+
+/// Session token, taken from `var` (the value of `AOC_SESSION`) when set, or
+/// else from the content of `file`.
+pub fn session_token(var: Option<String>, file: &str) -> Result<String, String> {
+    let token = match var.filter(|v| !v.trim().is_empty()) {
+        Some(token) => token,
+        None => fs::read_to_string(file).map_err(|e| {
+            format!("can't read the session token from {file}: {e}, set {SESSION_VAR} or create the file")
+        })?,
+    };
+    match token.trim() {
+        "" => Err(format!("empty session token in {file}")),
+        token => Ok(token.to_string()),
+    }
+}
+
+/// Client of the puzzle site, authenticated with the session cookie.
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str) -> Self {
+        let agent = Agent::config_builder()
+            .http_status_as_error(false)
+            .user_agent(USER_AGENT)
+            .timeout_global(Some(Duration::from_secs(30)))
+            .build()
+            .into();
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            agent,
+        }
+    }
+
+    fn read(&self, response: Result<Response<Body>, ureq::Error>) -> Result<(u16, String), String> {
+        let mut response =
+            response.map_err(|e| format!("request to {} failed: {e}", self.base_url))?;
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| format!("can't read response: {e}"))?;
+        Ok((response.status().as_u16(), body))
+    }
+
+    fn get(&self, path: &str) -> Result<(u16, String), String> {
+        self.read(
+            self.agent
+                .get(format!("{}{path}", self.base_url))
+                .header("Cookie", format!("session={}", self.session))
+                .call(),
+        )
+    }
+
+    fn post(&self, path: &str, form: &[(&str, &str)]) -> Result<(u16, String), String> {
+        self.read(
+            self.agent
+                .post(format!("{}{path}", self.base_url))
+                .header("Cookie", format!("session={}", self.session))
+                .send_form(form.iter().copied()),
+        )
+    }
+
+    pub fn input(&self, day: usize) -> Result<String, String> {
+        match self.get(&format!("/{YEAR}/day/{day}/input"))? {
+            (200, input) => Ok(input),
+            (404, _) => Err(format!("the input of day {day} isn't available")),
+            (400 | 500, _) => Err("the session token was rejected".to_string()),
+            (status, _) => Err(format!("can't fetch the input of day {day}: HTTP {status}")),
+        }
+    }
+
+    /// Posts `answer` to part `part` of `day` and returns the page telling
+    /// whether it was right.
+    pub fn answer(&self, day: usize, part: usize, answer: &str) -> Result<String, String> {
+        let level = part.to_string();
+        match self.post(
+            &format!("/{YEAR}/day/{day}/answer"),
+            &[("level", &level), ("answer", answer)],
+        )? {
+            (200, page) => Ok(page),
+            (400 | 500, _) => Err("the session token was rejected".to_string()),
+            (status, _) => Err(format!(
+                "can't submit the answer of day {day}: HTTP {status}"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Fetched {
+    Cached,
+    Downloaded,
+}
+
+/// Downloads the input of `day` to `path`, unless it's already there. Inputs
+/// never change, so a cached one is never downloaded again. Empty files, like
+/// the ones left by `scaffold`, don't count.
+pub fn fetch(client: &Client, day: usize, path: &str) -> Result<Fetched, String> {
+    let path = Path::new(path);
+    if path.metadata().is_ok_and(|m| m.len() > 0) {
+        return Ok(Fetched::Cached);
+    }
+    let input = client.input(day)?;
+    // Written aside first, so an interrupted download doesn't leave a
+    // truncated input behind that would count as cached.
+    let partial = path.with_extension("partial");
+    fs::write(&partial, input)
+        .and_then(|_| fs::rename(&partial, path))
+        .map_err(|e| format!("can't write {}: {e}", path.display()))?;
+    Ok(Fetched::Downloaded)
+}
+
+/// Local stand-in for the puzzle site.
+#[cfg(test)]
+pub mod mock {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::thread::{self, JoinHandle};
+
+    /// Answers one request per response and returns the requests it got.
+    pub fn serve(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    request += &line;
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let mut content = vec![0; length];
+                reader.read_exact(&mut content).unwrap();
+                request += &String::from_utf8(content).unwrap();
+                requests.push(request);
+                write!(
+                    reader.get_mut(),
+                    "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+            requests
+        });
+        (url, handle)
+    }
+
+    pub fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2022-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let _ = fs::remove_file(&path);
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::{serve, temp_path};
+    use super::*;
+
+    #[test]
+    fn test_session_token() {
+        let file = temp_path("session");
+        fs::write(&file, "abc123\n").unwrap();
+        let file = file.to_str().unwrap();
+        assert_eq!(session_token(None, file), Ok("abc123".to_string()));
+        assert_eq!(
+            session_token(Some("xyz".to_string()), file),
+            Ok("xyz".to_string())
+        );
+        assert_eq!(
+            session_token(Some(" ".to_string()), file),
+            Ok("abc123".to_string())
+        );
+        assert!(session_token(None, "no/such/file").is_err());
+    }
+
+    #[test]
+    fn test_fetch() {
+        let (url, server) = serve(vec![(200, "1\n2\n")]);
+        let client = Client::new(&format!("{url}/"), "abc123");
+        let path = temp_path("aoc_1.input");
+        fs::write(&path, "").unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(fetch(&client, 1, path), Ok(Fetched::Downloaded));
+        assert_eq!(fs::read_to_string(path).unwrap(), "1\n2\n");
+        // The server is gone after the first request, so this must not hit it.
+        assert_eq!(fetch(&client, 1, path), Ok(Fetched::Cached));
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /2022/day/1/input HTTP/1.1"));
+        assert!(requests[0]
+            .to_lowercase()
+            .contains("cookie: session=abc123"));
+    }
+
+    #[test]
+    fn test_fetch_errors() {
+        let (url, server) = serve(vec![(404, "Not Found"), (400, "Puzzle inputs differ")]);
+        let client = Client::new(&url, "abc123");
+        let path = temp_path("aoc_26.input");
+        let path = path.to_str().unwrap();
+        assert_eq!(
+            fetch(&client, 26, path),
+            Err("the input of day 26 isn't available".to_string())
+        );
+        assert_eq!(
+            fetch(&client, 26, path),
+            Err("the session token was rejected".to_string())
+        );
+        assert!(!Path::new(path).exists());
+        server.join().unwrap();
+    }
+}
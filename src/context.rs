@@ -0,0 +1,227 @@
+use crate::cli::Part;
+use crate::trace::{Event, Trace};
+use crate::visualize::{Frame, Visualizer};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Returned by a part that gave up because its run was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+/// How far a long running part got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// What `done` counts, like rows or nodes.
+    pub unit: &'static str,
+    pub done: u64,
+    pub total: Option<u64>,
+    /// Best answer found so far, for searches.
+    pub best: Option<i64>,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.total {
+            Some(total) => write!(f, "{}/{total} {}", self.done, self.unit)?,
+            None => write!(f, "{} {}", self.done, self.unit)?,
+        }
+        match self.best {
+            Some(best) => write!(f, ", best {best}"),
+            None => Ok(()),
+        }
+    }
+}
+
+type ProgressFn = dyn Fn(Progress) + Send + Sync;
+
+/// Handed to the parts of a day, so long searches can report how far they
+/// got and stop early once the run is cancelled, and days can trace their
+/// intermediate steps with `--trace` and show their grids with `--visualize`.
+#[derive(Clone, Default)]
+pub struct Context {
+    // Own flag last, preceded by the flags of the contexts it was made from.
+    cancelled: Vec<Arc<AtomicBool>>,
+    progress: Option<Arc<ProgressFn>>,
+    trace: Option<Arc<Trace>>,
+    visualizer: Option<Arc<Visualizer>>,
+    // Day and part the traced events and frames come from.
+    part: Option<(usize, Part)>,
+}
+
+impl Context {
+    /// Context cancelled together with this one, or on its own, reporting
+    /// progress to `progress`.
+    pub fn child(&self, progress: impl Fn(Progress) + Send + Sync + 'static) -> Context {
+        let mut cancelled = self.cancelled.clone();
+        cancelled.push(Arc::default());
+        Context {
+            cancelled,
+            progress: Some(Arc::new(progress)),
+            trace: self.trace.clone(),
+            visualizer: self.visualizer.clone(),
+            part: self.part,
+        }
+    }
+
+    /// Context tracing the events of the parts solved with it to `trace`.
+    pub fn traced(&self, trace: Arc<Trace>) -> Context {
+        Context {
+            trace: Some(trace),
+            ..self.clone()
+        }
+    }
+
+    /// Context showing the frames of the parts solved with it on `visualizer`.
+    pub fn visualized(&self, visualizer: Arc<Visualizer>) -> Context {
+        Context {
+            visualizer: Some(visualizer),
+            ..self.clone()
+        }
+    }
+
+    /// Context solving `part` of `day`, which the traced events and frames
+    /// are tagged with.
+    pub fn in_part(&self, day: usize, part: Part) -> Context {
+        Context {
+            part: Some((day, part)),
+            ..self.clone()
+        }
+    }
+
+    pub fn cancel(&self) {
+        match self.cancelled.last() {
+            Some(flag) => flag.store(true, Ordering::Relaxed),
+            None => panic!("the default context can't be cancelled"),
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+            .iter()
+            .any(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    pub fn check(&self) -> Result<(), Cancelled> {
+        match self.is_cancelled() {
+            true => Err(Cancelled),
+            false => Ok(()),
+        }
+    }
+
+    pub fn progress(&self, progress: Progress) {
+        if let Some(f) = &self.progress {
+            f(progress);
+        }
+    }
+
+    pub fn is_tracing(&self) -> bool {
+        self.trace.is_some() && self.part.is_some()
+    }
+
+    /// Traces the event made by `event`, which is only called when tracing,
+    /// so untraced runs don't pay for it.
+    pub fn trace(&self, event: impl FnOnce() -> Event) {
+        if let (Some(trace), Some((day, part))) = (&self.trace, self.part) {
+            trace.write(day, part, &event());
+        }
+    }
+
+    pub fn is_visualizing(&self) -> bool {
+        self.visualizer.is_some() && self.part.is_some()
+    }
+
+    /// Shows the frame made by `frame`, which is only called when
+    /// visualizing.
+    pub fn frame(&self, frame: impl FnOnce() -> Frame) {
+        if let (Some(visualizer), Some((day, part))) = (&self.visualizer, self.part) {
+            visualizer.show(day, part, &frame());
+        }
+    }
+}
+
+/// Work counter shared by the threads of a part, reporting its progress
+/// every `every` units and checking for cancellation as it goes.
+pub struct Counter<'a> {
+    ctx: &'a Context,
+    unit: &'static str,
+    total: Option<u64>,
+    every: u64,
+    done: AtomicU64,
+    best: AtomicI64,
+}
+
+impl<'a> Counter<'a> {
+    pub fn new(ctx: &'a Context, unit: &'static str, total: Option<u64>, every: u64) -> Self {
+        Counter {
+            ctx,
+            unit,
+            total,
+            every,
+            done: AtomicU64::new(0),
+            best: AtomicI64::new(i64::MIN),
+        }
+    }
+
+    pub fn add(&self, n: u64) -> Result<(), Cancelled> {
+        let before = self.done.fetch_add(n, Ordering::Relaxed);
+        if (before + n) / self.every != before / self.every {
+            let best = self.best.load(Ordering::Relaxed);
+            self.ctx.progress(Progress {
+                unit: self.unit,
+                done: before + n,
+                total: self.total,
+                best: (best != i64::MIN).then_some(best),
+            });
+        }
+        self.ctx.check()
+    }
+
+    pub fn best(&self, value: i64) {
+        self.best.fetch_max(value, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_cancel() {
+        let root = Context::default();
+        assert_eq!(root.check(), Ok(()));
+        let run = root.child(|_| {});
+        let day = run.child(|_| {});
+        day.cancel();
+        assert_eq!(day.check(), Err(Cancelled));
+        assert!(!run.is_cancelled());
+        let day = run.child(|_| {});
+        run.cancel();
+        assert!(day.is_cancelled());
+    }
+
+    #[test]
+    fn test_counter() {
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let shared = reported.clone();
+        let ctx = Context::default().child(move |p| shared.lock().unwrap().push(p.to_string()));
+        let rows = Counter::new(&ctx, "rows", Some(10), 4);
+        (0..3).for_each(|_| rows.add(1).unwrap());
+        rows.best(7);
+        rows.best(5);
+        (0..7).for_each(|_| rows.add(1).unwrap());
+        assert_eq!(
+            *reported.lock().unwrap(),
+            ["4/10 rows, best 7", "8/10 rows, best 7"]
+        );
+        ctx.cancel();
+        assert_eq!(rows.add(1), Err(Cancelled));
+    }
+}
@@ -1,35 +1,104 @@
-use std::{collections::BTreeSet, fs};
-
-fn solution() -> (i32, i32) {
-    let mut current_calories = 0;
-    let maxes: BTreeSet<i32> = fs::read_to_string("src/inputs/aoc_1.input")
-        .unwrap()
-        .lines()
-        .map(|line| {
-            if line.is_empty() {
-                let r = current_calories;
-                current_calories = 0;
-                r
-            } else {
-                current_calories += line.parse::<i32>().unwrap();
-                0
-            }
-        })
-        .collect();
-    (*maxes.last().unwrap(), maxes.iter().rev().take(3).sum())
+use crate::context::Context;
+use crate::parse::{blocks, normalize, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartError, PartResult, Sample, Solver};
+
+// Calories carried by each elf, from the fewest.
+pub fn parse(input: &str) -> ParseResult<Vec<i32>> {
+    let input = &*normalize(input, Normalize::default());
+    let mut maxes = blocks(input)
+        .map(|elf| elf.iter().map(|line| line.number::<i32>(line.text)).sum())
+        .collect::<ParseResult<Vec<i32>>>()?;
+    if maxes.is_empty() {
+        return Err(ParseError::new(1, 1, "no elves"));
+    }
+    maxes.sort();
+    Ok(maxes)
+}
+
+pub fn part1(maxes: &[i32]) -> Option<i32> {
+    maxes.last().copied()
+}
+
+// Only with at least three elves.
+pub fn part2(maxes: &[i32]) -> Option<i32> {
+    (maxes.len() >= 3).then(|| maxes.iter().rev().take(3).sum())
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution();
-    (p1.to_string(), p2.to_string())
+pub struct Day1;
+
+impl Solver for Day1 {
+    type Input = Vec<i32>;
+
+    fn day(&self) -> usize {
+        1
+    }
+
+    fn title(&self) -> &'static str {
+        "Calorie Counting"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, maxes: &Self::Input, _ctx: &Context) -> PartResult {
+        part1(maxes)
+            .map(|calories| calories.to_string())
+            .ok_or_else(|| PartError::Unsolvable("no elves".to_string()))
+    }
+
+    fn part2(&self, maxes: &Self::Input, _ctx: &Context) -> PartResult {
+        part2(maxes)
+            .map(|calories| calories.to_string())
+            .ok_or_else(|| PartError::Unsolvable("fewer than three elves".to_string()))
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_1_sample.input").part1("24000").part2("45000")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    proptest! {
+        // Equal totals and a last elf without a blank line after it count too.
+        #[test]
+        fn test_calories_match_naive(
+            elves in prop::collection::vec(prop::collection::vec(1..10i32, 1..4), 1..10)
+        ) {
+            let input = elves
+                .iter()
+                .map(|elf| elf.iter().map(|c| c.to_string()).collect::<Vec<_>>().join("\n"))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            let maxes = parse(&input).unwrap();
+            let mut totals = elves.iter().map(|elf| elf.iter().sum()).collect::<Vec<i32>>();
+            totals.sort_by(|a, b| b.cmp(a));
+            prop_assert_eq!(part1(&maxes), Some(totals[0]));
+            let top3 = (totals.len() >= 3).then(|| totals.iter().take(3).sum::<i32>());
+            prop_assert_eq!(part2(&maxes), top3);
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution(), (72017, 212520));
+        let maxes = parse(&fs::read_to_string("src/inputs/aoc_1.input").unwrap()).unwrap();
+        assert_answer(1, Part::One, "aoc_1.input", part1(&maxes).unwrap());
+        assert_answer(1, Part::Two, "aoc_1.input", part2(&maxes).unwrap());
+    }
+
+    #[test]
+    fn test_too_few_elves() {
+        assert_eq!(parse("\n").unwrap_err(), ParseError::new(1, 1, "no elves"));
+        let maxes = parse("1\n\n2\n").unwrap();
+        assert_eq!(part1(&maxes), Some(2));
+        assert_eq!(part2(&maxes), None);
     }
 }
@@ -1,5 +1,9 @@
-use std::fs;
-use std::iter;
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::trace::Event;
 
 fn get_signal(cycle: i32, regx: i32) -> i32 {
     if ((cycle - 20) % 40) == 0 {
@@ -9,94 +13,179 @@ fn get_signal(cycle: i32, regx: i32) -> i32 {
     }
 }
 
-fn crt_to_string(crt: &[Vec<char>]) -> String {
-    crt.iter()
-        .map(|row| iter::once(&'\n').chain(row.iter()).collect::<String>())
-        .collect::<String>()
-}
-
 fn cycle_to_pos(cycle: i32) -> (usize, usize) {
     let cycle = cycle as usize;
-    ((cycle - 1) / 40, (cycle - 1) % 40)
+    ((cycle - 1) % 40, (cycle - 1) / 40)
 }
 
-fn update_pixel(crt: &mut [Vec<char>], cycle: i32, regx: i32) {
+fn update_pixel(crt: &mut Grid<char>, cycle: i32, regx: i32) {
     let pos = cycle_to_pos(cycle);
-    if pos.1 as i32 >= regx - 1 && pos.1 as i32 <= regx + 1 {
-        crt[pos.0][pos.1] = '#';
+    if pos.0 as i32 >= regx - 1 && pos.0 as i32 <= regx + 1 {
+        crt[pos] = '#';
     }
 }
 
-fn solution(filename: &str) -> (i32, String) {
+#[derive(Debug)]
+pub enum Instruction {
+    Noop,
+    Addx(i32),
+}
+
+impl TryFrom<Line<'_>> for Instruction {
+    type Error = ParseError;
+
+    fn try_from(line: Line) -> ParseResult<Self> {
+        match line.text {
+            "noop" => Ok(Instruction::Noop),
+            addx if addx.starts_with("addx ") => Ok(Instruction::Addx(line.number(&addx[5..])?)),
+            _ => Err(line.error(line.text, format!("unknown instruction '{}'", line.text))),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> ParseResult<Vec<Instruction>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input).map(Instruction::try_from).collect()
+}
+
+// Calls `on_cycle` with the cycle number and the value of the X register
+// during every cycle of the program, tracing the cycles as it goes.
+fn execute<F>(program: &[Instruction], ctx: &Context, mut on_cycle: F)
+where
+    F: FnMut(i32, i32),
+{
     let mut cycle: i32 = 1;
     let mut regx: i32 = 1;
-    let mut crt: Vec<Vec<char>> = vec![vec!['.'; 40]; 6];
-    let sum: i32 = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| match line {
-            "noop" => {
-                let signal = get_signal(cycle, regx);
-                update_pixel(&mut crt, cycle, regx);
-                cycle += 1;
-                signal
-            }
-            addx if addx.starts_with("addx ") => {
-                let signal1 = get_signal(cycle, regx);
-                update_pixel(&mut crt, cycle, regx);
-                cycle += 1;
-                let signal2 = get_signal(cycle, regx);
-                update_pixel(&mut crt, cycle, regx);
-                cycle += 1;
-                regx += line[5..].parse::<i32>().unwrap();
-                signal1 + signal2
-            }
-            _ => {
-                panic!("error: {line}")
-            }
-        })
-        .sum();
-    (sum, crt_to_string(&crt))
+    let mut tick = |cycle: &mut i32, regx: i32| {
+        ctx.trace(|| Event::new("cycle").with("cycle", *cycle).with("x", regx));
+        on_cycle(*cycle, regx);
+        *cycle += 1;
+    };
+    program.iter().for_each(|instruction| match instruction {
+        Instruction::Noop => tick(&mut cycle, regx),
+        Instruction::Addx(v) => {
+            tick(&mut cycle, regx);
+            tick(&mut cycle, regx);
+            regx += v;
+        }
+    });
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_10.input");
-    (p1.to_string(), p2)
+pub fn part1(program: &[Instruction], ctx: &Context) -> i32 {
+    let mut sum = 0;
+    execute(program, ctx, |cycle, regx| sum += get_signal(cycle, regx));
+    sum
+}
+
+pub fn part2(program: &[Instruction], ctx: &Context) -> String {
+    let mut crt = Grid::new(40, 6, '.');
+    execute(program, ctx, |cycle, regx| {
+        update_pixel(&mut crt, cycle, regx)
+    });
+    format!("\n{crt}")
+}
+
+pub struct Day10;
+
+impl Solver for Day10 {
+    type Input = Vec<Instruction>;
+
+    fn day(&self) -> usize {
+        10
+    }
+
+    fn title(&self) -> &'static str {
+        "Cathode-Ray Tube"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, program: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(part1(program, ctx).to_string())
+    }
+
+    fn part2(&self, program: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(part2(program, ctx))
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_10_sample.input").part1("13140").part2(concat!(
+            "\n##..##..##..##..##..##..##..##..##..##..",
+            "\n###...###...###...###...###...###...###.",
+            "\n####....####....####....####....####....",
+            "\n#####.....#####.....#####.....#####.....",
+            "\n######......######......######......####",
+            "\n#######.......#######.......#######....."
+        ))]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Value of X during each cycle, from the first.
+    fn naive_cycles(program: &[Option<i32>]) -> Vec<i32> {
+        let mut x = 1;
+        let mut cycles = Vec::new();
+        for instruction in program {
+            cycles.push(x);
+            if let Some(v) = instruction {
+                cycles.push(x);
+                x += v;
+            }
+        }
+        cycles
+    }
+
+    proptest! {
+        // Short enough for the 240 pixels of the screen.
+        #[test]
+        fn test_cpu_matches_naive(
+            program in prop::collection::vec(prop::option::of(-5..6i32), 1..120)
+        ) {
+            let input = program
+                .iter()
+                .map(|instruction| match instruction {
+                    Some(v) => format!("addx {v}\n"),
+                    None => "noop\n".to_string(),
+                })
+                .collect::<String>();
+            let parsed = parse(&input).unwrap();
+            let cycles = naive_cycles(&program);
+            let strength = (20..=cycles.len())
+                .step_by(40)
+                .map(|cycle| cycle as i32 * cycles[cycle - 1])
+                .sum::<i32>();
+            let screen = (0..240)
+                .map(|i| match cycles.get(i) {
+                    Some(x) if (x - (i % 40) as i32).abs() <= 1 => '#',
+                    _ => '.',
+                })
+                .collect::<Vec<_>>()
+                .chunks(40)
+                .map(|row| format!("\n{}", row.iter().collect::<String>()))
+                .collect::<String>();
+            let ctx = Context::default();
+            prop_assert_eq!(part1(&parsed, &ctx), strength);
+            prop_assert_eq!(part2(&parsed, &ctx), screen);
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(
-            solution("src/inputs/aoc_10_sample.input"),
-            (
-                13140,
-                "
-##..##..##..##..##..##..##..##..##..##..
-###...###...###...###...###...###...###.
-####....####....####....####....####....
-#####.....#####.....#####.....#####.....
-######......######......######......####
-#######.......#######.......#######....."
-                    .to_owned()
-            )
-        );
-        assert_eq!(
-            solution("src/inputs/aoc_10.input"),
-            (
-                16480,
-                "
-###..#....####.####.#..#.#....###..###..
-#..#.#....#....#....#..#.#....#..#.#..#.
-#..#.#....###..###..#..#.#....#..#.###..
-###..#....#....#....#..#.#....###..#..#.
-#....#....#....#....#..#.#....#....#..#.
-#....####.####.#.....##..####.#....###.."
-                    .to_owned()
-            )
-        );
+        let ctx = Context::default();
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_10_sample.input").unwrap()).unwrap();
+        let program = parse(&fs::read_to_string("src/inputs/aoc_10.input").unwrap()).unwrap();
+        assert_answer(10, Part::One, "aoc_10_sample.input", part1(&sample, &ctx));
+        assert_answer(10, Part::Two, "aoc_10_sample.input", part2(&sample, &ctx));
+        assert_answer(10, Part::One, "aoc_10.input", part1(&program, &ctx));
+        assert_answer(10, Part::Two, "aoc_10.input", part2(&program, &ctx));
     }
 }
@@ -1,211 +1,284 @@
-use itertools::Itertools;
-use regex::Regex;
-use std::{collections::BTreeSet, fs};
-
-#[derive(Debug)]
-enum Operator {
-    None,
-    Mul,
-    Add,
-}
-
-impl From<&str> for Operator {
-    fn from(s: &str) -> Self {
-        match s {
-            "*" => Operator::Mul,
-            "+" => Operator::Add,
-            _ => Operator::None,
-        }
-    }
-}
-
-#[derive(Debug)]
-enum Value {
-    Old,
-    Int(i64),
-}
-
-impl From<&str> for Value {
-    fn from(s: &str) -> Self {
-        match s {
-            "old" => Value::Old,
-            _ => Value::Int(s.parse::<i64>().unwrap()),
-        }
-    }
-}
-
-impl Value {
-    fn get_or(&self, or: i64) -> i64 {
-        match self {
-            Value::Old => or,
-            Value::Int(v) => *v,
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Operation {
-    a: Value,
-    b: Value,
-    op: Operator,
-}
-
-impl Operation {
-    fn new(a: &str, b: &str, op: &str) -> Operation {
-        Operation {
-            a: Value::from(a),
-            b: Value::from(b),
-            op: Operator::from(op),
-        }
-    }
-
-    fn execute(&self, old: i64) -> i64 {
-        match self.op {
-            Operator::Add => self.a.get_or(old) + self.b.get_or(old),
-            Operator::Mul => self.a.get_or(old) * self.b.get_or(old),
-            _ => 0,
-        }
-    }
-}
-
-impl From<regex::Captures<'_>> for Operation {
-    fn from(c: regex::Captures) -> Self {
-        Operation::new(
-            c.name("a").unwrap().as_str(),
-            c.name("b").unwrap().as_str(),
-            c.name("op").unwrap().as_str(),
-        )
-    }
-}
-
-#[derive(Debug)]
-struct Monkey {
-    items: Vec<i64>,
-    operation: Operation,
-    divisible_by: i64,
-    if_true_throw_to: i32,
-    if_false_throw_to: i32,
-    number_of_inspections: i64,
-}
-
-fn play_monkey_business(monkeys: &mut [Monkey], stress_relieve: i64, modulo: i64) {
-    for i in 0..monkeys.len() {
-        let monkey: &mut Monkey = &mut monkeys[i];
-        let throws = monkey
-            .items
-            .iter()
-            .map(|item| {
-                let new_item = monkey.operation.execute(*item) / stress_relieve;
-                if new_item % monkey.divisible_by == 0 {
-                    (monkey.if_true_throw_to, new_item)
-                } else {
-                    (monkey.if_false_throw_to, new_item)
-                }
-            })
-            .collect::<Vec<(i32, i64)>>();
-        monkey.number_of_inspections += monkey.items.len() as i64;
-        monkey.items.clear();
-        throws.iter().for_each(|throw| {
-            monkeys[throw.0 as usize].items.push(throw.1 % modulo);
-        });
-    }
-}
-
-pub fn solution(filename: &str, rounds: i32, stress_relieve: i64) -> i64 {
-    let operation_re =
-        Regex::new(r"new\s=\s(?P<a>[[:alnum:]]*)\s(?P<op>\*|\+)\s(?P<b>[[:alnum:]]*)").unwrap();
-    let mut monkeys = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .chunks(7)
-        .into_iter()
-        .map(|mut chunk| {
-            chunk.next();
-            Monkey {
-                items: chunk
-                    .next()
-                    .unwrap()
-                    .trim()
-                    .strip_prefix("Starting items: ")
-                    .unwrap()
-                    .split(',')
-                    .map(|x| x.trim().parse::<i64>().unwrap())
-                    .collect(),
-                operation: Operation::from(
-                    operation_re
-                        .captures(
-                            chunk
-                                .next()
-                                .unwrap()
-                                .trim()
-                                .strip_prefix("Operation: ")
-                                .unwrap(),
-                        )
-                        .unwrap(),
-                ),
-                divisible_by: chunk
-                    .next()
-                    .unwrap()
-                    .trim()
-                    .strip_prefix("Test: divisible by ")
-                    .unwrap()
-                    .parse::<i64>()
-                    .unwrap(),
-                if_true_throw_to: chunk
-                    .next()
-                    .unwrap()
-                    .trim()
-                    .strip_prefix("If true: throw to monkey ")
-                    .unwrap()
-                    .parse::<i32>()
-                    .unwrap(),
-                if_false_throw_to: chunk
-                    .next()
-                    .unwrap()
-                    .trim()
-                    .strip_prefix("If false: throw to monkey ")
-                    .unwrap()
-                    .parse::<i32>()
-                    .unwrap(),
-                number_of_inspections: 0,
-            }
-        })
-        .collect::<Vec<Monkey>>();
-    let modulo = monkeys.iter().map(|monkey| monkey.divisible_by).product();
-
-    (0..rounds).for_each(|_| {
-        play_monkey_business(&mut monkeys, stress_relieve, modulo);
-    });
-
-    monkeys
-        .iter()
-        .map(|monkey| monkey.number_of_inspections)
-        .collect::<BTreeSet<i64>>()
-        .iter()
-        .rev()
-        .take(2)
-        .product()
-}
-
-pub fn run() -> (String, String) {
-    (
-        solution("src/inputs/aoc_11.input", 20, 3).to_string(),
-        solution("src/inputs/aoc_11.input", 10_000, 1).to_string(),
-    )
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_11_sample.input", 20, 3), 10605);
-        assert_eq!(
-            solution("src/inputs/aoc_11_sample.input", 10_000, 1),
-            2713310158
-        );
-        assert_eq!(solution("src/inputs/aoc_11.input", 20, 3), 55458);
-        assert_eq!(solution("src/inputs/aoc_11.input", 10_000, 1), 14508081294);
-    }
-}
+use crate::context::Context;
+use crate::parse::{blocks, normalize, Line, Normalize, ParseError, ParseResult, Scanner};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::trace::Event;
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Operator {
+    Mul,
+    Add,
+}
+
+/// Operand of an operation, the worry level being inspected or a number.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Old,
+    Int(i64),
+}
+
+impl Value {
+    fn parse(s: &mut Scanner) -> ParseResult<Self> {
+        match s.try_tag("old") {
+            true => Ok(Value::Old),
+            false => Ok(Value::Int(s.integer()?)),
+        }
+    }
+
+    fn get_or(&self, or: i64) -> i64 {
+        match self {
+            Value::Old => or,
+            Value::Int(v) => *v,
+        }
+    }
+}
+
+/// How inspecting an item changes its worry level.
+#[derive(Debug, Clone)]
+pub struct Operation {
+    pub a: Value,
+    pub b: Value,
+    pub op: Operator,
+}
+
+impl Operation {
+    fn parse(s: &mut Scanner) -> ParseResult<Self> {
+        let a = Value::parse(s)?;
+        let op = s.one_of(&[(" * ", Operator::Mul), (" + ", Operator::Add)])?;
+        let b = Value::parse(s)?;
+        Ok(Operation { a, b, op })
+    }
+
+    fn execute(&self, old: i64) -> i64 {
+        match self.op {
+            Operator::Add => self.a.get_or(old) + self.b.get_or(old),
+            Operator::Mul => self.a.get_or(old) * self.b.get_or(old),
+        }
+    }
+}
+
+/// Monkey as `parse` reads it, before it inspects anything.
+#[derive(Debug, Clone)]
+pub struct Monkey {
+    items: Vec<i64>,
+    operation: Operation,
+    divisible_by: i64,
+    if_true_throw_to: i32,
+    if_false_throw_to: i32,
+    number_of_inspections: i64,
+}
+
+impl Monkey {
+    /// Worry levels of the items it starts with.
+    pub fn items(&self) -> &[i64] {
+        &self.items
+    }
+
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    pub fn divisible_by(&self) -> i64 {
+        self.divisible_by
+    }
+
+    pub fn if_true_throw_to(&self) -> i32 {
+        self.if_true_throw_to
+    }
+
+    pub fn if_false_throw_to(&self) -> i32 {
+        self.if_false_throw_to
+    }
+}
+
+// Plays a round, tracing every throw.
+fn play_monkey_business(
+    monkeys: &mut [Monkey],
+    stress_relieve: i64,
+    modulo: i64,
+    round: i32,
+    ctx: &Context,
+) {
+    for i in 0..monkeys.len() {
+        let monkey: &mut Monkey = &mut monkeys[i];
+        let throws = monkey
+            .items
+            .iter()
+            .map(|item| {
+                let new_item = monkey.operation.execute(*item) / stress_relieve;
+                if new_item % monkey.divisible_by == 0 {
+                    (monkey.if_true_throw_to, new_item)
+                } else {
+                    (monkey.if_false_throw_to, new_item)
+                }
+            })
+            .collect::<Vec<(i32, i64)>>();
+        monkey.number_of_inspections += monkey.items.len() as i64;
+        monkey.items.clear();
+        // Kept out of the loop below, which the untraced runs spend their
+        // time in.
+        if ctx.is_tracing() {
+            throws.iter().for_each(|throw| {
+                ctx.trace(|| {
+                    Event::new("throw")
+                        .with("round", round)
+                        .with("monkey", i)
+                        .with("worry", throw.1)
+                        .with("to", throw.0)
+                })
+            });
+        }
+        throws.iter().for_each(|throw| {
+            monkeys[throw.0 as usize].items.push(throw.1 % modulo);
+        });
+    }
+}
+
+// Reads the `n`-th line of the monkey block with `read`, after the
+// indentation and `prefix`.
+fn field<'a, T>(
+    block: &[Line<'a>],
+    n: usize,
+    prefix: &str,
+    read: impl FnOnce(&mut Scanner<'a>) -> ParseResult<T>,
+) -> ParseResult<T> {
+    let line = block
+        .get(n)
+        .ok_or_else(|| ParseError::new(block[0].number + n, 1, format!("expected '{prefix}'")))?;
+    let mut s = line.scanner();
+    s.whitespace();
+    s.tag(prefix)?;
+    let value = read(&mut s)?;
+    s.end()?;
+    Ok(value)
+}
+
+pub fn parse(input: &str) -> ParseResult<Vec<Monkey>> {
+    let input = &*normalize(input, Normalize::default());
+    blocks(input)
+        .map(|block| {
+            field(&block, 0, "Monkey ", |s| {
+                s.integer::<usize>()?;
+                s.tag(":")
+            })?;
+            let items = field(&block, 1, "Starting items: ", |s| {
+                s.list(", ", Scanner::integer)
+            })?;
+            let operation = field(&block, 2, "Operation: new = ", Operation::parse)?;
+            let divisible_by = field(&block, 3, "Test: divisible by ", Scanner::integer)?;
+            let if_true_throw_to = field(&block, 4, "If true: throw to monkey ", Scanner::integer)?;
+            let if_false_throw_to =
+                field(&block, 5, "If false: throw to monkey ", Scanner::integer)?;
+            Ok(Monkey {
+                items,
+                operation,
+                divisible_by,
+                if_true_throw_to,
+                if_false_throw_to,
+                number_of_inspections: 0,
+            })
+        })
+        .collect()
+}
+
+pub fn solution(monkeys: &[Monkey], rounds: i32, stress_relieve: i64, ctx: &Context) -> i64 {
+    let mut monkeys = monkeys.to_vec();
+    let modulo = monkeys.iter().map(|monkey| monkey.divisible_by).product();
+
+    (0..rounds).for_each(|round| {
+        play_monkey_business(&mut monkeys, stress_relieve, modulo, round + 1, ctx);
+    });
+
+    monkeys
+        .iter()
+        .map(|monkey| monkey.number_of_inspections)
+        .collect::<BTreeSet<i64>>()
+        .iter()
+        .rev()
+        .take(2)
+        .product()
+}
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    type Input = Vec<Monkey>;
+
+    fn day(&self) -> usize {
+        11
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey in the Middle"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, monkeys: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(monkeys, 20, 3, ctx).to_string())
+    }
+
+    fn part2(&self, monkeys: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(monkeys, 10_000, 1, ctx).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_11_sample.input")
+            .part1("10605")
+            .part2("2713310158")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use std::fs;
+
+    #[test]
+    fn test_run() {
+        let ctx = Context::default();
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_11_sample.input").unwrap()).unwrap();
+        let monkeys = parse(&fs::read_to_string("src/inputs/aoc_11.input").unwrap()).unwrap();
+        assert_answer(
+            11,
+            Part::One,
+            "aoc_11_sample.input",
+            solution(&sample, 20, 3, &ctx),
+        );
+        assert_answer(
+            11,
+            Part::Two,
+            "aoc_11_sample.input",
+            solution(&sample, 10_000, 1, &ctx),
+        );
+        assert_answer(
+            11,
+            Part::One,
+            "aoc_11.input",
+            solution(&monkeys, 20, 3, &ctx),
+        );
+        assert_answer(
+            11,
+            Part::Two,
+            "aoc_11.input",
+            solution(&monkeys, 10_000, 1, &ctx),
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("Monkey 0:\n  Starting items: 79, x8").unwrap_err(),
+            ParseError::new(2, 23, "expected a number")
+        );
+        assert_eq!(
+            parse("Monkey 0:\n  Starting items: 79\n  Operation: new = old ^ 2").unwrap_err(),
+            ParseError::new(3, 23, "expected ' * ' or ' + '")
+        );
+    }
+}
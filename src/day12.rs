@@ -1,135 +1,189 @@
-extern crate derive_more;
-use rayon::prelude::*;
-use std::{
-    collections::{HashSet, VecDeque},
-    fs,
-};
-
-use derive_more::Constructor;
-#[derive(Debug, Default, Constructor, PartialEq, Eq, Hash, Clone, Copy)]
-struct Pos {
-    row: usize,
-    column: usize,
-}
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{normalize, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::search::Search;
+use crate::solver::{PartResult, Sample, Solver};
 
-#[derive(Debug, Default)]
-struct Map {
-    map: Vec<Vec<u8>>,
-    start: Pos,
-    end: Pos,
+/// Heightmap, with the elevations of the start and best signal as `b'a'` and
+/// `b'z'`.
+#[derive(Debug)]
+pub struct Map {
+    map: Grid<u8>,
+    start: (usize, usize),
+    end: (usize, usize),
 }
 
 impl Map {
-    fn from_str(s: &str) -> Self {
-        let mut start = Default::default();
-        let mut end = Default::default();
-        let map = s
-            .lines()
-            .enumerate()
-            .map(|(row, line)| {
-                line.bytes()
-                    .enumerate()
-                    .map(|(column, t)| match t {
-                        b'S' => {
-                            start = Pos::new(row, column);
-                            b'a'
-                        }
-                        b'E' => {
-                            end = Pos::new(row, column);
-                            b'z'
-                        }
-                        _ => t,
-                    })
-                    .collect()
-            })
-            .collect();
-        Map { map, start, end }
-    }
-
-    fn can_move(&self, p: &Pos, dest_elevation: u8) -> bool {
-        self.map[p.row][p.column] >= dest_elevation
-            || self.map[p.row][p.column] + 1 == dest_elevation
-    }
-
-    fn can_move_up(&self, pos: &Pos) -> bool {
-        pos.row > 0 && self.can_move(pos, self.map[pos.row - 1][pos.column])
-    }
-
-    fn can_move_down(&self, pos: &Pos) -> bool {
-        pos.row < self.map.len() - 1 && self.can_move(pos, self.map[pos.row + 1][pos.column])
-    }
-
-    fn can_move_left(&self, pos: &Pos) -> bool {
-        pos.column > 0 && self.can_move(pos, self.map[pos.row][pos.column - 1])
-    }
-
-    fn can_move_right(&self, pos: &Pos) -> bool {
-        pos.column < self.map[0].len() - 1 && self.can_move(pos, self.map[pos.row][pos.column + 1])
-    }
-
-    fn bfs(&self, start: &Pos) -> Option<usize> {
-        let mut visited: HashSet<Pos> = HashSet::with_capacity(7000);
-        let mut q: VecDeque<(Pos, usize)> = VecDeque::with_capacity(256);
-        q.push_back((*start, 0));
-        loop {
-            if q.is_empty() {
-                return None;
-            }
-            let p = q.pop_front().unwrap();
-            if !visited.insert(p.0) {
-                continue;
-            }
-            if p.0 == self.end {
-                return Some(p.1);
-            }
-            if self.can_move_right(&p.0) {
-                q.push_back((Pos::new(p.0.row, p.0.column + 1), p.1 + 1));
-            }
-            if self.can_move_down(&p.0) {
-                q.push_back((Pos::new(p.0.row + 1, p.0.column), p.1 + 1));
-            }
-            if self.can_move_up(&p.0) {
-                q.push_back((Pos::new(p.0.row - 1, p.0.column), p.1 + 1));
-            }
-            if self.can_move_left(&p.0) {
-                q.push_back((Pos::new(p.0.row, p.0.column - 1), p.1 + 1));
-            }
-        }
+    /// Elevations, from `b'a'` to `b'z'`.
+    pub fn elevations(&self) -> &Grid<u8> {
+        &self.map
     }
+
+    pub fn start(&self) -> (usize, usize) {
+        self.start
+    }
+
+    /// Position of the best signal.
+    pub fn end(&self) -> (usize, usize) {
+        self.end
+    }
+
+    fn from_str(s: &str) -> ParseResult<Self> {
+        let mut map = Grid::parse(s, |t| match t {
+            'a'..='z' | 'S' | 'E' => Ok(t as u8),
+            _ => Err("invalid elevation, expected a-z, S or E".to_string()),
+        })?;
+        let start = map
+            .position(|t| *t == b'S')
+            .ok_or_else(|| ParseError::new(1, 1, "missing start position 'S'"))?;
+        let end = map
+            .position(|t| *t == b'E')
+            .ok_or_else(|| ParseError::new(1, 1, "missing best signal position 'E'"))?;
+        map[start] = b'a';
+        map[end] = b'z';
+        Ok(Map { map, start, end })
+    }
+
+    fn can_move(&self, from: (usize, usize), to: (usize, usize)) -> bool {
+        self.map[from] >= self.map[to] || self.map[from] + 1 == self.map[to]
+    }
+
+    // Steps from the nearest of the `starts` to the best signal.
+    fn bfs(&self, starts: impl IntoIterator<Item = (usize, usize)>) -> Option<usize> {
+        Search::new(starts)
+            .visited(Grid::new(self.map.width(), self.map.height(), false))
+            .bfs(
+                |&pos| {
+                    self.map
+                        .neighbours4(pos)
+                        .filter(move |next| self.can_move(pos, *next))
+                },
+                |pos| *pos == self.end,
+            )
+            .map(|found| found.cost)
+    }
+}
+
+pub fn parse(input: &str) -> ParseResult<Map> {
+    let input = &*normalize(input, Normalize::default());
+    Map::from_str(input)
+}
+
+pub fn part1(map: &Map) -> usize {
+    map.bfs([map.start]).expect("no path to the best signal")
 }
 
-fn solution(filename: &str) -> (usize, usize) {
-    let map: Map = Map::from_str(&fs::read_to_string(filename).unwrap());
-    (
-        map.bfs(&map.start).unwrap(),
-        map.map
-            .par_iter()
-            .enumerate()
-            .map(|(i, row)| {
-                row.par_iter()
-                    .enumerate()
-                    .filter(|(_, t)| t == &&b'a')
-                    .map(|(j, _)| map.bfs(&Pos::new(i, j)).unwrap_or(usize::MAX))
-                    .min()
-                    .unwrap()
-            })
-            .min()
-            .unwrap(),
-    )
+pub fn part2(map: &Map) -> usize {
+    let lowest = map
+        .map
+        .iter()
+        .filter(|(_, t)| **t == b'a')
+        .map(|(pos, _)| pos);
+    map.bfs(lowest).expect("no path to the best signal")
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_12.input");
-    (p1.to_string(), p2.to_string())
+pub struct Day12;
+
+impl Solver for Day12 {
+    type Input = Map;
+
+    fn day(&self) -> usize {
+        12
+    }
+
+    fn title(&self) -> &'static str {
+        "Hill Climbing Algorithm"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, map: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part1(map).to_string())
+    }
+
+    fn part2(&self, map: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part2(map).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_12_sample.input").part1("31").part2("29")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Steps from `start` to `end`, relaxing the distances of every square
+    // until none gets shorter.
+    fn naive_steps(rows: &[Vec<u8>], start: (usize, usize), end: (usize, usize)) -> Option<usize> {
+        let (width, height) = (rows[0].len(), rows.len());
+        let mut steps = vec![vec![None; width]; height];
+        steps[start.1][start.0] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (x, y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+                let Some(n) = steps[y][x] else { continue };
+                let next = [
+                    (x + 1, y),
+                    (x.wrapping_sub(1), y),
+                    (x, y + 1),
+                    (x, y.wrapping_sub(1)),
+                ];
+                for (nx, ny) in next {
+                    if nx < width
+                        && ny < height
+                        && rows[ny][nx] <= rows[y][x] + 1
+                        && steps[ny][nx].is_none_or(|m| n + 1 < m)
+                    {
+                        steps[ny][nx] = Some(n + 1);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        steps[end.1][end.0]
+    }
+
+    // Elevations of the squares, start and end.
+    type Hills = (Vec<Vec<u8>>, (usize, usize), (usize, usize));
+
+    // Low hills, so most of them can be climbed.
+    fn hills() -> impl Strategy<Value = Hills> {
+        (1..8usize, 1..8usize).prop_flat_map(|(width, height)| {
+            let square = (0..width, 0..height);
+            (
+                prop::collection::vec(prop::collection::vec(b'a'..b'e', width), height),
+                square.clone(),
+                square,
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_steps_match_naive((rows, start, end) in hills()) {
+            let map = Map { map: Grid::from_rows(rows.clone()), start, end };
+            prop_assert_eq!(map.bfs([start]), naive_steps(&rows, start, end));
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_12_sample.input"), (31, 29));
-        assert_eq!(solution("src/inputs/aoc_12.input"), (437, 430));
+        let sample =
+            Map::from_str(&fs::read_to_string("src/inputs/aoc_12_sample.input").unwrap()).unwrap();
+        let map = Map::from_str(&fs::read_to_string("src/inputs/aoc_12.input").unwrap()).unwrap();
+        assert_answer(12, Part::One, "aoc_12_sample.input", part1(&sample));
+        assert_answer(12, Part::Two, "aoc_12_sample.input", part2(&sample));
+        assert_answer(12, Part::One, "aoc_12.input", part1(&map));
+        assert_answer(12, Part::Two, "aoc_12.input", part2(&map));
     }
 }
@@ -1,148 +1,335 @@
-use core::cmp::Ordering;
-use itertools::sorted;
-use itertools::Itertools;
-use std::{cmp, fs};
-
-#[derive(Debug, Clone)]
-enum Packet {
-    Int(i32),
-    List(Vec<Self>),
-}
-
-impl From<&str> for Packet {
-    fn from(s: &str) -> Self {
-        let mut i = 0;
-        let mut p: Vec<Packet> = Vec::new();
-        loop {
-            match s.chars().nth(i).unwrap() {
-                '[' => {
-                    p.push(Packet::List(Vec::new()));
-                    i += 1;
-                }
-                ']' => {
-                    let tl = p.pop().unwrap();
-                    if p.is_empty() {
-                        return tl;
-                    }
-                    if let Packet::List(cp) = p.last_mut().unwrap() {
-                        cp.push(tl);
-                    }
-                    i += 1;
-                }
-                v if v.is_ascii_digit() => {
-                    let ss = s.get(i..).unwrap();
-                    let x_end = cmp::min(
-                        ss.find(',').or(Some(ss.len())),
-                        ss.find(']').or(Some(ss.len())),
-                    )
-                    .unwrap();
-                    if let Packet::List(cp) = p.last_mut().unwrap() {
-                        cp.push(Packet::Int(
-                            s.get(i..i + x_end).unwrap().parse::<i32>().unwrap(),
-                        ));
-                    }
-                    i += x_end;
-                }
-                _ => i += 1,
-            }
-        }
-    }
-}
-
-impl PartialEq for Packet {
-    fn eq(&self, other: &Self) -> bool {
-        self.partial_cmp(other).unwrap() == Ordering::Equal
-    }
-}
-
-impl Eq for Packet {}
-
-impl PartialOrd for Packet {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        match (&self, &other) {
-            (Packet::Int(a), Packet::Int(b)) => a.partial_cmp(b),
-            (Packet::List(a), Packet::List(b)) => {
-                for i in 0..cmp::min(a.len(), b.len()) {
-                    if a[i] == b[i] {
-                        continue;
-                    } else {
-                        return a[i].partial_cmp(&b[i]);
-                    }
-                }
-                a.len().partial_cmp(&b.len())
-            }
-            (Packet::Int(a), &b) => Packet::List(vec![Packet::Int(*a)]).partial_cmp(b),
-            (&a, Packet::Int(b)) => a.partial_cmp(&Packet::List(vec![Packet::Int(*b)])),
-        }
-    }
-}
-
-impl Ord for Packet {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap()
-    }
-}
-
-#[derive(Debug)]
-struct Pair {
-    left: Packet,
-    right: Packet,
-}
-
-impl From<&Vec<&str>> for Pair {
-    fn from(v: &Vec<&str>) -> Pair {
-        Pair {
-            left: Packet::from(v[0]),
-            right: Packet::from(v[1]),
-        }
-    }
-}
-
-fn solution(filename: &str) -> (usize, usize) {
-    let pairs: Vec<Pair> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .chunks(3)
-        .into_iter()
-        .map(|chunk| Pair::from(&chunk.collect::<Vec<&str>>()))
-        .collect();
-
-    let packet_2 = Packet::from("[[2]]");
-    let packet_6 = Packet::from("[[6]]");
-    (
-        // p1
-        pairs
-            .iter()
-            .enumerate()
-            .filter(|(_, p)| p.left < p.right)
-            .map(|(i, _)| i + 1)
-            .sum(),
-        // p2
-        sorted(
-            pairs
-                .into_iter()
-                .flat_map(|p| [p.left, p.right])
-                .chain([packet_2.clone(), packet_6.clone()]),
-        )
-        .enumerate()
-        .filter(|(_, p)| p == &packet_2 || p == &packet_6)
-        .map(|(i, _)| i + 1)
-        .product(),
-    )
-}
-
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_13.input");
-    (p1.to_string(), p2.to_string())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_13_sample.input"), (13, 140));
-        assert_eq!(solution("src/inputs/aoc_13.input"), (6101, 21909));
-    }
-}
+use crate::context::Context;
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use core::cmp::Ordering;
+use itertools::sorted;
+use itertools::Itertools;
+use std::cmp;
+use std::str::FromStr;
+
+#[derive(Debug, Clone)]
+pub enum Packet {
+    Int(i32),
+    List(Vec<Self>),
+}
+
+impl TryFrom<Line<'_>> for Packet {
+    type Error = ParseError;
+
+    fn try_from(line: Line) -> ParseResult<Self> {
+        let s = line.text;
+        let mut i: usize = 0;
+        let mut p: Vec<Packet> = Vec::new();
+        loop {
+            // Values start a list or follow a ',', which only comes after a
+            // value.
+            let prev = i.checked_sub(1).map(|j| s.as_bytes()[j]);
+            match s.as_bytes().get(i) {
+                Some(b'[' | b'0'..=b'9') if prev.is_some_and(|c| c != b'[' && c != b',') => {
+                    return Err(line.error_at(i, "expected ','"));
+                }
+                Some(b',' | b']') if prev == Some(b',') => {
+                    return Err(line.error_at(i, "expected a value after ','"));
+                }
+                Some(b',') if prev == Some(b'[') => {
+                    return Err(line.error_at(i, "expected a value before ','"));
+                }
+                Some(b'[') => {
+                    p.push(Packet::List(Vec::new()));
+                    i += 1;
+                }
+                Some(b']') => {
+                    let tl = p.pop().ok_or_else(|| line.error_at(i, "unmatched ']'"))?;
+                    if p.is_empty() {
+                        if i + 1 != s.len() {
+                            return Err(line.error_at(i + 1, "trailing characters after packet"));
+                        }
+                        return Ok(tl);
+                    }
+                    if let Some(Packet::List(cp)) = p.last_mut() {
+                        cp.push(tl);
+                    }
+                    i += 1;
+                }
+                Some(v) if v.is_ascii_digit() => {
+                    let ss = s.get(i..).unwrap();
+                    let x_end = cmp::min(
+                        ss.find(',').or(Some(ss.len())),
+                        ss.find(']').or(Some(ss.len())),
+                    )
+                    .unwrap();
+                    match p.last_mut() {
+                        Some(Packet::List(cp)) => {
+                            cp.push(Packet::Int(line.number(&s[i..i + x_end])?))
+                        }
+                        _ => return Err(line.error_at(i, "expected '['")),
+                    }
+                    i += x_end;
+                }
+                Some(b',') if !p.is_empty() => i += 1,
+                Some(_) => return Err(line.error_at(i, "unexpected character")),
+                None => return Err(line.error_at(i, "missing ']'")),
+            }
+        }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> ParseResult<Self> {
+        Packet::try_from(Line { number: 1, text: s })
+    }
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self, &other) {
+            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+            (Packet::List(a), Packet::List(b)) => {
+                for i in 0..cmp::min(a.len(), b.len()) {
+                    if a[i] == b[i] {
+                        continue;
+                    } else {
+                        return a[i].cmp(&b[i]);
+                    }
+                }
+                a.len().cmp(&b.len())
+            }
+            (Packet::Int(a), &b) => Packet::List(vec![Packet::Int(*a)]).cmp(b),
+            (&a, Packet::Int(b)) => a.cmp(&Packet::List(vec![Packet::Int(*b)])),
+        }
+    }
+}
+
+/// Packets of a pair, in the order they're received.
+#[derive(Debug)]
+pub struct Pair {
+    pub left: Packet,
+    pub right: Packet,
+}
+
+pub fn parse(input: &str) -> ParseResult<Vec<Pair>> {
+    let input = &*normalize(input, Normalize::default());
+    let packets = lines(input)
+        .filter(|line| !line.text.is_empty())
+        .map(Packet::try_from)
+        .collect::<ParseResult<Vec<Packet>>>()?;
+    if packets.len() % 2 != 0 {
+        return Err(ParseError::new(
+            input.lines().count(),
+            1,
+            "last packet has no pair",
+        ));
+    }
+    Ok(packets
+        .into_iter()
+        .tuples()
+        .map(|(left, right)| Pair { left, right })
+        .collect())
+}
+
+fn divider_packet(n: i32) -> Packet {
+    Packet::List(vec![Packet::List(vec![Packet::Int(n)])])
+}
+
+pub fn part1(pairs: &[Pair]) -> usize {
+    pairs
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.left < p.right)
+        .map(|(i, _)| i + 1)
+        .sum()
+}
+
+pub fn part2(pairs: &[Pair]) -> usize {
+    let packet_2 = divider_packet(2);
+    let packet_6 = divider_packet(6);
+    sorted(
+        pairs
+            .iter()
+            .flat_map(|p| [&p.left, &p.right])
+            .chain([&packet_2, &packet_6]),
+    )
+    .enumerate()
+    .filter(|(_, p)| *p == &packet_2 || *p == &packet_6)
+    .map(|(i, _)| i + 1)
+    .product()
+}
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    type Input = Vec<Pair>;
+
+    fn day(&self) -> usize {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Distress Signal"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, pairs: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part1(pairs).to_string())
+    }
+
+    fn part2(&self, pairs: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part2(pairs).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_13_sample.input").part1("13").part2("140")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    fn render(packet: &Packet) -> String {
+        match packet {
+            Packet::Int(v) => v.to_string(),
+            Packet::List(items) => {
+                format!(
+                    "[{}]",
+                    items.iter().map(render).collect::<Vec<_>>().join(",")
+                )
+            }
+        }
+    }
+
+    // Whether the packets are in the right order, or nothing when they're
+    // equal, by the rules of the puzzle text.
+    fn naive_order(left: &Packet, right: &Packet) -> Option<bool> {
+        match (left, right) {
+            (Packet::Int(l), Packet::Int(r)) => (l != r).then_some(l < r),
+            (Packet::List(l), Packet::List(r)) => l
+                .iter()
+                .zip(r)
+                .find_map(|(l, r)| naive_order(l, r))
+                .or((l.len() != r.len()).then_some(l.len() < r.len())),
+            (Packet::Int(l), _) => naive_order(&Packet::List(vec![Packet::Int(*l)]), right),
+            (_, Packet::Int(r)) => naive_order(left, &Packet::List(vec![Packet::Int(*r)])),
+        }
+    }
+
+    // Lists of up to 4 levels. Without 2 and 6, none ties with a divider.
+    fn packet() -> impl Strategy<Value = Packet> {
+        prop::sample::select(vec![0, 1, 3, 4, 5, 7, 8, 9, 10])
+            .prop_map(Packet::Int)
+            .prop_recursive(4, 32, 4, |inner| {
+                prop::collection::vec(inner, 0..4).prop_map(Packet::List)
+            })
+            .prop_map(|packet| match packet {
+                Packet::Int(_) => Packet::List(vec![packet]),
+                list => list,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_order_matches_naive(packets in prop::collection::vec((packet(), packet()), 1..10)) {
+            let input = packets
+                .iter()
+                .map(|(left, right)| format!("{}\n{}\n", render(left), render(right)))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let pairs = parse(&input).unwrap();
+            for (pair, (left, right)) in pairs.iter().zip(&packets) {
+                prop_assert_eq!(render(&pair.left), render(left));
+                prop_assert_eq!(render(&pair.right), render(right));
+            }
+            let ordered = packets
+                .iter()
+                .zip(1..)
+                .filter(|((left, right), _)| naive_order(left, right) == Some(true))
+                .map(|(_, i)| i)
+                .sum::<usize>();
+            prop_assert_eq!(part1(&pairs), ordered);
+            // Dividers go after the packets before them, and [[2]] before [[6]].
+            let before = |divider| {
+                packets
+                    .iter()
+                    .flat_map(|(left, right)| [left, right])
+                    .filter(|packet| naive_order(packet, &divider_packet(divider)) == Some(true))
+                    .count()
+            };
+            prop_assert_eq!(part2(&pairs), (before(2) + 1) * (before(6) + 2));
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_13_sample.input").unwrap()).unwrap();
+        let pairs = parse(&fs::read_to_string("src/inputs/aoc_13.input").unwrap()).unwrap();
+        assert_answer(13, Part::One, "aoc_13_sample.input", part1(&sample));
+        assert_answer(13, Part::Two, "aoc_13_sample.input", part2(&sample));
+        assert_answer(13, Part::One, "aoc_13.input", part1(&pairs));
+        assert_answer(13, Part::Two, "aoc_13.input", part2(&pairs));
+    }
+
+    #[test]
+    fn test_compare() {
+        let packet = |s: &str| s.parse::<Packet>().unwrap();
+        assert!(packet("[1,1,3,1,1]") < packet("[1,1,5,1,1]"));
+        assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+        assert!(packet("[9]") > packet("[[8,7,6]]"));
+        assert_eq!(packet("[[2]]"), packet("[2]"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("[1,[2]\n[3]").unwrap_err(),
+            ParseError::new(1, 7, "missing ']'")
+        );
+        assert_eq!(
+            parse("[1]\n[a]").unwrap_err(),
+            ParseError::new(2, 2, "unexpected character")
+        );
+        assert_eq!(
+            parse("[1]\n[2]\n\n[3]").unwrap_err(),
+            ParseError::new(4, 1, "last packet has no pair")
+        );
+        assert_eq!(
+            parse("[,1]\n[1]").unwrap_err(),
+            ParseError::new(1, 2, "expected a value before ','")
+        );
+        assert_eq!(
+            parse("[1]\n[1,,2]").unwrap_err(),
+            ParseError::new(2, 4, "expected a value after ','")
+        );
+        assert_eq!(
+            parse("[1,]\n[1]").unwrap_err(),
+            ParseError::new(1, 4, "expected a value after ','")
+        );
+        assert_eq!(
+            parse("[[1][2]]\n[1]").unwrap_err(),
+            ParseError::new(1, 5, "expected ','")
+        );
+    }
+}
@@ -1,232 +1,408 @@
-extern crate derive_more;
-use derive_more::Constructor;
-use itertools::Itertools;
-use std::collections::HashSet;
-use std::fmt;
-use std::fs;
-
-#[derive(Debug, PartialEq, Eq, Hash, Constructor)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl From<(usize, usize)> for Point {
-    fn from((x, y): (usize, usize)) -> Self {
-        Self { x, y }
-    }
-}
-
-impl From<&str> for Point {
-    fn from(s: &str) -> Self {
-        Self::from(
-            s.split(',')
-                .map(|v| v.parse::<usize>().unwrap())
-                .tuples::<(usize, usize)>()
-                .next()
-                .unwrap(),
-        )
-    }
-}
-
-fn get_points_from_tuple_vector((s, e): (&Point, &Point)) -> Vec<Point> {
-    let x_d = e.x as i32 - s.x as i32;
-    let y_d = e.y as i32 - s.y as i32;
-    if x_d != 0 {
-        (0..=x_d.abs())
-            .map(|i| Point::new((s.x as i32 + i * x_d.signum()) as usize, s.y))
-            .collect()
-    } else if y_d != 0 {
-        (0..=y_d.abs())
-            .map(|i| Point::new(s.x, (s.y as i32 + i * y_d.signum()) as usize))
-            .collect()
-    } else {
-        panic!("empty vector");
-    }
-}
-
-#[derive(Debug)]
-struct Path {
-    points: Vec<Point>,
-}
-
-impl From<&str> for Path {
-    fn from(s: &str) -> Self {
-        Self {
-            points: s.split("->").map(|s| Point::from(s.trim())).collect(),
-        }
-    }
-}
-
-impl Path {
-    fn max_x(&self) -> usize {
-        self.points.iter().map(|p| p.x).max().unwrap()
-    }
-
-    fn max_y(&self) -> usize {
-        self.points.iter().map(|p| p.y).max().unwrap()
-    }
-
-    fn get_all_points(&self) -> impl Iterator<Item = Point> + '_ {
-        self.points
-            .iter()
-            .zip(self.points[1..].iter())
-            .flat_map(get_points_from_tuple_vector)
-    }
-}
-
-#[derive(Debug)]
-struct Paths {
-    paths: Vec<Path>,
-}
-
-impl From<&str> for Paths {
-    fn from(s: &str) -> Self {
-        Self {
-            paths: s.lines().map(Path::from).collect(),
-        }
-    }
-}
-
-impl Paths {
-    fn max_x(&self) -> usize {
-        self.paths.iter().map(|p| p.max_x()).max().unwrap()
-    }
-
-    fn max_y(&self) -> usize {
-        self.paths.iter().map(|p| p.max_y()).max().unwrap()
-    }
-}
-
-struct Map {
-    grid: Vec<Vec<char>>,
-    sand_source: Point,
-    abyss: bool,
-    width: usize,
-    height: usize,
-}
-
-impl fmt::Display for Map {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.grid
-                .iter()
-                .map(|row| row.iter().collect::<String>() + "\n")
-                .collect::<String>()
-        )
-    }
-}
-
-impl Map {
-    fn from_paths(paths: &Paths, sand_source: &Point, abyss: bool) -> Map {
-        let width = if abyss {
-            paths.max_x() + 1
-        } else {
-            2 * (paths.max_x() + 1)
-        };
-        let height = if abyss {
-            paths.max_y() + 1
-        } else {
-            paths.max_y() + 2
-        };
-
-        let points: HashSet<Point> =
-            HashSet::from_iter(paths.paths.iter().flat_map(|p| p.get_all_points()));
-
-        Map {
-            grid: (0..height)
-                .map(|y| {
-                    (0..width)
-                        .map(|x| {
-                            if points.contains(&Point::new(x, y)) {
-                                'X'
-                            } else {
-                                '.'
-                            }
-                        })
-                        .collect::<Vec<char>>()
-                })
-                .collect(),
-            sand_source: Point::new(sand_source.x, sand_source.y),
-            abyss,
-            width,
-            height,
-        }
-    }
-
-    fn is_blocked(&self, x: usize, y: usize) -> bool {
-        self.is_wall(x) || self.is_floor(y) || self.grid[y][x] == 'X' || self.grid[y][x] == 'o'
-    }
-
-    fn is_floor(&self, y: usize) -> bool {
-        y >= self.height
-    }
-
-    fn is_wall(&self, x: usize) -> bool {
-        x >= self.width
-    }
-
-    fn add_sand(&mut self) -> bool {
-        let mut cur = Point::new(self.sand_source.x, self.sand_source.y);
-        loop {
-            if self.abyss && (self.is_floor(cur.y + 1) || self.is_wall(cur.x + 1)) {
-                return false;
-            }
-            if !self.is_blocked(cur.x, cur.y + 1) {
-                // down
-                cur.y += 1;
-            } else if !self.is_blocked(cur.x - 1, cur.y + 1) {
-                // left
-                cur.y += 1;
-                cur.x -= 1;
-            } else if !self.is_blocked(cur.x + 1, cur.y + 1) {
-                // right
-                cur.y += 1;
-                cur.x += 1;
-            } else {
-                self.grid[cur.y][cur.x] = 'o';
-                return !(cur == self.sand_source);
-            }
-        }
-    }
-}
-
-fn solution(filename: &str, abyss: bool) -> i32 {
-    let mut map = Map::from_paths(
-        &Paths::from(fs::read_to_string(filename).unwrap().as_str()),
-        &Point::new(500, 0),
-        abyss,
-    );
-
-    let mut i = 0;
-    while map.add_sand() {
-        i += 1;
-    }
-
-    if !abyss {
-        i += 1;
-    }
-    i
-}
-
-pub fn run() -> (String, String) {
-    (
-        solution("src/inputs/aoc_14.input", true).to_string(),
-        solution("src/inputs/aoc_14.input", false).to_string(),
-    )
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_14_sample.input", true), 24);
-        assert_eq!(solution("src/inputs/aoc_14_sample.input", false), 93);
-        assert_eq!(solution("src/inputs/aoc_14.input", true), 873);
-        assert_eq!(solution("src/inputs/aoc_14.input", false), 24813);
-    }
-}
+extern crate derive_more;
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::visualize::{Frame, Pixel};
+use derive_more::Constructor;
+
+#[derive(Debug, PartialEq, Eq, Constructor)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    fn parse(line: &Line, s: &str) -> ParseResult<Self> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| line.error(s, format!("invalid point '{s}', expected 'X,Y'")))?;
+        Ok(Self::new(line.number(x)?, line.number(y)?))
+    }
+}
+
+fn get_points_from_tuple_vector((s, e): (&Point, &Point)) -> Vec<Point> {
+    let x_d = e.x as i32 - s.x as i32;
+    let y_d = e.y as i32 - s.y as i32;
+    if x_d != 0 {
+        (0..=x_d.abs())
+            .map(|i| Point::new((s.x as i32 + i * x_d.signum()) as usize, s.y))
+            .collect()
+    } else if y_d != 0 {
+        (0..=y_d.abs())
+            .map(|i| Point::new(s.x, (s.y as i32 + i * y_d.signum()) as usize))
+            .collect()
+    } else {
+        panic!("empty vector");
+    }
+}
+
+/// Rock path, whose lines `parse` checks are horizontal or vertical.
+#[derive(Debug)]
+pub struct Path {
+    points: Vec<Point>,
+}
+
+impl TryFrom<Line<'_>> for Path {
+    type Error = ParseError;
+
+    fn try_from(line: Line) -> ParseResult<Self> {
+        let mut points: Vec<Point> = Vec::new();
+        for s in line.text.split("->").map(str::trim) {
+            let point = Point::parse(&line, s)?;
+            if let Some(last) = points.last() {
+                if (last.x == point.x) == (last.y == point.y) {
+                    return Err(line.error(s, "path must be horizontal or vertical"));
+                }
+            }
+            points.push(point);
+        }
+        Ok(Self { points })
+    }
+}
+
+impl Path {
+    /// Ends of the lines of the path, in order.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    fn max_x(&self) -> usize {
+        self.points.iter().map(|p| p.x).max().unwrap()
+    }
+
+    fn max_y(&self) -> usize {
+        self.points.iter().map(|p| p.y).max().unwrap()
+    }
+
+    fn get_all_points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points
+            .iter()
+            .zip(self.points[1..].iter())
+            .flat_map(get_points_from_tuple_vector)
+    }
+}
+
+/// Rock paths of the cave, at least one.
+#[derive(Debug)]
+pub struct Paths {
+    paths: Vec<Path>,
+}
+
+impl TryFrom<&str> for Paths {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> ParseResult<Self> {
+        let paths = lines(s)
+            .map(Path::try_from)
+            .collect::<ParseResult<Vec<Path>>>()?;
+        if paths.is_empty() {
+            return Err(ParseError::new(1, 1, "no rock paths"));
+        }
+        Ok(Self { paths })
+    }
+}
+
+impl Paths {
+    pub fn paths(&self) -> &[Path] {
+        &self.paths
+    }
+
+    fn max_x(&self) -> usize {
+        self.paths.iter().map(|p| p.max_x()).max().unwrap()
+    }
+
+    fn max_y(&self) -> usize {
+        self.paths.iter().map(|p| p.max_y()).max().unwrap()
+    }
+}
+
+const AIR: Pixel = Pixel::new('.', [0, 0, 0]);
+const ROCK: Pixel = Pixel::new('#', [128, 128, 128]);
+const SAND: Pixel = Pixel::new('o', [230, 190, 90]);
+
+// Grains of sand dropped between two frames.
+const FRAME_EVERY: i32 = 10;
+
+struct Map {
+    grid: Grid<char>,
+    sand_source: Point,
+    abyss: bool,
+}
+
+impl Map {
+    fn from_paths(paths: &Paths, sand_source: &Point, abyss: bool) -> Map {
+        let width = if abyss {
+            paths.max_x() + 1
+        } else {
+            2 * (paths.max_x() + 1)
+        };
+        let height = if abyss {
+            paths.max_y() + 1
+        } else {
+            paths.max_y() + 2
+        };
+
+        let mut grid = Grid::new(width, height, '.');
+        paths
+            .paths
+            .iter()
+            .flat_map(|p| p.get_all_points())
+            .for_each(|p| grid[(p.x, p.y)] = 'X');
+        Map {
+            grid,
+            sand_source: Point::new(sand_source.x, sand_source.y),
+            abyss,
+        }
+    }
+
+    // Off the map is the floor, or with the abyss, air all the way down.
+    fn is_blocked(&self, x: usize, y: usize) -> bool {
+        if self.is_wall(x) || self.is_floor(y) {
+            return !self.abyss;
+        }
+        self.grid[(x, y)] == 'X' || self.grid[(x, y)] == 'o'
+    }
+
+    fn is_floor(&self, y: usize) -> bool {
+        y >= self.grid.height()
+    }
+
+    fn is_wall(&self, x: usize) -> bool {
+        x >= self.grid.width()
+    }
+
+    // Only the columns with rock or sand, the floor of part two makes the
+    // map much wider than the cave.
+    fn frame(&self) -> Frame {
+        let used = |x: &usize| *x == self.sand_source.x || self.grid.column(*x).any(|c| *c != '.');
+        let left = (0..self.grid.width()).find(used).unwrap();
+        let right = (0..self.grid.width()).rfind(used).unwrap();
+        Grid::from_rows(self.grid.rows().map(|row| {
+            row[left..=right]
+                .iter()
+                .map(|c| match c {
+                    'X' => ROCK,
+                    'o' => SAND,
+                    _ => AIR,
+                })
+                .collect()
+        }))
+    }
+
+    // Drops a grain of sand, telling whether it comes to rest.
+    fn add_sand(&mut self) -> bool {
+        let mut cur = Point::new(self.sand_source.x, self.sand_source.y);
+        loop {
+            // Below or right of the rocks, nothing stops it any more.
+            if self.abyss && (self.is_floor(cur.y + 1) || self.is_wall(cur.x)) {
+                return false;
+            }
+            if !self.is_blocked(cur.x, cur.y + 1) {
+                // down
+                cur.y += 1;
+            } else if !self.is_blocked(cur.x - 1, cur.y + 1) {
+                // left
+                cur.y += 1;
+                cur.x -= 1;
+            } else if !self.is_blocked(cur.x + 1, cur.y + 1) {
+                // right
+                cur.y += 1;
+                cur.x += 1;
+            } else {
+                self.grid[(cur.x, cur.y)] = 'o';
+                return true;
+            }
+        }
+    }
+}
+
+pub fn parse(input: &str) -> ParseResult<Paths> {
+    let input = &*normalize(input, Normalize::default());
+    Paths::try_from(input)
+}
+
+pub fn solution(paths: &Paths, abyss: bool, ctx: &Context) -> i32 {
+    let mut map = Map::from_paths(paths, &Point::new(500, 0), abyss);
+
+    let mut i = 0;
+    // Until a grain falls into the abyss, or one rests at the source.
+    while !map.is_blocked(map.sand_source.x, map.sand_source.y) && map.add_sand() {
+        i += 1;
+        if i % FRAME_EVERY == 0 {
+            ctx.frame(|| map.frame());
+        }
+    }
+    ctx.frame(|| map.frame());
+    i
+}
+
+pub struct Day14;
+
+impl Solver for Day14 {
+    type Input = Paths;
+
+    fn day(&self) -> usize {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Regolith Reservoir"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, paths: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(paths, true, ctx).to_string())
+    }
+
+    fn part2(&self, paths: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(paths, false, ctx).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_14_sample.input").part1("24").part2("93")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+    use std::fs;
+
+    // Grains resting in a set of blocked squares before one falls below the
+    // lowest rock, or with a floor, once one rests at the source.
+    fn naive_sand(paths: &[Vec<(usize, usize)>], floor: bool) -> i32 {
+        let mut blocked = HashSet::new();
+        for path in paths {
+            for segment in path.windows(2) {
+                let ((x1, y1), (x2, y2)) = (segment[0], segment[1]);
+                for x in x1.min(x2)..=x1.max(x2) {
+                    for y in y1.min(y2)..=y1.max(y2) {
+                        blocked.insert((x, y));
+                    }
+                }
+            }
+        }
+        let bottom = blocked.iter().map(|(_, y)| *y).max().unwrap();
+        let mut grains = 0;
+        while !blocked.contains(&(500, 0)) {
+            let (mut x, mut y) = (500, 0);
+            loop {
+                if !floor && y > bottom {
+                    return grains;
+                }
+                let below = [(x, y + 1), (x - 1, y + 1), (x + 1, y + 1)]
+                    .into_iter()
+                    .find(|p| !(blocked.contains(p) || (floor && p.1 == bottom + 2)));
+                match below {
+                    Some(next) => (x, y) = next,
+                    None => break,
+                }
+            }
+            blocked.insert((x, y));
+            grains += 1;
+        }
+        grains
+    }
+
+    // Paths below the source turning at right angles, or going on straight.
+    fn paths() -> impl Strategy<Value = Vec<Vec<(usize, usize)>>> {
+        let turn = (
+            any::<bool>(),
+            prop::sample::select(vec![-3isize, -2, -1, 1, 2, 3]),
+        );
+        let path = (490..511usize, 1..12usize, prop::collection::vec(turn, 1..4)).prop_map(
+            |(x, y, turns)| {
+                let mut path = vec![(x, y)];
+                for (horizontal, d) in turns {
+                    let (x, y) = *path.last().unwrap();
+                    path.push(match horizontal {
+                        true => (x.saturating_add_signed(d), y),
+                        false if y as isize + d < 1 => (x, y + d.unsigned_abs()),
+                        false => (x, y.saturating_add_signed(d)),
+                    });
+                }
+                path
+            },
+        );
+        prop::collection::vec(path, 1..5)
+    }
+
+    proptest! {
+        #[test]
+        fn test_sand_matches_naive(paths in paths()) {
+            let input = paths
+                .iter()
+                .map(|path| {
+                    let points = path.iter().map(|(x, y)| format!("{x},{y}")).collect::<Vec<_>>();
+                    points.join(" -> ") + "\n"
+                })
+                .collect::<String>();
+            let parsed = parse(&input).unwrap();
+            let ctx = Context::default();
+            prop_assert_eq!(solution(&parsed, true, &ctx), naive_sand(&paths, false));
+            prop_assert_eq!(solution(&parsed, false, &ctx), naive_sand(&paths, true));
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let sample = Paths::try_from(
+            fs::read_to_string("src/inputs/aoc_14_sample.input")
+                .unwrap()
+                .as_str(),
+        )
+        .unwrap();
+        let paths = Paths::try_from(
+            fs::read_to_string("src/inputs/aoc_14.input")
+                .unwrap()
+                .as_str(),
+        )
+        .unwrap();
+        let ctx = Context::default();
+        assert_answer(
+            14,
+            Part::One,
+            "aoc_14_sample.input",
+            solution(&sample, true, &ctx),
+        );
+        assert_answer(
+            14,
+            Part::Two,
+            "aoc_14_sample.input",
+            solution(&sample, false, &ctx),
+        );
+        assert_answer(14, Part::One, "aoc_14.input", solution(&paths, true, &ctx));
+        assert_answer(14, Part::Two, "aoc_14.input", solution(&paths, false, &ctx));
+    }
+
+    #[test]
+    fn test_frame() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_14_sample.input").unwrap()).unwrap();
+        let mut map = Map::from_paths(&sample, &Point::new(500, 0), true);
+        (0..5).for_each(|_| {
+            map.add_sand();
+        });
+        assert_eq!(
+            map.frame().map(|p| p.glyph).to_string(),
+            "..........\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             ....#...##\n\
+             ....#...#.\n\
+             ..###...#.\n\
+             ......o.#.\n\
+             ....oooo#.\n\
+             #########."
+        );
+    }
+}
@@ -1,142 +1,260 @@
-extern crate derive_more;
-
-use derive_more::Constructor;
-use itertools::{sorted, Itertools};
-use rayon::prelude::*;
-use regex::Regex;
-use std::collections::HashSet;
-use std::fs;
-
-#[derive(Debug, PartialEq, Eq, Hash, Constructor, Ord, PartialOrd)]
-struct Point {
-    x: i32,
-    y: i32,
-}
-
-#[derive(Debug)]
-struct SensorBeacon {
-    sensor: Point,
-    beacon: Point,
-}
-
-fn match_to_i32(m: &Option<regex::Match>) -> i32 {
-    m.unwrap().as_str().parse::<i32>().unwrap()
-}
-
-impl From<&regex::Captures<'_>> for SensorBeacon {
-    fn from(capture: &regex::Captures) -> Self {
-        SensorBeacon {
-            sensor: Point::new(
-                match_to_i32(&capture.name("sx")),
-                match_to_i32(&capture.name("sy")),
-            ),
-            beacon: Point::new(
-                match_to_i32(&capture.name("bx")),
-                match_to_i32(&capture.name("by")),
-            ),
-        }
-    }
-}
-
-impl SensorBeacon {
-    fn get_distance(&self) -> i32 {
-        (self.sensor.x - self.beacon.x).abs() + (self.sensor.y - self.beacon.y).abs()
-    }
-
-    fn get_coverage(&self, flt: i32) -> Option<HashSet<Point>> {
-        let distance = self.get_distance();
-        if (self.sensor.y - distance..=self.sensor.y + distance).contains(&flt) {
-            let flt_distance = flt - self.sensor.y;
-            let delta = distance - flt_distance.abs();
-            Some(
-                (0..2 * delta + 1)
-                    .map(|i| Point::new(self.sensor.x - delta + i, flt))
-                    .collect::<HashSet<Point>>(),
-            )
-        } else {
-            None
-        }
-    }
-
-    fn get_range_for_row(&self, flt: i32) -> Option<(i32, i32)> {
-        let distance = self.get_distance();
-        if (self.sensor.y - distance..=self.sensor.y + distance).contains(&flt) {
-            let flt_distance = flt - self.sensor.y;
-            let delta = distance - flt_distance.abs();
-            Some((self.sensor.x - delta, self.sensor.x + delta))
-        } else {
-            None
-        }
-    }
-}
-
-fn get_row_blind_spot(row: i32, sb: &[SensorBeacon]) -> Option<i32> {
-    let mut i = 0;
-    if sorted(sb.iter().filter_map(|sb| sb.get_range_for_row(row)))
-        .filter(|r| {
-            if (r.0..=r.1).contains(&i) {
-                i = r.1 + 1;
-                false
-            } else {
-                i < r.0
-            }
-        })
-        .count()
-        != 0
-    {
-        Some(i)
-    } else {
-        None
-    }
-}
-
-fn get_row_coverage(row: i32, sb: &[SensorBeacon], beacons: &HashSet<&Point>) -> usize {
-    sb.iter()
-        .filter_map(|sb| sb.get_coverage(row))
-        .flatten()
-        .sorted()
-        .dedup()
-        .count()
-        - beacons.iter().filter(|b| b.y == row).count()
-}
-
-fn solution(filename: &str, p1_row: i32) -> (usize, i64) {
-    let rex =
-        Regex::new(r"Sensor\sat\sx=(?P<sx>-?\d*),\sy=(?P<sy>-?\d*):\sclosest\sbeacon\sis\sat\sx=(?P<bx>-?\d*),\sy=(?P<by>-?\d*)").unwrap();
-    let sb: Vec<SensorBeacon> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|l| SensorBeacon::from(&rex.captures(l).unwrap()))
-        .collect();
-    let p1 = get_row_coverage(p1_row, &sb, &sb.iter().map(|sb| &sb.beacon).collect());
-
-    let p2 = (0..4_000_000)
-        .into_par_iter()
-        .find_map_any(|i| {
-            get_row_blind_spot(i, &sb).map(|blind_row| blind_row as i64 * 4_000_000 + i as i64)
-        })
-        .unwrap();
-    (p1, p2)
-}
-
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_15.input", 2_000_000);
-    (p1.to_string(), p2.to_string())
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_run() {
-        assert_eq!(
-            solution("src/inputs/aoc_15_sample.input", 10),
-            (26, 56000011)
-        );
-        assert_eq!(
-            solution("src/inputs/aoc_15.input", 2_000_000),
-            (5525990, 11756174628223)
-        );
-    }
-}
+extern crate derive_more;
+use crate::context::{Cancelled, Context, Counter};
+use crate::parse::{lines, normalize, Line, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartError, PartResult, Sample, Solver};
+
+use derive_more::Constructor;
+use itertools::{sorted, Itertools};
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Debug, PartialEq, Eq, Hash, Constructor, Ord, PartialOrd)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// Sensor and the closest beacon it detects.
+#[derive(Debug)]
+pub struct SensorBeacon {
+    pub sensor: Point,
+    pub beacon: Point,
+}
+
+impl SensorBeacon {
+    fn parse(line: &Line) -> ParseResult<Self> {
+        let mut s = line.scanner();
+        let sb = SensorBeacon {
+            sensor: Point::new(s.field("Sensor at x=")?, s.field(", y=")?),
+            beacon: Point::new(s.field(": closest beacon is at x=")?, s.field(", y=")?),
+        };
+        s.end()?;
+        Ok(sb)
+    }
+
+    fn get_distance(&self) -> i32 {
+        (self.sensor.x - self.beacon.x).abs() + (self.sensor.y - self.beacon.y).abs()
+    }
+
+    fn get_coverage(&self, flt: i32) -> Option<HashSet<Point>> {
+        let distance = self.get_distance();
+        if (self.sensor.y - distance..=self.sensor.y + distance).contains(&flt) {
+            let flt_distance = flt - self.sensor.y;
+            let delta = distance - flt_distance.abs();
+            Some(
+                (0..2 * delta + 1)
+                    .map(|i| Point::new(self.sensor.x - delta + i, flt))
+                    .collect::<HashSet<Point>>(),
+            )
+        } else {
+            None
+        }
+    }
+
+    fn get_range_for_row(&self, flt: i32) -> Option<(i32, i32)> {
+        let distance = self.get_distance();
+        if (self.sensor.y - distance..=self.sensor.y + distance).contains(&flt) {
+            let flt_distance = flt - self.sensor.y;
+            let delta = distance - flt_distance.abs();
+            Some((self.sensor.x - delta, self.sensor.x + delta))
+        } else {
+            None
+        }
+    }
+}
+
+fn get_row_blind_spot(row: i32, sb: &[SensorBeacon]) -> Option<i32> {
+    let mut i = 0;
+    if sorted(sb.iter().filter_map(|sb| sb.get_range_for_row(row)))
+        .filter(|r| {
+            if (r.0..=r.1).contains(&i) {
+                i = r.1 + 1;
+                false
+            } else {
+                i < r.0
+            }
+        })
+        .count()
+        != 0
+    {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+fn get_row_coverage(row: i32, sb: &[SensorBeacon], beacons: &HashSet<&Point>) -> usize {
+    sb.iter()
+        .filter_map(|sb| sb.get_coverage(row))
+        .flatten()
+        .sorted()
+        .dedup()
+        .count()
+        - beacons.iter().filter(|b| b.y == row).count()
+}
+
+pub fn parse(input: &str) -> ParseResult<Vec<SensorBeacon>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input)
+        .map(|line| SensorBeacon::parse(&line))
+        .collect()
+}
+
+pub fn part1(sb: &[SensorBeacon], row: i32) -> usize {
+    get_row_coverage(row, sb, &sb.iter().map(|sb| &sb.beacon).collect())
+}
+
+// Tuning frequency of the only spot the sensors can't see with both
+// coordinates between 0 and `max`, if there's one. Whatever `max`, the
+// frequency is x * 4000000 + y.
+pub fn part2(sb: &[SensorBeacon], max: i32, ctx: &Context) -> Result<Option<i64>, Cancelled> {
+    let rows = Counter::new(ctx, "rows", Some(max as u64 + 1), 100_000);
+    (0..=max)
+        .into_par_iter()
+        .find_map_any(|y| match rows.add(1) {
+            Ok(()) => get_row_blind_spot(y, sb)
+                .filter(|x| *x <= max)
+                .map(|x| Ok(x as i64 * 4_000_000 + y as i64)),
+            Err(cancelled) => Some(Err(cancelled)),
+        })
+        .transpose()
+}
+
+#[derive(Constructor)]
+pub struct Day15 {
+    // Row scanned by part 1, 10 for the sample input.
+    row: i32,
+    // Largest coordinate part 2 searches, 20 for the sample input.
+    max: i32,
+}
+
+impl Default for Day15 {
+    fn default() -> Self {
+        Day15 {
+            row: 2_000_000,
+            max: 4_000_000,
+        }
+    }
+}
+
+impl Solver for Day15 {
+    type Input = Vec<SensorBeacon>;
+
+    fn day(&self) -> usize {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Beacon Exclusion Zone"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, sb: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part1(sb, self.row).to_string())
+    }
+
+    fn part2(&self, sb: &Self::Input, ctx: &Context) -> PartResult {
+        part2(sb, self.max, ctx)?
+            .map(|frequency| frequency.to_string())
+            .ok_or_else(|| PartError::Unsolvable("no spot the sensors can't see".to_string()))
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_15_sample.input")
+            .solver(Day15::new(10, 20))
+            .part1("26")
+            .part2("56000011")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    type Report = Vec<((i32, i32), (i32, i32))>;
+
+    // Points of `row` within reach of a sensor, beacons included.
+    fn covered(report: &Report, row: i32) -> HashSet<i32> {
+        report
+            .iter()
+            .flat_map(|&((sx, sy), (bx, by))| {
+                let distance = (sx - bx).abs() + (sy - by).abs();
+                (sx - distance..=sx + distance)
+                    .filter(move |x| (x - sx).abs() + (row - sy).abs() <= distance)
+            })
+            .collect()
+    }
+
+    fn naive_part1(report: &Report, row: i32) -> usize {
+        let beacons = report
+            .iter()
+            .filter(|(_, b)| b.1 == row)
+            .map(|(_, b)| b.0)
+            .collect::<HashSet<i32>>();
+        covered(report, row).difference(&beacons).count()
+    }
+
+    // First point of `row` from 0 on that no sensor reaches, as long as a
+    // sensor reaches further right.
+    fn naive_blind_spot(report: &Report, row: i32) -> Option<i32> {
+        let covered = covered(report, row);
+        let blind = (0..).find(|x| !covered.contains(x)).unwrap();
+        covered.iter().any(|x| *x > blind).then_some(blind)
+    }
+
+    fn report() -> impl Strategy<Value = Report> {
+        prop::collection::vec(((-10..10, -10..10), (-10..10, -10..10)), 1..6)
+    }
+
+    proptest! {
+        #[test]
+        fn test_coverage_matches_naive(report in report(), row in -12..12) {
+            let input = report
+                .iter()
+                .map(|((sx, sy), (bx, by))| {
+                    format!("Sensor at x={sx}, y={sy}: closest beacon is at x={bx}, y={by}\n")
+                })
+                .collect::<String>();
+            let sb = parse(&input).unwrap();
+            prop_assert_eq!(part1(&sb, row), naive_part1(&report, row));
+            prop_assert_eq!(get_row_blind_spot(row, &sb), naive_blind_spot(&report, row));
+        }
+    }
+
+    #[test]
+    fn test_run() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_15_sample.input").unwrap()).unwrap();
+        let sb = parse(&fs::read_to_string("src/inputs/aoc_15.input").unwrap()).unwrap();
+        let ctx = Context::default();
+        assert_answer(15, Part::One, "aoc_15_sample.input", part1(&sample, 10));
+        assert_answer(
+            15,
+            Part::Two,
+            "aoc_15_sample.input",
+            part2(&sample, 20, &ctx).unwrap().unwrap(),
+        );
+        assert_answer(15, Part::One, "aoc_15.input", part1(&sb, 2_000_000));
+        assert_answer(
+            15,
+            Part::Two,
+            "aoc_15.input",
+            part2(&sb, 4_000_000, &ctx).unwrap().unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_15_sample.input").unwrap()).unwrap();
+        let ctx = Context::default().child(|_| {});
+        ctx.cancel();
+        assert_eq!(part2(&sample, 20, &ctx), Err(Cancelled));
+    }
+}
@@ -1,19 +1,23 @@
 extern crate derive_more;
-extern crate lazy_static;
 extern crate smallvec;
+use crate::context::{Cancelled, Context, Counter};
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult, Scanner};
+use crate::sample;
+use crate::search::Search;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::trace::Event;
 
 use derive_more::Constructor;
-use regex::Regex;
 use smallvec::SmallVec;
-use std::collections::{HashMap, VecDeque};
-use std::fs;
-use std::sync::Mutex;
-
-#[derive(Debug, Constructor, PartialEq, Eq, Hash)]
-struct Valve {
-    name: usize,
-    flow_rate: i32,
-    leads_to: SmallVec<[usize; 10]>,
+use std::collections::{HashMap, HashSet};
+
+/// Valve and the valves its tunnels lead to, by their index in
+/// `Volcano::valves`.
+#[derive(Debug, Constructor, Clone, PartialEq, Eq, Hash)]
+pub struct Valve {
+    pub name: usize,
+    pub flow_rate: i32,
+    pub leads_to: SmallVec<[usize; 10]>,
 }
 
 struct NodeIdMap {
@@ -37,10 +41,15 @@ impl NodeIdMap {
         self.next_id += 1;
         self.next_id - 1
     }
-}
 
-fn match_to_i32(m: &Option<regex::Match>) -> i32 {
-    m.unwrap().as_str().parse::<i32>().unwrap()
+    // Names of the valves, indexed by their id.
+    fn into_names(self) -> Vec<String> {
+        let mut names = vec![String::new(); self.next_id];
+        for (name, id) in self.nodes {
+            names[id] = name;
+        }
+        names
+    }
 }
 
 #[allow(dead_code)]
@@ -53,24 +62,47 @@ fn i32_to_node_name(i: i32) -> String {
     .collect()
 }
 
-lazy_static::lazy_static! {
-    static ref NODE_ID_MAP: Mutex<NodeIdMap> = Mutex::new(NodeIdMap::new());
-}
+// Name, flow rate and tunnels of a valve, as written in the input.
+type ValveLine<'a> = (&'a str, i32, Vec<&'a str>);
 
-impl From<regex::Captures<'_>> for Valve {
-    fn from(capture: regex::Captures) -> Self {
-        let mut node_id_map = NODE_ID_MAP.lock().unwrap();
-        Valve {
-            name: node_id_map.insert_or_get(capture.name("valve").unwrap().as_str()),
-            flow_rate: match_to_i32(&capture.name("rate")),
-            leads_to: capture
-                .name("dest")
-                .unwrap()
-                .as_str()
-                .split(',')
-                .map(|d| node_id_map.insert_or_get(d.trim()))
-                .collect(),
-        }
+impl Valve {
+    fn scan<'a>(line: &Line<'a>) -> ParseResult<ValveLine<'a>> {
+        let mut s = line.scanner();
+        s.tag("Valve ")?;
+        let name = s.word()?;
+        let flow_rate = s.field(" has flow rate=")?;
+        s.one_of(&[
+            ("; tunnels lead to valves ", ()),
+            ("; tunnel leads to valve ", ()),
+        ])?;
+        let leads_to = s.list(", ", Scanner::word)?;
+        s.end()?;
+        Ok((name, flow_rate, leads_to))
+    }
+
+    // `names` are all the valves of the input, tunnels can only lead to one
+    // of them.
+    fn parse(
+        line: &Line,
+        valve: &ValveLine,
+        names: &HashSet<&str>,
+        node_id_map: &mut NodeIdMap,
+    ) -> ParseResult<Self> {
+        let (name, flow_rate, leads_to) = valve;
+        Ok(Valve {
+            name: node_id_map.insert_or_get(name),
+            flow_rate: *flow_rate,
+            leads_to: leads_to
+                .iter()
+                .map(|d| {
+                    if names.contains(d) {
+                        Ok(node_id_map.insert_or_get(d))
+                    } else {
+                        Err(line.error(d, format!("unknown valve '{d}'")))
+                    }
+                })
+                .collect::<ParseResult<_>>()?,
+        })
     }
 }
 
@@ -80,37 +112,29 @@ struct Distance {
     len: usize,
 }
 
-lazy_static::lazy_static! {
-    static ref BFSCACHE: Mutex<HashMap<(usize, usize), Option<Distance>>> = Mutex::new(HashMap::new());
+// Distances between the valves, found as a solve needs them.
+struct Distances {
+    cache: HashMap<(usize, usize), Option<Distance>>,
 }
 
-fn bfs(from: usize, to: usize, valves: &[Valve]) -> Option<Distance> {
-    if let Some(r) = BFSCACHE.lock().unwrap().get(&(from, to)) {
-        return *r;
-    }
-    let mut q: VecDeque<(usize, usize)> = VecDeque::new();
-    q.push_back((from, 1));
-    loop {
-        if q.is_empty() {
-            BFSCACHE.lock().unwrap().insert((from, to), None);
-            return None;
-        }
-        let p = q.pop_front().unwrap();
-        if p.0 == to {
-            BFSCACHE
-                .lock()
-                .unwrap()
-                .insert((from, to), Some(Distance::new(p.0, p.1)));
-            return Some(Distance::new(p.0, p.1));
+impl Distances {
+    fn new() -> Self {
+        Distances {
+            cache: HashMap::new(),
         }
-        valves[p.0]
-            .leads_to
-            .iter()
-            .for_each(|lead| q.push_back((*lead, p.1 + 1)));
+    }
+
+    fn bfs(&mut self, from: usize, to: usize, valves: &[Valve]) -> Option<Distance> {
+        *self.cache.entry((from, to)).or_insert_with(|| {
+            // The length includes the minute spent opening the valve at `to`.
+            Search::new([from])
+                .bfs(|v| valves[*v].leads_to.iter().copied(), |v| *v == to)
+                .map(|found| Distance::new(to, found.cost + 1))
+        })
     }
 }
 
-#[derive(Debug, Constructor, PartialEq, Eq)]
+#[derive(Debug, Constructor, Clone, Copy, PartialEq, Eq)]
 struct PathPotential {
     last_node: usize,
     score: usize,
@@ -136,11 +160,15 @@ fn evaluate_paths(
         .collect::<SmallVec<[PathPotential; 32]>>()
 }
 
-fn paths_for_node(valves: &[Valve], node: usize) -> impl Iterator<Item = Distance> + '_ {
+fn paths_for_node<'a>(
+    valves: &'a [Valve],
+    distances: &'a mut Distances,
+    node: usize,
+) -> impl Iterator<Item = Distance> + 'a {
     valves
         .iter()
         .filter(|valve| valve.flow_rate > 0)
-        .map(move |valve| bfs(node, valve.name, valves).unwrap())
+        .filter_map(move |valve| distances.bfs(node, valve.name, valves))
 }
 
 fn set_flow_rate(valves: &mut [Valve], node_id: usize, new_flow_rate: i32) -> i32 {
@@ -149,22 +177,74 @@ fn set_flow_rate(valves: &mut [Valve], node_id: usize, new_flow_rate: i32) -> i3
     ret
 }
 
-fn search_path(valves: &mut Vec<Valve>, cur_node: usize, minutes_left: usize, sum: usize) -> usize {
+// Valves opened along the route being searched, and along the first route
+// found releasing the most pressure, each with the index of who opens it.
+#[derive(Default)]
+struct Route {
+    opened: Vec<(usize, PathPotential)>,
+    best: Vec<(usize, PathPotential)>,
+    released: usize,
+}
+
+impl Route {
+    fn reach(&mut self, released: usize) {
+        if released > self.released {
+            self.released = released;
+            self.best.clone_from(&self.opened);
+        }
+    }
+}
+
+fn search_path(
+    valves: &mut Vec<Valve>,
+    distances: &mut Distances,
+    cur_node: usize,
+    minutes_left: usize,
+    sum: usize,
+    route: &mut Route,
+) {
+    route.reach(sum);
     let flow_rate_left: i32 = valves.iter().map(|valve| valve.flow_rate).sum();
     if flow_rate_left == 0 {
-        return sum;
+        return;
+    }
+    let potential = evaluate_paths(
+        paths_for_node(valves, distances, cur_node),
+        valves,
+        minutes_left,
+    );
+    for p in &potential {
+        let flow_rate_backup = set_flow_rate(valves, p.last_node, 0);
+        route.opened.push((0, *p));
+        search_path(
+            valves,
+            distances,
+            p.last_node,
+            minutes_left - p.len,
+            sum + p.score,
+            route,
+        );
+        route.opened.pop();
+        valves[p.last_node].flow_rate = flow_rate_backup;
+    }
+}
+
+// What the part 2 search reports to: the nodes it counts, and the trace of
+// the moves it skips, by valve name.
+struct Report<'a> {
+    nodes: Counter<'a>,
+    ctx: &'a Context,
+    names: &'a [String],
+}
+
+impl Report<'_> {
+    fn skip(&self, reason: &'static str, valve: usize) {
+        self.ctx.trace(|| {
+            Event::new("skip")
+                .with("reason", reason)
+                .with("valve", self.names[valve].as_str())
+        });
     }
-    let potential = evaluate_paths(paths_for_node(valves, cur_node), valves, minutes_left);
-    potential
-        .iter()
-        .map(|p| {
-            let flow_rate_backup = set_flow_rate(valves, p.last_node, 0);
-            let r = search_path(valves, p.last_node, minutes_left - p.len, sum + p.score);
-            valves[p.last_node].flow_rate = flow_rate_backup;
-            r
-        })
-        .max()
-        .unwrap_or(sum)
 }
 
 #[derive(Constructor)]
@@ -176,21 +256,26 @@ struct Visitor {
 
 fn search_path_p2(
     valves: &mut Vec<Valve>,
+    distances: &mut Distances,
     you: &Visitor,
     elephant: &Visitor,
     flow_rate_left: i32,
-) -> (usize, usize) {
+    report: &Report,
+    route: &mut Route,
+) -> Result<(usize, usize), Cancelled> {
+    report.nodes.add(1)?;
+    route.reach(you.sum + elephant.sum);
     if flow_rate_left == 0 {
-        return (you.sum, elephant.sum);
+        return Ok((you.sum, elephant.sum));
     }
 
     let potential_you = evaluate_paths(
-        paths_for_node(valves, you.node_id),
+        paths_for_node(valves, distances, you.node_id),
         valves,
         you.minutes_left,
     );
     let potential_elephant = evaluate_paths(
-        paths_for_node(valves, elephant.node_id),
+        paths_for_node(valves, distances, elephant.node_id),
         valves,
         elephant.minutes_left,
     );
@@ -200,10 +285,12 @@ fn search_path_p2(
         //
     } else if potential_you.is_empty() {
         //only elephants moves
-        potential_elephant.iter().for_each(|pp_elephant| {
+        for pp_elephant in &potential_elephant {
             let flow_rate_backup_elephant = set_flow_rate(valves, pp_elephant.last_node, 0);
+            route.opened.push((1, *pp_elephant));
             let r = search_path_p2(
                 valves,
+                distances,
                 you,
                 &Visitor::new(
                     pp_elephant.last_node,
@@ -211,19 +298,24 @@ fn search_path_p2(
                     elephant.sum + pp_elephant.score,
                 ),
                 flow_rate_left - flow_rate_backup_elephant,
-            );
+                report,
+                route,
+            )?;
             if r.0 + r.1 > sum_max.0 + sum_max.1 {
                 sum_max = r;
             }
 
+            route.opened.pop();
             valves[pp_elephant.last_node].flow_rate = flow_rate_backup_elephant;
-        });
+        }
     } else if potential_elephant.is_empty() {
         //only you moves
-        potential_you.iter().for_each(|pp_you| {
+        for pp_you in &potential_you {
             let flow_rate_backup_you = set_flow_rate(valves, pp_you.last_node, 0);
+            route.opened.push((0, *pp_you));
             let r = search_path_p2(
                 valves,
+                distances,
                 &Visitor::new(
                     pp_you.last_node,
                     you.minutes_left - pp_you.len,
@@ -231,28 +323,33 @@ fn search_path_p2(
                 ),
                 elephant,
                 flow_rate_left - flow_rate_backup_you,
-            );
+                report,
+                route,
+            )?;
             if r.0 + r.1 > sum_max.0 + sum_max.1 {
                 sum_max = r;
             }
 
+            route.opened.pop();
             valves[pp_you.last_node].flow_rate = flow_rate_backup_you;
-        });
+        }
     } else {
         for pp_you in &potential_you {
             for pp_elephant in &potential_elephant {
                 if pp_elephant == pp_you {
-                    //println!("SAME PATH");
+                    report.skip("same path", pp_you.last_node);
                     continue;
                 }
                 if pp_elephant.last_node == pp_you.last_node {
-                    //println!("SAME DEST");
+                    report.skip("same destination", pp_you.last_node);
                     continue;
                 }
                 let flow_rate_backup_you = set_flow_rate(valves, pp_you.last_node, 0);
                 let flow_rate_backup_elephant = set_flow_rate(valves, pp_elephant.last_node, 0);
+                route.opened.extend([(0, *pp_you), (1, *pp_elephant)]);
                 let r = search_path_p2(
                     valves,
+                    distances,
                     &Visitor::new(
                         pp_you.last_node,
                         you.minutes_left - pp_you.len,
@@ -264,64 +361,277 @@ fn search_path_p2(
                         elephant.sum + pp_elephant.score,
                     ),
                     flow_rate_left - flow_rate_backup_elephant - flow_rate_backup_you,
-                );
+                    report,
+                    route,
+                )?;
                 if r.0 + r.1 > sum_max.0 + sum_max.1 {
                     sum_max = r;
                 }
 
+                route.opened.truncate(route.opened.len() - 2);
                 valves[pp_you.last_node].flow_rate = flow_rate_backup_you;
                 valves[pp_elephant.last_node].flow_rate = flow_rate_backup_elephant;
             }
         }
     }
-    sum_max
+    report.nodes.best((sum_max.0 + sum_max.1) as i64);
+    Ok(sum_max)
+}
+
+/// Valves of the volcano, which `parse` checks only lead to one another.
+#[derive(Debug)]
+pub struct Volcano {
+    valves: Vec<Valve>,
+    start: usize,
+    names: Vec<String>,
+}
+
+impl Volcano {
+    /// Valves, each at the index it's named by.
+    pub fn valves(&self) -> &[Valve] {
+        &self.valves
+    }
+
+    /// Index of valve AA, where the search starts.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Names of the valves as written in the input, by index.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
 }
 
-fn solution(filename: &str) -> (usize, usize) {
-    NODE_ID_MAP.lock().unwrap().nodes.clear();
-    NODE_ID_MAP.lock().unwrap().next_id = 0;
-    BFSCACHE.lock().unwrap().clear();
-    let rex =
-        Regex::new(r"Valve\s(?P<valve>[A-Z]*)\shas\sflow\srate=(?P<rate>\d*);\stunnels?\sleads?\sto\svalves?\s(?P<dest>([A-Z]*,?\s?)+)").unwrap();
-    let mut valves: Vec<Valve> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|l| Valve::from(rex.captures(l).unwrap()))
-        .flat_map(|v| vec![v])
-        .collect();
+pub fn parse(input: &str) -> ParseResult<Volcano> {
+    let input = &*normalize(input, Normalize::default());
+    let scanned = lines(input)
+        .map(|line| Ok((line, Valve::scan(&line)?)))
+        .collect::<ParseResult<Vec<_>>>()?;
+    let names = scanned
+        .iter()
+        .map(|(_, (name, ..))| *name)
+        .collect::<HashSet<&str>>();
+    if !names.contains("AA") {
+        return Err(ParseError::new(1, 1, "missing valve 'AA'"));
+    }
+    let mut node_id_map = NodeIdMap::new();
+    let mut valves: Vec<Valve> = scanned
+        .iter()
+        .map(|(line, valve)| Valve::parse(line, valve, &names, &mut node_id_map))
+        .collect::<ParseResult<_>>()?;
     valves.sort_by_key(|v| v.name);
+    let start = node_id_map.insert_or_get("AA");
+    Ok(Volcano {
+        valves,
+        start,
+        names: node_id_map.into_names(),
+    })
+}
 
-    let p1 = search_path(
-        &mut valves,
-        NODE_ID_MAP.lock().unwrap().insert_or_get("AA"),
+// Traces the valves opened along the best route the search found, in the
+// order they're opened. `by` names who opens them when there's more than one.
+fn trace_route(volcano: &Volcano, route: &Route, by: &[&'static str], ctx: &Context) {
+    let mut minutes = [0; 2];
+    let mut opened = route
+        .best
+        .iter()
+        .map(|(who, p)| {
+            minutes[*who] += p.len;
+            (minutes[*who], *who, p)
+        })
+        .collect::<Vec<_>>();
+    opened.sort_by_key(|(minute, who, _)| (*minute, *who));
+    for (minute, who, p) in opened {
+        ctx.trace(|| {
+            let event = match by.get(who) {
+                Some(name) => Event::new("open").with("by", *name),
+                None => Event::new("open"),
+            };
+            event
+                .with("valve", volcano.names[p.last_node].as_str())
+                .with("minute", minute)
+                .with("released", p.score)
+        });
+    }
+}
+
+pub fn part1(volcano: &Volcano, ctx: &Context) -> usize {
+    let mut route = Route::default();
+    search_path(
+        &mut volcano.valves.clone(),
+        &mut Distances::new(),
+        volcano.start,
         30,
         0,
+        &mut route,
     );
+    trace_route(volcano, &route, &[], ctx);
+    route.released
+}
 
-    let mut node_id_map = NODE_ID_MAP.lock().unwrap();
+pub fn part2(volcano: &Volcano, ctx: &Context) -> Result<usize, Cancelled> {
+    let mut valves = volcano.valves.clone();
     let flow_rate_left = valves.iter().map(|valve| valve.flow_rate).sum();
+    let mut route = Route::default();
     let p2 = search_path_p2(
         &mut valves,
-        &Visitor::new(node_id_map.insert_or_get("AA"), 26, 0),
-        &Visitor::new(node_id_map.insert_or_get("AA"), 26, 0),
+        &mut Distances::new(),
+        &Visitor::new(volcano.start, 26, 0),
+        &Visitor::new(volcano.start, 26, 0),
         flow_rate_left,
-    );
-
-    (p1, p2.0 + p2.1)
+        &Report {
+            nodes: Counter::new(ctx, "nodes", None, 100_000),
+            ctx,
+            names: &volcano.names,
+        },
+        &mut route,
+    )?;
+    trace_route(volcano, &route, &["you", "elephant"], ctx);
+    Ok(p2.0 + p2.1)
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_16.input");
-    (p1.to_string(), p2.to_string())
+pub struct Day16;
+
+impl Solver for Day16 {
+    type Input = Volcano;
+
+    fn day(&self) -> usize {
+        16
+    }
+
+    fn title(&self) -> &'static str {
+        "Proboscidea Volcanium"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, volcano: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(part1(volcano, ctx).to_string())
+    }
+
+    fn part2(&self, volcano: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(part2(volcano, ctx)?.to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_16_sample.input").part1("1651").part2("1707")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use crate::trace::Trace;
+    use std::fs;
+    use std::sync::Arc;
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_16_sample.input"), (1651, 1707));
-        assert_eq!(solution("src/inputs/aoc_16.input"), (1873, 2425));
+        let ctx = Context::default();
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_16_sample.input").unwrap()).unwrap();
+        let volcano = parse(&fs::read_to_string("src/inputs/aoc_16.input").unwrap()).unwrap();
+        assert_answer(16, Part::One, "aoc_16_sample.input", part1(&sample, &ctx));
+        assert_answer(
+            16,
+            Part::Two,
+            "aoc_16_sample.input",
+            part2(&sample, &ctx).unwrap(),
+        );
+        assert_answer(16, Part::One, "aoc_16.input", part1(&volcano, &ctx));
+        assert_answer(
+            16,
+            Part::Two,
+            "aoc_16.input",
+            part2(&volcano, &ctx).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_unreachable_valve() {
+        let ctx = Context::default();
+        let volcano = parse(
+            "Valve AA has flow rate=0; tunnels lead to valves BB, DD\n\
+             Valve BB has flow rate=5; tunnels lead to valves AA\n\
+             Valve CC has flow rate=9; tunnels lead to valves AA\n\
+             Valve DD has flow rate=3; tunnels lead to valves AA",
+        )
+        .unwrap();
+        assert_eq!(part1(&volcano, &ctx), 5 * 28 + 3 * 25);
+        assert_eq!(part2(&volcano, &ctx), Ok(5 * 24 + 3 * 24));
+    }
+
+    #[test]
+    fn test_concurrent_inputs() {
+        let inputs = ["aoc_16_sample.input", "aoc_16.input"];
+        std::thread::scope(|s| {
+            for name in inputs {
+                s.spawn(move || {
+                    let input = fs::read_to_string(format!("src/inputs/{name}")).unwrap();
+                    let volcano = parse(&input).unwrap();
+                    assert_answer(16, Part::One, name, part1(&volcano, &Context::default()));
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_trace_route() {
+        let path = crate::client::mock::temp_path("day16.jsonl");
+        let trace = Trace::create(path.to_str().unwrap()).unwrap();
+        let ctx = Context::default()
+            .traced(Arc::new(trace))
+            .in_part(16, Part::One);
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_16_sample.input").unwrap()).unwrap();
+        assert_eq!(part1(&sample, &ctx), 1651);
+        let opened = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| line[line.find("\"valve\"").unwrap()..].to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            opened,
+            [
+                "\"valve\":\"DD\",\"minute\":2,\"released\":560}",
+                "\"valve\":\"BB\",\"minute\":5,\"released\":325}",
+                "\"valve\":\"JJ\",\"minute\":9,\"released\":441}",
+                "\"valve\":\"HH\",\"minute\":17,\"released\":286}",
+                "\"valve\":\"EE\",\"minute\":21,\"released\":27}",
+                "\"valve\":\"CC\",\"minute\":24,\"released\":12}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_part2() {
+        let path = crate::client::mock::temp_path("day16-part2.jsonl");
+        let trace = Trace::create(path.to_str().unwrap()).unwrap();
+        let ctx = Context::default()
+            .traced(Arc::new(trace))
+            .in_part(16, Part::Two);
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_16_sample.input").unwrap()).unwrap();
+        assert_eq!(part2(&sample, &ctx), Ok(1707));
+        let trace = fs::read_to_string(&path).unwrap();
+        assert!(trace.contains("\"event\":\"skip\",\"reason\":\"same path\",\"valve\":\"DD\"}"));
+        assert!(trace.contains("\"event\":\"skip\",\"reason\":\"same destination\","));
+        let opened = trace
+            .lines()
+            .filter_map(|line| line.find("\"by\"").map(|at| &line[at..]))
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            opened,
+            [
+                "\"by\":\"you\",\"valve\":\"DD\",\"minute\":2,\"released\":480}",
+                "\"by\":\"elephant\",\"valve\":\"JJ\",\"minute\":3,\"released\":483}",
+                "\"by\":\"you\",\"valve\":\"HH\",\"minute\":7,\"released\":418}",
+                "\"by\":\"elephant\",\"valve\":\"BB\",\"minute\":7,\"released\":247}",
+                "\"by\":\"elephant\",\"valve\":\"CC\",\"minute\":9,\"released\":34}",
+                "\"by\":\"you\",\"valve\":\"EE\",\"minute\":11,\"released\":45}",
+            ]
+        );
     }
 }
@@ -1,7 +1,14 @@
 extern crate derive_more;
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{lines, normalize, Normalize, ParseError, ParseResult};
+use crate::pos::{Direction, Pos};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::visualize::{Frame, Pixel};
 use derive_more::Constructor;
 use std::cmp;
-use std::fs;
+use std::iter;
 
 #[derive(Debug, Constructor, Clone, Copy)]
 struct Shape<const X: usize, const Y: usize> {
@@ -34,17 +41,6 @@ impl Shapes {
             Shapes::S41(s) => &s.p[3 - n],
         }
     }
-
-    #[allow(dead_code)]
-    fn print_shape(&self) {
-        for i in (0..self.height()).rev() {
-            let row = self.get_row(i);
-            for c in row {
-                print!("{}", if *c { '@' } else { ' ' });
-            }
-            println!();
-        }
-    }
 }
 
 static SHAPES: [Shapes; 5] = [
@@ -73,198 +69,264 @@ static SHAPES: [Shapes; 5] = [
     }),
 ];
 
+const AIR: Pixel = Pixel::new('.', [0, 0, 0]);
+const ROCK: Pixel = Pixel::new('#', [200, 90, 40]);
+const WALL: Pixel = Pixel::new('|', [128, 128, 128]);
+const FLOOR: Pixel = Pixel::new('-', [128, 128, 128]);
+const CORNER: Pixel = Pixel::new('+', [128, 128, 128]);
+
+// Rows of the tower shown, from its top.
+const FRAME_ROWS: usize = 30;
+// Rocks shown as they come to rest, part two drops far more.
+const FRAME_ROCKS: usize = 2022;
+
 #[derive(Debug, Clone, Copy)]
-enum Move {
+pub enum Move {
     Left,
     Right,
 }
 
-#[derive(Debug, Constructor, Clone, Copy)]
-struct Pos {
-    x: usize,
-    y: usize,
-}
-
 #[derive(Debug)]
 struct TheGame {
-    grid: Vec<Vec<bool>>,
+    // Upside down, the floor is the first row.
+    grid: Grid<bool>,
     moves: Vec<Move>,
     current_move: usize,
-    grid_width: usize,
 }
 
 impl TheGame {
     fn new(moves: &[Move], grid_width: usize) -> Self {
         Self {
-            grid: Vec::new(),
+            grid: Grid::new(grid_width, 0, false),
             moves: moves.to_owned(),
             current_move: 0,
-            grid_width,
         }
     }
 
-    #[allow(dead_code)]
-    fn print_grid(&self) {
-        for r in self.grid.iter().rev() {
-            println!(
-                "|{}|",
-                r.iter()
-                    .map(|c| if *c { '@' } else { ' ' })
-                    .collect::<String>()
-            );
-        }
-        println!("{}", "-".repeat(self.grid_width + 2));
+    // The top of the tower between its walls, down to the floor until the
+    // tower is taller than the frame.
+    fn frame(&self) -> Frame {
+        let top = cmp::max(self.grid.height(), FRAME_ROWS - 1) as i64;
+        Grid::from_rows((top - FRAME_ROWS as i64..top).rev().map(|y| {
+            let (side, fill) = match y {
+                -1 => (CORNER, FLOOR),
+                _ => (WALL, AIR),
+            };
+            iter::once(side)
+                .chain(
+                    (0..self.grid.width()).map(|x| match self.grid.get((x, y as usize)) {
+                        Some(true) => ROCK,
+                        _ => fill,
+                    }),
+                )
+                .chain(iter::once(side))
+                .collect()
+        }))
     }
 
-    fn can_move_to(&self, x: usize, y: usize) -> bool {
-        if y >= self.grid.len() {
-            // We are above the grid, all moves are ok.
-            true
-        } else {
-            // Check the actual position in the grid.
-            !self.grid[y][x]
+    // Whether the shape fits with its bottom left corner at `p`.
+    fn fits(&self, s: &Shapes, p: Pos) -> bool {
+        if p.x < 0 || p.y < 0 || p.x as usize + s.get_row(0).len() > self.grid.width() {
+            return false;
         }
+        let (x, y) = (p.x as usize, p.y as usize);
+        // Above the grid, all moves are ok.
+        (0..s.height())
+            .take_while(|j| y + j < self.grid.height())
+            .all(|j| {
+                s.get_row(j)
+                    .iter()
+                    .enumerate()
+                    .all(|(i, c)| !c || !self.grid[(x + i, y + j)])
+            })
     }
 
-    fn can_move_row(&self, row: &[bool], x: usize, y: usize, m: &Move) -> bool {
-        for (i, c) in row.iter().enumerate() {
-            if !*c {
-                continue;
-            }
-            match m {
-                Move::Left => {
-                    if x == 0 || !self.can_move_to(x - 1 + i, y) {
-                        return false;
-                    }
-                }
-                Move::Right => {
-                    if x + row.len() >= self.grid_width || !self.can_move_to(x + i + 1, y) {
-                        return false;
-                    }
-                }
-            }
-        }
-        true
-    }
-
-    fn apply_jet(&self, s: &Shapes, cp: &Pos, m: &Move) -> Option<Pos> {
-        for i in 0..s.height() {
-            if !self.can_move_row(s.get_row(i), cp.x, cp.y + i, m) {
-                return None;
-            }
-        }
-        match m {
-            Move::Left => Some(Pos::new(cp.x - 1, cp.y)),
-            Move::Right => Some(Pos::new(cp.x + 1, cp.y)),
-        }
+    fn apply_jet(&self, s: &Shapes, cp: Pos, m: &Move) -> Option<Pos> {
+        let p = match m {
+            Move::Left => cp + Direction::Left,
+            Move::Right => cp + Direction::Right,
+        };
+        self.fits(s, p).then_some(p)
     }
 
-    fn fall(&self, s: &Shapes, cp: &Pos) -> Option<Pos> {
-        if cp.y == 0 {
-            None
-        } else if cp.y > self.grid.len() {
-            Some(Pos::new(cp.x, cp.y - 1))
-        } else {
-            for j in 0..s.height() {
-                if cp.y - 1 + j >= self.grid.len() {
-                    continue;
-                }
-                for (i, c) in s.get_row(j).iter().enumerate() {
-                    if *c && self.grid[cp.y - 1 + j][cp.x + i] {
-                        return None;
-                    }
-                }
-            }
-            Some(Pos::new(cp.x, cp.y - 1))
-        }
+    fn fall(&self, s: &Shapes, cp: Pos) -> Option<Pos> {
+        // The grid is upside down, so falling goes up the rows.
+        let p = cp + Direction::Up;
+        self.fits(s, p).then_some(p)
     }
 
-    fn add_shape_to_the_grid(&mut self, s: &Shapes, p: &Pos) {
-        let delta = s.height() as i32 + p.y as i32 - self.grid.len() as i32;
-        if delta > 0 {
-            (0..delta).for_each(|_| self.grid.push(vec![false; self.grid_width]));
+    fn add_shape_to_the_grid(&mut self, s: &Shapes, p: Pos) {
+        let top = p.y as usize + s.height();
+        while self.grid.height() < top {
+            self.grid.push_row(vec![false; self.grid.width()]);
         }
         for i in 0..s.height() {
             for (j, c) in s.get_row(i).iter().enumerate() {
                 if *c {
-                    self.grid[p.y + i][p.x + j] = true;
+                    self.grid[p + Pos::new(j as i64, i as i64)] = true;
                 }
             }
         }
     }
 
     fn play_shape(&mut self, s: &Shapes) {
-        let mut cp: Pos = Pos::new(2, self.grid.len() + 3);
+        let mut cp = Pos::new(2, self.grid.height() as i64 + 3);
         loop {
             cp = self
-                .apply_jet(s, &cp, &self.moves[self.current_move % self.moves.len()])
+                .apply_jet(s, cp, &self.moves[self.current_move % self.moves.len()])
                 .unwrap_or(cp);
             self.current_move += 1;
-            if let Some(p) = self.fall(s, &cp) {
+            if let Some(p) = self.fall(s, cp) {
                 cp = p;
             } else {
-                self.add_shape_to_the_grid(s, &cp);
-                //s.print_shape();
-                //self.print_grid();
+                self.add_shape_to_the_grid(s, cp);
                 break;
             }
         }
     }
 }
 
-fn solution(filename: &str, interval: usize, n: i64) -> usize {
-    let moves = fs::read_to_string(filename)
-        .unwrap()
-        .trim()
-        .chars()
-        .map(|m| if m == '<' { Move::Left } else { Move::Right })
-        .collect::<Vec<Move>>();
+pub fn parse(input: &str) -> ParseResult<Vec<Move>> {
+    let input = &*normalize(input, Normalize::default());
+    let line = lines(input)
+        .next()
+        .ok_or_else(|| ParseError::new(1, 1, "empty input"))?;
+    line.text
+        .trim_end()
+        .char_indices()
+        .map(|(i, m)| match m {
+            '<' => Ok(Move::Left),
+            '>' => Ok(Move::Right),
+            _ => Err(line.error_at(i, "invalid jet, expected '<' or '>'")),
+        })
+        .collect()
+}
 
-    let mut the_game = TheGame::new(&moves, 7);
+pub fn solution(moves: &[Move], interval: usize, n: i64, ctx: &Context) -> usize {
+    let mut the_game = TheGame::new(moves, 7);
 
     let mut prev_h = 0;
     let iterations = moves.len() * SHAPES.len() * interval;
     let mut next_heights = 0;
     for i in 0..cmp::min(n as usize, 2 * iterations + 1) {
         if i != 0 && i % iterations == 0 {
-            next_heights = the_game.grid.len() - prev_h;
-            prev_h = the_game.grid.len();
+            next_heights = the_game.grid.height() - prev_h;
+            prev_h = the_game.grid.height();
         }
         the_game.play_shape(&SHAPES[i % SHAPES.len()]);
+        if i < FRAME_ROCKS {
+            ctx.frame(|| the_game.frame());
+        }
     }
     let iterations_left = n - (2 * iterations + 1) as i64;
     if iterations_left > 0 {
         for i in 0..iterations_left as usize % iterations {
             the_game.play_shape(&SHAPES[(i + 2 * iterations + 1) % SHAPES.len()]);
         }
-        the_game.grid.len() + next_heights * (iterations_left as usize / iterations)
+        the_game.grid.height() + next_heights * (iterations_left as usize / iterations)
     } else {
-        the_game.grid.len()
+        the_game.grid.height()
+    }
+}
+
+#[derive(Constructor)]
+pub struct Day17 {
+    // Number of jet pattern cycles after which the tower height repeats,
+    // 7 for the sample input.
+    interval: usize,
+}
+
+impl Default for Day17 {
+    fn default() -> Self {
+        Day17 { interval: 341 }
     }
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution("src/inputs/aoc_17.input", 341, 2022).to_string(),
-        solution("src/inputs/aoc_17.input", 341, 1_000_000_000_000).to_string(),
-    )
+impl Solver for Day17 {
+    type Input = Vec<Move>;
+
+    fn day(&self) -> usize {
+        17
+    }
+
+    fn title(&self) -> &'static str {
+        "Pyroclastic Flow"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, moves: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(moves, self.interval, 2022, ctx).to_string())
+    }
+
+    fn part2(&self, moves: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(moves, self.interval, 1_000_000_000_000, ctx).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_17_sample.input")
+            .solver(Day17::new(7))
+            .part1("3068")
+            .part2("1514285714288")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use std::fs;
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_17_sample.input", 7, 2022), 3068);
-        assert_eq!(
-            solution("src/inputs/aoc_17_sample.input", 7, 1_000_000_000_000),
-            1_514_285_714_288
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_17_sample.input").unwrap()).unwrap();
+        let moves = parse(&fs::read_to_string("src/inputs/aoc_17.input").unwrap()).unwrap();
+        let ctx = Context::default();
+        assert_answer(
+            17,
+            Part::One,
+            "aoc_17_sample.input",
+            solution(&sample, 7, 2022, &ctx),
+        );
+        assert_answer(
+            17,
+            Part::Two,
+            "aoc_17_sample.input",
+            solution(&sample, 7, 1_000_000_000_000, &ctx),
+        );
+        assert_answer(
+            17,
+            Part::One,
+            "aoc_17.input",
+            solution(&moves, 341, 2022, &ctx),
         );
-        assert_eq!(solution("src/inputs/aoc_17.input", 341, 2022), 3153);
-        assert_eq!(
-            solution("src/inputs/aoc_17.input", 341, 1_000_000_000_000),
-            1_553_665_689_155
+        assert_answer(
+            17,
+            Part::Two,
+            "aoc_17.input",
+            solution(&moves, 341, 1_000_000_000_000, &ctx),
         );
     }
+
+    #[test]
+    fn test_frame() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_17_sample.input").unwrap()).unwrap();
+        let mut game = TheGame::new(&sample, 7);
+        SHAPES[..3].iter().for_each(|s| game.play_shape(s));
+        let frame = game.frame().map(|p| p.glyph).to_string();
+        assert_eq!(frame.lines().count(), FRAME_ROWS);
+        assert!(frame.ends_with(
+            "|.......|\n\
+             |..#....|\n\
+             |..#....|\n\
+             |####...|\n\
+             |..###..|\n\
+             |...#...|\n\
+             |..####.|\n\
+             +-------+"
+        ));
+    }
 }
@@ -1,7 +1,13 @@
-use itertools::Itertools;
-use std::{collections::HashSet, fs};
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::search::Search;
+use crate::solver::{PartResult, Sample, Solver};
+use std::collections::HashSet;
 
-fn is_boundary(cube: &(usize, usize, usize), pond: &Vec<Vec<Vec<bool>>>) -> bool {
+type Cube = (usize, usize, usize);
+
+fn is_boundary(cube: &Cube, pond: &[Vec<Vec<bool>>]) -> bool {
     cube.0 == pond.len() - 1
         || cube.0 == 0
         || cube.1 == pond[0].len() - 1
@@ -10,104 +16,85 @@ fn is_boundary(cube: &(usize, usize, usize), pond: &Vec<Vec<Vec<bool>>>) -> bool
         || cube.2 == 0
 }
 
-fn has_surface_access(
-    cube: &(usize, usize, usize),
-    pond: &Vec<Vec<Vec<bool>>>,
-    visited: &mut HashSet<(usize, usize, usize)>,
-) -> bool {
-    if visited.contains(cube) {
-        return false;
-    }
-    visited.insert(*cube);
-    if pond[cube.0][cube.1][cube.2] {
-        return false;
-    }
-    if is_boundary(cube, pond) {
-        return true;
-    }
-    if has_surface_access(&(cube.0 + 1, cube.1, cube.2), pond, visited) {
-        return true;
-    }
-    if has_surface_access(&(cube.0 - 1, cube.1, cube.2), pond, visited) {
-        return true;
-    }
-    if has_surface_access(&(cube.0, cube.1 + 1, cube.2), pond, visited) {
-        return true;
-    }
-    if has_surface_access(&(cube.0, cube.1 - 1, cube.2), pond, visited) {
-        return true;
-    }
-    if has_surface_access(&(cube.0, cube.1, cube.2 + 1), pond, visited) {
-        return true;
-    }
-    if has_surface_access(&(cube.0, cube.1, cube.2 - 1), pond, visited) {
-        return true;
-    }
-    false
+fn is_air(cube: &Cube, pond: &[Vec<Vec<bool>>]) -> bool {
+    !pond[cube.0][cube.1][cube.2]
 }
 
-fn is_empty_cube(
-    cube: &(usize, usize, usize),
-    pond: &Vec<Vec<Vec<bool>>>,
-    needs_surface_access: bool,
-) -> usize {
-    if !pond[cube.0][cube.1][cube.2] {
-        if needs_surface_access {
-            let mut visited: HashSet<(usize, usize, usize)> = HashSet::new();
-            if has_surface_access(&(cube.0, cube.1, cube.2), pond, &mut visited) {
-                1
-            } else {
-                0
-            }
-        } else {
-            1
-        }
-    } else {
-        0
-    }
+// Cubes sharing a side with `cube`, within the pond.
+fn neighbours(cube: &Cube, pond: &[Vec<Vec<bool>>]) -> impl Iterator<Item = Cube> {
+    let (x, y, z) = *cube;
+    let size = (pond.len(), pond[0].len(), pond[0][0].len());
+    [
+        (x + 1, y, z),
+        (x.wrapping_sub(1), y, z),
+        (x, y + 1, z),
+        (x, y.wrapping_sub(1), z),
+        (x, y, z + 1),
+        (x, y, z.wrapping_sub(1)),
+    ]
+    .into_iter()
+    .filter(move |c| c.0 < size.0 && c.1 < size.1 && c.2 < size.2)
+}
+
+// Air cubes connected to the boundary of the pond, which the outside air
+// can get to.
+fn outside_air(pond: &[Vec<Vec<bool>>]) -> HashSet<Cube> {
+    let boundary = (0..pond.len())
+        .flat_map(|x| {
+            (0..pond[0].len()).flat_map(move |y| (0..pond[0][0].len()).map(move |z| (x, y, z)))
+        })
+        .filter(|c| is_boundary(c, pond) && is_air(c, pond));
+    Search::new(boundary).reachable(|c| neighbours(c, pond).filter(|n| is_air(n, pond)))
+}
+
+// Whether `cube` is air, and the outside air gets to it when `outside` is
+// given.
+fn is_empty_cube(cube: &Cube, pond: &[Vec<Vec<bool>>], outside: Option<&HashSet<Cube>>) -> usize {
+    usize::from(is_air(cube, pond) && outside.is_none_or(|outside| outside.contains(cube)))
 }
 
 fn get_not_connected_sides_for_cube(
-    cube: &(usize, usize, usize),
-    pond: &Vec<Vec<Vec<bool>>>,
-    needs_surface_access: bool,
+    cube: &Cube,
+    pond: &[Vec<Vec<bool>>],
+    outside: Option<&HashSet<Cube>>,
 ) -> usize {
     let mut r: usize = 0;
 
     if cube.0 < pond.len() - 1 {
-        r += is_empty_cube(&(cube.0 + 1, cube.1, cube.2), pond, needs_surface_access);
+        r += is_empty_cube(&(cube.0 + 1, cube.1, cube.2), pond, outside);
     } else {
         r += 1;
     }
     if cube.0 > 0 {
-        r += is_empty_cube(&(cube.0 - 1, cube.1, cube.2), pond, needs_surface_access);
+        r += is_empty_cube(&(cube.0 - 1, cube.1, cube.2), pond, outside);
     } else {
         r += 1;
     }
     if cube.1 < pond[0].len() - 1 {
-        r += is_empty_cube(&(cube.0, cube.1 + 1, cube.2), pond, needs_surface_access);
+        r += is_empty_cube(&(cube.0, cube.1 + 1, cube.2), pond, outside);
     } else {
         r += 1;
     }
     if cube.1 > 0 {
-        r += is_empty_cube(&(cube.0, cube.1 - 1, cube.2), pond, needs_surface_access);
+        r += is_empty_cube(&(cube.0, cube.1 - 1, cube.2), pond, outside);
     } else {
         r += 1;
     }
     if cube.2 < pond[0][0].len() - 1 {
-        r += is_empty_cube(&(cube.0, cube.1, cube.2 + 1), pond, needs_surface_access);
+        r += is_empty_cube(&(cube.0, cube.1, cube.2 + 1), pond, outside);
     } else {
         r += 1;
     }
     if cube.2 > 0 {
-        r += is_empty_cube(&(cube.0, cube.1, cube.2 - 1), pond, needs_surface_access);
+        r += is_empty_cube(&(cube.0, cube.1, cube.2 - 1), pond, outside);
     } else {
         r += 1;
     }
     r
 }
 
-fn get_not_connected_sides(pond: &Vec<Vec<Vec<bool>>>, surface_access: bool) -> usize {
+pub fn get_not_connected_sides(pond: &[Vec<Vec<bool>>], surface_access: bool) -> usize {
+    let outside = surface_access.then(|| outside_air(pond));
     pond.iter()
         .enumerate()
         .map(|(x, ry)| {
@@ -118,7 +105,7 @@ fn get_not_connected_sides(pond: &Vec<Vec<Vec<bool>>>, surface_access: bool) ->
                         .enumerate()
                         .map(|(z, b)| {
                             if *b {
-                                get_not_connected_sides_for_cube(&(x, y, z), pond, surface_access)
+                                get_not_connected_sides_for_cube(&(x, y, z), pond, outside.as_ref())
                             } else {
                                 0
                             }
@@ -130,18 +117,19 @@ fn get_not_connected_sides(pond: &Vec<Vec<Vec<bool>>>, surface_access: bool) ->
         .sum()
 }
 
-fn solution(filename: &str) -> (usize, usize) {
-    let cubes: Vec<(usize, usize, usize)> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|l| {
-            l.split(',')
-                .map(|v| v.parse::<usize>().unwrap())
-                .tuples()
-                .next()
-                .unwrap()
-        })
-        .collect();
+pub fn parse(input: &str) -> ParseResult<Vec<Vec<Vec<bool>>>> {
+    let input = &*normalize(input, Normalize::default());
+    let cubes = lines(input)
+        .map(
+            |line| match line.text.split(',').collect::<Vec<&str>>()[..] {
+                [x, y, z] => Ok((line.number(x)?, line.number(y)?, line.number(z)?)),
+                _ => Err(line.error(line.text, "expected 'X,Y,Z'")),
+            },
+        )
+        .collect::<ParseResult<Vec<(usize, usize, usize)>>>()?;
+    if cubes.is_empty() {
+        return Err(ParseError::new(1, 1, "no cubes"));
+    }
 
     let max_x = cubes.iter().map(|c| c.0).max().unwrap() + 1;
     let max_y = cubes.iter().map(|c| c.1).max().unwrap() + 1;
@@ -149,25 +137,134 @@ fn solution(filename: &str) -> (usize, usize) {
 
     let mut pond: Vec<Vec<Vec<bool>>> = vec![vec![vec![false; max_z]; max_y]; max_x];
     cubes.iter().for_each(|c| pond[c.0][c.1][c.2] = true);
-
-    (
-        get_not_connected_sides(&pond, false),
-        get_not_connected_sides(&pond, true),
-    )
+    Ok(pond)
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_18.input");
-    (p1.to_string(), p2.to_string())
+pub struct Day18;
+
+impl Solver for Day18 {
+    type Input = Vec<Vec<Vec<bool>>>;
+
+    fn day(&self) -> usize {
+        18
+    }
+
+    fn title(&self) -> &'static str {
+        "Boiling Boulders"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, pond: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(get_not_connected_sides(pond, false).to_string())
+    }
+
+    fn part2(&self, pond: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(get_not_connected_sides(pond, true).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_18_sample.input").part1("64").part2("58")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    type Cubes = HashSet<(i32, i32, i32)>;
+
+    fn sides((x, y, z): (i32, i32, i32)) -> [(i32, i32, i32); 6] {
+        [
+            (x + 1, y, z),
+            (x - 1, y, z),
+            (x, y + 1, z),
+            (x, y - 1, z),
+            (x, y, z + 1),
+            (x, y, z - 1),
+        ]
+    }
+
+    fn naive_surface(cubes: &Cubes) -> usize {
+        cubes
+            .iter()
+            .flat_map(|c| sides(*c))
+            .filter(|s| !cubes.contains(s))
+            .count()
+    }
+
+    // Floods the box one cube larger than the droplet from a corner, and
+    // counts the sides of the cubes the water touches.
+    fn naive_exterior_surface(cubes: &Cubes) -> usize {
+        let max = cubes.iter().flat_map(|c| [c.0, c.1, c.2]).max().unwrap() + 1;
+        let mut water = HashSet::from([(-1, -1, -1)]);
+        let mut stack = vec![(-1, -1, -1)];
+        while let Some(cube) = stack.pop() {
+            for side in sides(cube) {
+                let inside = [side.0, side.1, side.2]
+                    .iter()
+                    .all(|v| (-1..=max).contains(v));
+                if inside && !cubes.contains(&side) && water.insert(side) {
+                    stack.push(side);
+                }
+            }
+        }
+        cubes
+            .iter()
+            .flat_map(|c| sides(*c))
+            .filter(|s| water.contains(s))
+            .count()
+    }
+
+    proptest! {
+        #[test]
+        fn test_surface_matches_naive(cubes in prop::collection::hash_set((0..6, 0..6, 0..6), 1..60)) {
+            let input = cubes
+                .iter()
+                .map(|(x, y, z)| format!("{x},{y},{z}\n"))
+                .collect::<String>();
+            let pond = parse(&input).unwrap();
+            prop_assert_eq!(get_not_connected_sides(&pond, false), naive_surface(&cubes));
+            prop_assert_eq!(
+                get_not_connected_sides(&pond, true),
+                naive_exterior_surface(&cubes)
+            );
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_18_sample.input"), (64, 58));
-        assert_eq!(solution("src/inputs/aoc_18.input"), (4314, 2444));
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_18_sample.input").unwrap()).unwrap();
+        let pond = parse(&fs::read_to_string("src/inputs/aoc_18.input").unwrap()).unwrap();
+        assert_answer(
+            18,
+            Part::One,
+            "aoc_18_sample.input",
+            get_not_connected_sides(&sample, false),
+        );
+        assert_answer(
+            18,
+            Part::Two,
+            "aoc_18_sample.input",
+            get_not_connected_sides(&sample, true),
+        );
+        assert_answer(
+            18,
+            Part::One,
+            "aoc_18.input",
+            get_not_connected_sides(&pond, false),
+        );
+        assert_answer(
+            18,
+            Part::Two,
+            "aoc_18.input",
+            get_not_connected_sides(&pond, true),
+        );
     }
 }
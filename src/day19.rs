@@ -1,19 +1,23 @@
 extern crate derive_more;
+use crate::context::{Cancelled, Context, Counter};
+use crate::parse::{lines, normalize, Line, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 
 use derive_more::Constructor;
 use rayon::prelude::*;
-use regex::Regex;
-use std::fs;
 
+/// Cost of a robot.
 #[derive(Debug, Constructor)]
-struct Robot {
-    ore_cost: i32,
-    clay_cost: i32,
-    obsidian_cost: i32,
+pub struct Robot {
+    pub ore_cost: i32,
+    pub clay_cost: i32,
+    pub obsidian_cost: i32,
 }
 
+/// Blueprint, along with the most of each resource its robots cost.
 #[derive(Debug)]
-struct Blueprint {
+pub struct Blueprint {
     id: i32,
     ore_robot: Robot,
     clay_robot: Robot,
@@ -24,26 +28,46 @@ struct Blueprint {
     max_obsidian: i32,
 }
 
-fn match_to_i32(m: &Option<regex::Match>) -> i32 {
-    m.unwrap().as_str().parse::<i32>().unwrap()
-}
+impl Blueprint {
+    pub fn id(&self) -> i32 {
+        self.id
+    }
 
-impl From<regex::Captures<'_>> for Blueprint {
-    fn from(capture: regex::Captures) -> Self {
-        let ore_robot = Robot::new(match_to_i32(&capture.name("ore_robot_cost_ore")), 0, 0);
-        let clay_robot = Robot::new(match_to_i32(&capture.name("clay_robot_cost_ore")), 0, 0);
+    pub fn ore_robot(&self) -> &Robot {
+        &self.ore_robot
+    }
+
+    pub fn clay_robot(&self) -> &Robot {
+        &self.clay_robot
+    }
+
+    pub fn obsidian_robot(&self) -> &Robot {
+        &self.obsidian_robot
+    }
+
+    pub fn geode_robot(&self) -> &Robot {
+        &self.geode_robot
+    }
+
+    fn parse(line: &Line) -> ParseResult<Self> {
+        let mut s = line.scanner();
+        let id = s.field("Blueprint ")?;
+        let ore_robot = Robot::new(s.field(": Each ore robot costs ")?, 0, 0);
+        let clay_robot = Robot::new(s.field(" ore. Each clay robot costs ")?, 0, 0);
         let obsidian_robot = Robot::new(
-            match_to_i32(&capture.name("obsidian_robot_cost_ore")),
-            match_to_i32(&capture.name("obsidian_robot_cost_clay")),
+            s.field(" ore. Each obsidian robot costs ")?,
+            s.field(" ore and ")?,
             0,
         );
         let geode_robot = Robot::new(
-            match_to_i32(&capture.name("geode_robot_cost_ore")),
+            s.field(" clay. Each geode robot costs ")?,
             0,
-            match_to_i32(&capture.name("geode_robot_cost_obsidian")),
+            s.field(" ore and ")?,
         );
-        Blueprint {
-            id: match_to_i32(&capture.name("id")),
+        s.tag(" obsidian.")?;
+        s.end()?;
+        Ok(Blueprint {
+            id,
             max_ore: *[
                 ore_robot.ore_cost,
                 clay_robot.ore_cost,
@@ -75,13 +99,17 @@ impl From<regex::Captures<'_>> for Blueprint {
             clay_robot,
             obsidian_robot,
             geode_robot,
-        }
+        })
     }
 }
 
-#[derive(Debug)]
+// Nodes expanded by a mine before they are added to the shared counter.
+const NODES_BATCH: u64 = 1 << 16;
+
 struct Mine<'a> {
     bp: &'a Blueprint,
+    nodes: &'a Counter<'a>,
+    expanded: u64,
     resources: Resources,
     ore_robots: i32,
     clay_robots: i32,
@@ -109,9 +137,11 @@ impl Resources {
 }
 
 impl<'a> Mine<'a> {
-    fn new(bp: &'a Blueprint) -> Self {
+    fn new(bp: &'a Blueprint, nodes: &'a Counter<'a>) -> Self {
         Self {
             bp,
+            nodes,
+            expanded: 0,
             resources: Resources::new(),
             ore_robots: 1,
             clay_robots: 0,
@@ -162,9 +192,13 @@ impl<'a> Mine<'a> {
         self.geode_robots += 1;
     }
 
-    fn run(&mut self, minutes: i32) -> i32 {
+    fn run(&mut self, minutes: i32) -> Result<i32, Cancelled> {
+        self.expanded += 1;
+        if self.expanded.is_multiple_of(NODES_BATCH) {
+            self.nodes.add(NODES_BATCH)?;
+        }
         if minutes == 0 {
-            return self.resources.geode;
+            return Ok(self.resources.geode);
         }
         // figure out which robot to build
         let new_geode = self.can_build_geode_robot();
@@ -184,7 +218,7 @@ impl<'a> Mine<'a> {
         if new_geode {
             // we always want to build geode robot to maximize geodes
             self.build_geode_robot();
-            let m = self.run(minutes - 1);
+            let m = self.run(minutes - 1)?;
             if m > max {
                 max = m;
             }
@@ -193,7 +227,7 @@ impl<'a> Mine<'a> {
         } else {
             if new_obsidian && self.resources.obsidian < (minutes - 1) * self.bp.max_obsidian {
                 self.build_obsidian_robot();
-                let m = self.run(minutes - 1);
+                let m = self.run(minutes - 1)?;
                 if m > max {
                     max = m;
                 }
@@ -203,7 +237,7 @@ impl<'a> Mine<'a> {
             if new_clay && self.resources.clay < std::cmp::min(24, (minutes - 1) * self.bp.max_clay)
             {
                 self.build_clay_robot();
-                let m = self.run(minutes - 1);
+                let m = self.run(minutes - 1)?;
                 if m > max {
                     max = m;
                 }
@@ -212,70 +246,122 @@ impl<'a> Mine<'a> {
             }
             if new_ore && self.resources.ore < std::cmp::min(9, (minutes - 1) * self.bp.max_ore) {
                 self.build_ore_robot();
-                let m = self.run(minutes - 1);
+                let m = self.run(minutes - 1)?;
                 if m > max {
                     max = m;
                 }
                 self.ore_robots -= 1;
                 self.resources = rb;
             }
-            let m = self.run(minutes - 1);
+            let m = self.run(minutes - 1)?;
             if m > max {
                 max = m;
             }
             self.resources = rb;
         }
-        max
+        Ok(max)
     }
 }
 
-fn load_blueprints(filename: &str) -> Vec<Blueprint> {
-    let bprex = Regex::new(r"Blueprint\s(?P<id>\d+):\sEach\sore\srobot\scosts\s(?P<ore_robot_cost_ore>\d+)\sore\.\sEach\sclay\srobot\scosts\s(?P<clay_robot_cost_ore>\d+)\sore\.\sEach\sobsidian\srobot\scosts\s(?P<obsidian_robot_cost_ore>\d+)\sore\sand\s(?P<obsidian_robot_cost_clay>\d+)\sclay\.\sEach\sgeode\srobot\scosts\s(?P<geode_robot_cost_ore>\d+)\sore\sand\s(?P<geode_robot_cost_obsidian>\d+)\sobsidian\.").unwrap();
-    fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|l| Blueprint::from(bprex.captures(l).unwrap()))
-        .collect()
+pub fn parse(input: &str) -> ParseResult<Vec<Blueprint>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input).map(|line| Blueprint::parse(&line)).collect()
 }
 
-fn solution_p1(filename: &str) -> i32 {
-    load_blueprints(filename)
-        .into_par_iter()
-        .map(|bp| Mine::new(&bp).run(24) * bp.id)
-        .sum::<i32>()
+// Most geodes `bp` can open in `minutes`, with the best so far reported to
+// `nodes`.
+fn max_geodes(bp: &Blueprint, minutes: i32, nodes: &Counter) -> Result<i32, Cancelled> {
+    let geodes = Mine::new(bp, nodes).run(minutes)?;
+    nodes.best(geodes as i64);
+    Ok(geodes)
+}
+
+pub fn solution_p1(blueprints: &[Blueprint], ctx: &Context) -> Result<i32, Cancelled> {
+    let nodes = Counter::new(ctx, "nodes", None, 1 << 22);
+    blueprints
+        .par_iter()
+        .map(|bp| Ok(max_geodes(bp, 24, &nodes)? * bp.id))
+        .sum()
 }
 
-fn solution_p2(filename: &str) -> i32 {
-    load_blueprints(filename)
-        .into_iter()
+pub fn solution_p2(blueprints: &[Blueprint], ctx: &Context) -> Result<i32, Cancelled> {
+    let nodes = Counter::new(ctx, "nodes", None, 1 << 22);
+    blueprints
+        .iter()
         .take(3)
-        .collect::<Vec<Blueprint>>()
+        .collect::<Vec<&Blueprint>>()
         .into_par_iter()
-        .map(|bp| Mine::new(&bp).run(32))
-        .product::<i32>()
+        .map(|bp| max_geodes(bp, 32, &nodes))
+        .product()
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution_p1("src/inputs/aoc_19.input").to_string(),
-        solution_p2("src/inputs/aoc_19.input").to_string(),
-    )
+pub struct Day19;
+
+impl Solver for Day19 {
+    type Input = Vec<Blueprint>;
+
+    fn day(&self) -> usize {
+        19
+    }
+
+    fn title(&self) -> &'static str {
+        "Not Enough Minerals"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, blueprints: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution_p1(blueprints, ctx)?.to_string())
+    }
+
+    fn part2(&self, blueprints: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution_p2(blueprints, ctx)?.to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        // Part 2 takes a minute on the sample, longer than on the input.
+        vec![sample!("aoc_19_sample.input").part1("33")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use std::fs;
 
     #[test]
     fn test_run() {
-        let blueprints = load_blueprints("src/inputs/aoc_19_sample.input");
+        let blueprints =
+            parse(&fs::read_to_string("src/inputs/aoc_19_sample.input").unwrap()).unwrap();
         assert_eq!(blueprints.len(), 2);
-        assert_eq!(Mine::new(&blueprints[0]).run(24), 9);
-        assert_eq!(Mine::new(&blueprints[1]).run(24), 12);
-        assert_eq!(Mine::new(&blueprints[0]).run(32), 56);
-        assert_eq!(Mine::new(&blueprints[1]).run(32), 62);
-        assert_eq!(solution_p1("src/inputs/aoc_19_sample.input"), 33);
-        assert_eq!(solution_p1("src/inputs/aoc_19.input"), 1199);
-        assert_eq!(solution_p2("src/inputs/aoc_19.input"), 3510);
+        let ctx = Context::default();
+        let nodes = Counter::new(&ctx, "nodes", None, 1 << 22);
+        assert_eq!(Mine::new(&blueprints[0], &nodes).run(24), Ok(9));
+        assert_eq!(Mine::new(&blueprints[1], &nodes).run(24), Ok(12));
+        assert_eq!(Mine::new(&blueprints[0], &nodes).run(32), Ok(56));
+        assert_eq!(Mine::new(&blueprints[1], &nodes).run(32), Ok(62));
+        assert_answer(
+            19,
+            Part::One,
+            "aoc_19_sample.input",
+            solution_p1(&blueprints, &ctx).unwrap(),
+        );
+        let blueprints = parse(&fs::read_to_string("src/inputs/aoc_19.input").unwrap()).unwrap();
+        assert_answer(
+            19,
+            Part::One,
+            "aoc_19.input",
+            solution_p1(&blueprints, &ctx).unwrap(),
+        );
+        assert_answer(
+            19,
+            Part::Two,
+            "aoc_19.input",
+            solution_p2(&blueprints, &ctx).unwrap(),
+        );
     }
 }
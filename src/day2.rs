@@ -1,6 +1,9 @@
-use std::fs;
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 
-fn get_score_p1(input: &str) -> i32 {
+pub fn get_score_p1(input: &str) -> i32 {
     match input {
         "A X" => 1 + 3,
         "B X" => 1,
@@ -42,7 +45,7 @@ fn lose(a: char) -> i32 {
     }
 }
 
-fn get_score_p2(input: &str) -> i32 {
+pub fn get_score_p2(input: &str) -> i32 {
     match input.chars().nth(2) {
         Some('X') => lose(input.chars().next().unwrap()),
         Some('Y') => 3 + draw(input.chars().next().unwrap()),
@@ -51,31 +54,98 @@ fn get_score_p2(input: &str) -> i32 {
     }
 }
 
-fn solution<F>(get_score: F) -> i32
+pub fn parse(input: &str) -> ParseResult<Vec<String>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input)
+        .map(|line| match line.text.as_bytes() {
+            [b'A'..=b'C', b' ', b'X'..=b'Z'] => Ok(line.text.to_string()),
+            _ => Err(line.error(
+                line.text,
+                format!("invalid round '{}', expected e.g. 'A Y'", line.text),
+            )),
+        })
+        .collect()
+}
+
+pub fn solution<F>(rounds: &[String], get_score: F) -> i32
 where
     F: Fn(&str) -> i32,
 {
-    fs::read_to_string("src/inputs/aoc_2.input")
-        .unwrap()
-        .lines()
-        .map(get_score)
-        .sum()
+    rounds.iter().map(|round| get_score(round)).sum()
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution(get_score_p1).to_string(),
-        solution(get_score_p2).to_string(),
-    )
+pub struct Day2;
+
+impl Solver for Day2 {
+    type Input = Vec<String>;
+
+    fn day(&self) -> usize {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Rock Paper Scissors"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, rounds: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(rounds, get_score_p1).to_string())
+    }
+
+    fn part2(&self, rounds: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(rounds, get_score_p2).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_2_sample.input").part1("15").part2("12")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Score of a round from the shapes, 0 for rock to 2 for scissors, each
+    // beating the one before it.
+    fn naive_score(opponent: u8, me: u8) -> i32 {
+        let outcome = match (me + 3 - opponent) % 3 {
+            0 => 3,
+            1 => 6,
+            _ => 0,
+        };
+        me as i32 + 1 + outcome
+    }
+
+    proptest! {
+        #[test]
+        fn test_scores_match_naive(rounds in prop::collection::vec((0..3u8, 0..3u8), 1..20)) {
+            let input = rounds
+                .iter()
+                .map(|(a, x)| format!("{} {}\n", (b'A' + a) as char, (b'X' + x) as char))
+                .collect::<String>();
+            let parsed = parse(&input).unwrap();
+            let part1 = rounds.iter().map(|(a, x)| naive_score(*a, *x)).sum::<i32>();
+            prop_assert_eq!(solution(&parsed, get_score_p1), part1);
+            // The second column is the outcome in part two: lose, draw or win.
+            let part2 = rounds
+                .iter()
+                .map(|(a, x)| naive_score(*a, (a + x + 2) % 3))
+                .sum::<i32>();
+            prop_assert_eq!(solution(&parsed, get_score_p2), part2);
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution(get_score_p1), 13446);
-        assert_eq!(solution(get_score_p2), 13509);
+        let rounds = parse(&fs::read_to_string("src/inputs/aoc_2.input").unwrap()).unwrap();
+        assert_answer(2, Part::One, "aoc_2.input", solution(&rounds, get_score_p1));
+        assert_answer(2, Part::Two, "aoc_2.input", solution(&rounds, get_score_p2));
     }
 }
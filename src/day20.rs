@@ -1,9 +1,12 @@
 extern crate derive_more;
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 
 use derive_more::Constructor;
 use std::cmp::Ordering;
 use std::collections::VecDeque;
-use std::fs;
 
 #[derive(Debug, Constructor, Clone, Copy, PartialEq, Eq)]
 struct Val {
@@ -11,12 +14,22 @@ struct Val {
     v: i64,
 }
 
-fn solution(filename: &str, decryption_key: i64, rounds: usize) -> i64 {
-    let mut input: VecDeque<Val> = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
+pub fn parse(input: &str) -> ParseResult<Vec<i64>> {
+    let input = &*normalize(input, Normalize::default());
+    let numbers = lines(input)
+        .map(|line| line.number(line.text))
+        .collect::<ParseResult<Vec<i64>>>()?;
+    if !numbers.contains(&0) {
+        return Err(ParseError::new(1, 1, "missing number 0"));
+    }
+    Ok(numbers)
+}
+
+pub fn solution(numbers: &[i64], decryption_key: i64, rounds: usize) -> i64 {
+    let mut input: VecDeque<Val> = numbers
+        .iter()
         .enumerate()
-        .map(|(i, l)| Val::new(i, l.parse::<i64>().unwrap() * decryption_key))
+        .map(|(i, v)| Val::new(i, v * decryption_key))
         .collect();
 
     for i in 0..rounds * input.len() {
@@ -52,28 +65,114 @@ fn solution(filename: &str, decryption_key: i64, rounds: usize) -> i64 {
         + input[(zero_pos + 3000) % input.len()].v
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution("src/inputs/aoc_20.input", 1, 1).to_string(),
-        solution("src/inputs/aoc_20.input", 811589153, 10).to_string(),
-    )
+pub struct Day20;
+
+impl Solver for Day20 {
+    type Input = Vec<i64>;
+
+    fn day(&self) -> usize {
+        20
+    }
+
+    fn title(&self) -> &'static str {
+        "Grove Positioning System"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, numbers: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(numbers, 1, 1).to_string())
+    }
+
+    fn part2(&self, numbers: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(numbers, 811589153, 10).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_20_sample.input")
+            .part1("3")
+            .part2("1623178306")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Mixes by moving each number within a plain Vec, one at a time.
+    fn naive_solution(numbers: &[i64], decryption_key: i64, rounds: usize) -> i64 {
+        let mut mixed = numbers
+            .iter()
+            .map(|v| v * decryption_key)
+            .enumerate()
+            .collect::<Vec<(usize, i64)>>();
+        let len = mixed.len() as i64;
+        for i in (0..numbers.len()).cycle().take(rounds * numbers.len()) {
+            let from = mixed.iter().position(|(j, _)| *j == i).unwrap();
+            let val = mixed.remove(from);
+            let to = (from as i64 + val.1).rem_euclid(len - 1);
+            mixed.insert(to as usize, val);
+        }
+        let zero = mixed.iter().position(|(_, v)| *v == 0).unwrap();
+        [1000, 2000, 3000]
+            .iter()
+            .map(|n| mixed[(zero + n) % mixed.len()].1)
+            .sum()
+    }
+
+    // Numbers with a single 0, like the puzzle promises.
+    fn numbers() -> impl Strategy<Value = Vec<i64>> {
+        (
+            prop::collection::vec((-20i64..20).prop_filter("0", |v| *v != 0), 1..20),
+            any::<prop::sample::Index>(),
+        )
+            .prop_map(|(mut numbers, zero)| {
+                numbers.insert(zero.index(numbers.len() + 1), 0);
+                numbers
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn test_mixing_matches_naive(numbers in numbers()) {
+            let input = numbers.iter().map(|n| format!("{n}\n")).collect::<String>();
+            let numbers = parse(&input).unwrap();
+            prop_assert_eq!(solution(&numbers, 1, 1), naive_solution(&numbers, 1, 1));
+            prop_assert_eq!(
+                solution(&numbers, 811589153, 10),
+                naive_solution(&numbers, 811589153, 10)
+            );
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_20_sample.input", 1, 1), 3);
-        assert_eq!(
-            solution("src/inputs/aoc_20_sample.input", 811589153, 10),
-            1_623_178_306
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_20_sample.input").unwrap()).unwrap();
+        let numbers = parse(&fs::read_to_string("src/inputs/aoc_20.input").unwrap()).unwrap();
+        assert_answer(
+            20,
+            Part::One,
+            "aoc_20_sample.input",
+            solution(&sample, 1, 1),
+        );
+        assert_answer(
+            20,
+            Part::Two,
+            "aoc_20_sample.input",
+            solution(&sample, 811589153, 10),
         );
-        assert_eq!(solution("src/inputs/aoc_20.input", 1, 1), 7225);
-        assert_eq!(
-            solution("src/inputs/aoc_20.input", 811589153, 10),
-            548_634_267_428
+        assert_answer(20, Part::One, "aoc_20.input", solution(&numbers, 1, 1));
+        assert_answer(
+            20,
+            Part::Two,
+            "aoc_20.input",
+            solution(&numbers, 811589153, 10),
         );
     }
 }
@@ -1,191 +1,385 @@
 extern crate derive_more;
-extern crate lazy_static;
+use crate::context::Context;
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult, Scanner};
+use crate::sample;
+use crate::solver::{PartError, PartResult, Sample, Solver};
 
 use derive_more::Constructor;
-use regex::Regex;
-use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::fs;
-use std::sync::Mutex;
-
-#[derive(Debug)]
-enum Operator {
-    None,
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Operator {
     Add,
     Sub,
     Mul,
     Div,
-    Eq,
-}
-
-impl From<char> for Operator {
-    fn from(c: char) -> Self {
-        match c {
-            '+' => Operator::Add,
-            '-' => Operator::Sub,
-            '/' => Operator::Div,
-            '*' => Operator::Mul,
-            '=' => Operator::Eq,
-            _ => Operator::None,
-        }
-    }
 }
 
+/// Name of a monkey, up to its first four bytes, shown as it's written.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
-struct VarName {
-    id: u32,
+pub struct VarName {
+    /// The bytes of the name, the first one lowest.
+    pub id: u32,
 }
 
 impl From<&str> for VarName {
     fn from(s: &str) -> Self {
         VarName {
-            id: *s.as_bytes().first().unwrap() as u32
-                | (*s.as_bytes().get(1).unwrap() as u32) << 8
-                | (*s.as_bytes().get(2).unwrap() as u32) << 16
-                | (*s.as_bytes().get(3).unwrap() as u32) << 24,
+            id: s
+                .bytes()
+                .take(4)
+                .enumerate()
+                .fold(0, |id, (i, b)| id | (b as u32) << (8 * i)),
         }
     }
 }
 
-#[derive(Debug, Constructor)]
-struct Expression {
-    a: VarName,
-    op: Operator,
-    b: VarName,
+impl fmt::Display for VarName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.id.to_le_bytes();
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        write!(f, "{}", String::from_utf8_lossy(&bytes[..len]))
+    }
 }
 
-#[derive(Debug)]
-enum Value {
-    None,
-    Value(i64),
+#[derive(Debug, Clone, Constructor)]
+pub struct Expression {
+    pub a: VarName,
+    pub op: Operator,
+    pub b: VarName,
+}
+
+/// What a monkey yells: a number, or what two other monkeys yell combined.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
     Expression(Expression),
 }
 
-impl From<regex::Captures<'_>> for Value {
-    fn from(capture: regex::Captures) -> Self {
-        if let Some(v) = capture.name("value") {
-            Value::Value(v.as_str().parse::<i64>().unwrap())
-        } else if let Some(e) = capture.name("op") {
-            Value::Expression(Expression::new(
-                VarName::from(capture.name("v1").unwrap().as_str()),
-                Operator::from(e.as_str().chars().next().unwrap()),
-                VarName::from(capture.name("v2").unwrap().as_str()),
-            ))
-        } else {
-            Value::None
+impl Value {
+    // `names` are all the monkeys of the input, expressions can only refer to
+    // one of them.
+    fn parse(s: &mut Scanner, names: &HashSet<&str>) -> ParseResult<Self> {
+        if s.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return Ok(Value::Int(s.integer()?));
         }
+        let var_name = |s: &mut Scanner| {
+            let at = *s;
+            let name = s.word()?;
+            if names.contains(name) {
+                Ok(VarName::from(name))
+            } else {
+                Err(at.error(format!("unknown monkey '{name}'")))
+            }
+        };
+        Ok(Value::Expression(Expression::new(
+            var_name(s)?,
+            s.one_of(&[
+                (" + ", Operator::Add),
+                (" - ", Operator::Sub),
+                (" * ", Operator::Mul),
+                (" / ", Operator::Div),
+            ])?,
+            var_name(s)?,
+        )))
     }
 }
 
-#[derive(Debug)]
-struct Var {
+/// Monkey of an input. `parse` checks it only refers to monkeys of the same
+/// input, and that root doesn't depend on its own number.
+#[derive(Debug, Clone)]
+pub struct Var {
     name: VarName,
     value: Value,
 }
 
-impl From<regex::Captures<'_>> for Var {
-    fn from(capture: regex::Captures) -> Self {
-        Var {
-            name: VarName::from(capture.name("var").unwrap().as_str()),
-            value: Value::from(capture),
-        }
+impl Var {
+    pub fn name(&self) -> VarName {
+        self.name
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    fn parse(line: &Line, names: &HashSet<&str>) -> ParseResult<Self> {
+        let mut s = line.scanner();
+        let name = VarName::from(s.word()?);
+        s.tag(": ")?;
+        let value = Value::parse(&mut s, names)?;
+        s.end()?;
+        Ok(Var { name, value })
     }
 }
 
-lazy_static::lazy_static! {
-    static ref VALUECACHE: Mutex<HashMap<VarName, i64>> = Mutex::new(HashMap::new());
+// Values of the monkeys of one input, and whether they depend on the number
+// the human yells, cached as they get evaluated.
+struct Values<'a> {
+    input: &'a HashMap<VarName, Var>,
+    cache: HashMap<VarName, i64>,
+    humn: HashMap<VarName, bool>,
 }
 
-fn get_value(var: &VarName, input: &HashMap<VarName, Var>) -> i64 {
-    if let Some(r) = VALUECACHE.lock().unwrap().get(var) {
-        return *r;
+impl<'a> Values<'a> {
+    fn new(input: &'a HashMap<VarName, Var>) -> Self {
+        Values {
+            input,
+            cache: HashMap::new(),
+            humn: HashMap::new(),
+        }
     }
-    match &input.get(var).unwrap().value {
-        Value::None => 0,
-        Value::Value(v) => {
-            VALUECACHE.lock().unwrap().insert(*var, *v);
-            *v
+
+    fn has_humn(&mut self, var: &VarName) -> bool {
+        if let Some(r) = self.humn.get(var) {
+            return *r;
         }
-        Value::Expression(e) => match e.op {
-            Operator::None => 0,
-            Operator::Add => get_value(&e.a, input) + get_value(&e.b, input),
-            Operator::Mul => get_value(&e.a, input) * get_value(&e.b, input),
-            Operator::Div => get_value(&e.a, input) / get_value(&e.b, input),
-            Operator::Sub => get_value(&e.a, input) - get_value(&e.b, input),
-            Operator::Eq => get_value(&e.a, input) - get_value(&e.b, input),
-        },
+        let r = *var == VarName::from("humn")
+            || match &self.input[var].value {
+                Value::Int(_) => false,
+                Value::Expression(e) => self.has_humn(&e.a) || self.has_humn(&e.b),
+            };
+        self.humn.insert(*var, r);
+        r
     }
-}
 
-fn load_input(filename: &str) -> HashMap<VarName, Var> {
-    let input_re = Regex::new(
-        r"(?P<var>[a-z]{4}):\s((?P<value>\d+)|((?P<v1>[a-z]{4})\s(?P<op>[+\-*/])\s(?P<v2>[a-z]{4})))",
-    ).unwrap();
-    fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| Var::from(input_re.captures(line).unwrap()))
-        .map(|var| (var.name, var))
-        .collect::<HashMap<VarName, Var>>()
+    fn get(&mut self, var: &VarName) -> Result<i64, PartError> {
+        if let Some(r) = self.cache.get(var) {
+            return Ok(*r);
+        }
+        let r = match &self.input[var].value {
+            Value::Int(v) => *v,
+            Value::Expression(e) => {
+                let (a, b) = (self.get(&e.a)?, self.get(&e.b)?);
+                let r = match e.op {
+                    Operator::Add => a.checked_add(b),
+                    Operator::Sub => a.checked_sub(b),
+                    Operator::Mul => a.checked_mul(b),
+                    Operator::Div => exact_div(a, b),
+                };
+                r.ok_or_else(|| {
+                    PartError::Unsolvable(match (e.op, b) {
+                        (Operator::Div, 0) => format!("monkey '{var}' divides by zero"),
+                        (Operator::Div, _) => format!("monkey '{var}' doesn't divide exactly"),
+                        _ => format!("monkey '{var}' overflows"),
+                    })
+                })?
+            }
+        };
+        self.cache.insert(*var, r);
+        Ok(r)
+    }
 }
 
-fn solution_p1(filename: &str) -> i64 {
-    VALUECACHE.lock().unwrap().clear();
-    get_value(&VarName::from("root"), &load_input(filename))
+// Rejects the monkeys `var` depends on that depend on their own number, which
+// could never be worked out. `done` is false for the monkeys being checked.
+fn check_acyclic(
+    var: &VarName,
+    vars: &HashMap<VarName, (Line, Var)>,
+    done: &mut HashMap<VarName, bool>,
+) -> ParseResult<()> {
+    match done.get(var) {
+        Some(true) => return Ok(()),
+        Some(false) => {
+            let (line, _) = &vars[var];
+            return Err(line.error_at(0, format!("monkey '{var}' depends on its own number")));
+        }
+        None => {}
+    }
+    done.insert(*var, false);
+    if let Value::Expression(e) = &vars[var].1.value {
+        check_acyclic(&e.a, vars, done)?;
+        check_acyclic(&e.b, vars, done)?;
+    }
+    done.insert(*var, true);
+    Ok(())
 }
 
-fn solution_p2(filename: &str) -> i64 {
-    let mut input = load_input(filename);
-
-    if let Value::Expression(e) = &mut input.get_mut(&VarName::from("root")).unwrap().value {
-        e.op = Operator::Eq;
+pub fn parse(input: &str) -> ParseResult<HashMap<VarName, Var>> {
+    let input = &*normalize(input, Normalize::default());
+    let names = lines(input)
+        .map(|line| line.scanner().word())
+        .collect::<ParseResult<HashSet<&str>>>()?;
+    if let Some(name) = ["root", "humn"].iter().find(|name| !names.contains(*name)) {
+        return Err(ParseError::new(1, 1, format!("missing monkey '{name}'")));
     }
+    let vars = lines(input)
+        .map(|line| Var::parse(&line, &names).map(|var| (var.name, (line, var))))
+        .collect::<ParseResult<HashMap<_, _>>>()?;
+    check_acyclic(&VarName::from("root"), &vars, &mut HashMap::new())?;
+    Ok(vars
+        .into_iter()
+        .map(|(name, (_, var))| (name, var))
+        .collect())
+}
 
-    let mut lower = 0;
-    let mut upper: i64 = 10_000_000_000_000;
-    let mut current = 0;
-    loop {
-        VALUECACHE.lock().unwrap().clear();
-        if let Value::Value(v) = &mut input.get_mut(&VarName::from("humn")).unwrap().value {
-            *v = current;
-        }
+pub fn solution_p1(input: &HashMap<VarName, Var>) -> Result<i64, PartError> {
+    Values::new(input).get(&VarName::from("root"))
+}
 
-        let res = get_value(&VarName::from("root"), &input);
-        match res.cmp(&0) {
-            Ordering::Equal => {
-                return current;
-            }
-            Ordering::Greater => {
-                lower = current;
+// `a / b` when `b` divides `a`.
+fn exact_div(a: i64, b: i64) -> Option<i64> {
+    a.checked_rem(b).filter(|r| *r == 0)?;
+    a.checked_div(b)
+}
+
+// Solves root's equation for the human's number, undoing the operations from
+// root down to the human one at a time. That only works if the human's
+// number reaches root through one side of each operation.
+pub fn solution_p2(input: &HashMap<VarName, Var>) -> Result<i64, PartError> {
+    let humn = VarName::from("humn");
+    let unsolvable = |var: VarName| {
+        PartError::Unsolvable(format!(
+            "the human's number reaches monkey '{var}' through both or none of its sides"
+        ))
+    };
+    let mut values = Values::new(input);
+    let Value::Expression(root) = &input[&VarName::from("root")].value else {
+        return Err(PartError::Unsolvable("root has no equation".to_string()));
+    };
+    let (mut var, mut target) = match (values.has_humn(&root.a), values.has_humn(&root.b)) {
+        (true, false) => (root.a, values.get(&root.b)?),
+        (false, true) => (root.b, values.get(&root.a)?),
+        _ => return Err(unsolvable(VarName::from("root"))),
+    };
+    let inexact = || PartError::Unsolvable("the human's number isn't a whole one".to_string());
+    while var != humn {
+        let Value::Expression(e) = &input[&var].value else {
+            unreachable!("only expressions depend on the human");
+        };
+        (var, target) = match (values.has_humn(&e.a), values.has_humn(&e.b)) {
+            (true, false) => {
+                let b = values.get(&e.b)?;
+                let a = match e.op {
+                    Operator::Add => target.checked_sub(b),
+                    Operator::Sub => target.checked_add(b),
+                    Operator::Mul => exact_div(target, b),
+                    Operator::Div => target.checked_mul(b),
+                };
+                (e.a, a.ok_or_else(inexact)?)
             }
-            Ordering::Less => {
-                upper = current;
+            (false, true) => {
+                let a = values.get(&e.a)?;
+                let b = match e.op {
+                    Operator::Add => target.checked_sub(a),
+                    Operator::Sub => a.checked_sub(target),
+                    Operator::Mul => exact_div(target, a),
+                    Operator::Div => exact_div(a, target),
+                };
+                (e.b, b.ok_or_else(inexact)?)
             }
-        }
-        current = lower + (upper - lower) / 2;
-        if lower == upper {
-            panic!("couldn't find the value");
-        }
+            _ => return Err(unsolvable(var)),
+        };
     }
+    Ok(target)
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution_p1("src/inputs/aoc_21.input").to_string(),
-        solution_p2("src/inputs/aoc_21.input").to_string(),
-    )
+pub struct Day21;
+
+impl Solver for Day21 {
+    type Input = HashMap<VarName, Var>;
+
+    fn day(&self) -> usize {
+        21
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey Math"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, vars: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution_p1(vars)?.to_string())
+    }
+
+    fn part2(&self, vars: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution_p2(vars)?.to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_21_sample.input").part1("152").part2("301")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use std::fs;
 
     #[test]
     fn test_run() {
-        assert_eq!(solution_p1("src/inputs/aoc_21_sample.input"), 152);
-        // This one doesn't realy work with the current binary search parameters.
-        // assert_eq!(solution_p2("src/inputs/aoc_21_sample.input"), 301);
-        assert_eq!(solution_p1("src/inputs/aoc_21.input"), 223_971_851_179_174);
-        assert_eq!(solution_p2("src/inputs/aoc_21.input"), 3_379_022_190_351);
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_21_sample.input").unwrap()).unwrap();
+        let input = parse(&fs::read_to_string("src/inputs/aoc_21.input").unwrap()).unwrap();
+        let (p1, p2) = (solution_p1(&sample).unwrap(), solution_p2(&sample).unwrap());
+        assert_answer(21, Part::One, "aoc_21_sample.input", p1);
+        assert_answer(21, Part::Two, "aoc_21_sample.input", p2);
+        assert_answer(21, Part::One, "aoc_21.input", solution_p1(&input).unwrap());
+        assert_answer(21, Part::Two, "aoc_21.input", solution_p2(&input).unwrap());
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        let unsolvable = |msg: &str| Err(PartError::Unsolvable(msg.to_string()));
+        let vars = parse("root: aaaa / bbbb\naaaa: 3\nbbbb: humn - humn\nhumn: 5").unwrap();
+        assert_eq!(
+            solution_p1(&vars),
+            unsolvable("monkey 'root' divides by zero")
+        );
+        assert_eq!(
+            solution_p2(&vars),
+            unsolvable(
+                "the human's number reaches monkey 'bbbb' through both or none of its sides"
+            )
+        );
+        let vars =
+            parse("root: aaaa + bbbb\naaaa: humn / cccc\nbbbb: 3\ncccc: 2\nhumn: 5").unwrap();
+        assert_eq!(
+            solution_p1(&vars),
+            unsolvable("monkey 'aaaa' doesn't divide exactly")
+        );
+        assert_eq!(solution_p2(&vars), Ok(6));
+        let vars =
+            parse("root: aaaa + bbbb\naaaa: humn * cccc\nbbbb: 3\ncccc: 2\nhumn: 5").unwrap();
+        assert_eq!(solution_p1(&vars), Ok(13));
+        assert_eq!(
+            solution_p2(&vars),
+            unsolvable("the human's number isn't a whole one")
+        );
+    }
+
+    #[test]
+    fn test_concurrent_inputs() {
+        let inputs = ["aoc_21_sample.input", "aoc_21.input"];
+        std::thread::scope(|s| {
+            for name in inputs {
+                s.spawn(move || {
+                    let input = fs::read_to_string(format!("src/inputs/{name}")).unwrap();
+                    let vars = parse(&input).unwrap();
+                    assert_answer(21, Part::One, name, solution_p1(&vars).unwrap());
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("root: pppw + humn\nhumn: 5").unwrap_err(),
+            ParseError::new(1, 7, "unknown monkey 'pppw'")
+        );
+        assert_eq!(
+            parse("root: 1").unwrap_err(),
+            ParseError::new(1, 1, "missing monkey 'humn'")
+        );
+        assert_eq!(
+            parse("root: aaaa + bbbb\naaaa: bbbb * cccc\nbbbb: aaaa - cccc\ncccc: 2\nhumn: 5")
+                .unwrap_err(),
+            ParseError::new(2, 1, "monkey 'aaaa' depends on its own number")
+        );
     }
 }
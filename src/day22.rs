@@ -1,59 +1,27 @@
 extern crate derive_more;
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult};
+use crate::pos::{Direction, Pos, Rotate};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::visualize::{Frame, Pixel};
 
 use derive_more::Constructor;
-use regex::Regex;
 use std::collections::HashMap;
-use std::fs;
 use std::iter;
-use std::ops::Add;
 
-#[derive(Debug)]
-enum Rotate {
-    None,
-    Left,
-    Right,
-}
-
-impl From<char> for Rotate {
-    fn from(c: char) -> Self {
-        match c {
-            'R' => Rotate::Right,
-            'L' => Rotate::Left,
-            _ => Rotate::None,
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Move {
-    steps: usize,
-    rotate: Rotate,
+/// Steps forward, then a turn.
+#[derive(Debug, Clone)]
+pub struct Move {
+    pub steps: usize,
+    /// The last move of the path doesn't turn.
+    pub rotate: Option<Rotate>,
 }
 
-impl From<regex::Captures<'_>> for Move {
-    fn from(capture: regex::Captures) -> Self {
-        Move {
-            steps: capture
-                .name("steps")
-                .unwrap()
-                .as_str()
-                .parse::<usize>()
-                .unwrap(),
-            rotate: Rotate::from(
-                capture
-                    .name("rot")
-                    .unwrap()
-                    .as_str()
-                    .chars()
-                    .next()
-                    .unwrap_or('X'),
-            ),
-        }
-    }
-}
-
-#[derive(Debug, PartialEq, Eq)]
-enum Tile {
+/// Tile of the map. Teleports are only added by the part solving on a cube.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tile {
     None,
     Floor,
     Wall,
@@ -70,192 +38,83 @@ impl From<char> for Tile {
     }
 }
 
-#[derive(Debug, Constructor, Clone, Copy, PartialEq, Eq, Hash)]
-struct Pos {
-    x: usize,
-    y: usize,
-}
-
-impl Add<(i32, i32)> for Pos {
-    type Output = Self;
-
-    fn add(self, other: (i32, i32)) -> Self {
-        if self.x as i32 + other.0 < 0 || self.y as i32 + other.1 < 0 {
-            panic!("Pos cannot be negative.");
+// Teleports are off the map, so they're drawn like the void around it.
+impl From<&Tile> for Pixel {
+    fn from(tile: &Tile) -> Self {
+        match tile {
+            Tile::Floor => Pixel::new('.', [90, 90, 90]),
+            Tile::Wall => Pixel::new('#', [200, 200, 200]),
+            Tile::None | Tile::Teleport(_) => Pixel::new(' ', [0, 0, 0]),
         }
-        Pos::new(
-            (self.x as i32 + other.0) as usize,
-            (self.y as i32 + other.1) as usize,
-        )
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl From<(i32, i32)> for Direction {
-    fn from(t: (i32, i32)) -> Self {
-        match t {
-            (0, 1) => Direction::Down,
-            (0, -1) => Direction::Up,
-            (1, 0) => Direction::Right,
-            (-1, 0) => Direction::Left,
-            _ => panic!("wrong tuple"),
-        }
+fn facing(dir: Direction) -> usize {
+    match dir {
+        Direction::Right => 0,
+        Direction::Down => 1,
+        Direction::Left => 2,
+        Direction::Up => 3,
     }
 }
 
-impl From<Direction> for (i32, i32) {
-    fn from(val: Direction) -> Self {
-        match val {
-            Direction::Up => (0, -1),
-            Direction::Left => (-1, 0),
-            Direction::Down => (0, 1),
-            Direction::Right => (1, 0),
-        }
-    }
-}
-
-impl From<Direction> for usize {
-    fn from(val: Direction) -> Self {
-        match val {
-            Direction::Right => 0,
-            Direction::Down => 1,
-            Direction::Left => 2,
-            Direction::Up => 3,
-        }
-    }
-}
-
-impl Direction {
-    fn rotate(&self, r: &Rotate) -> Self {
-        match r {
-            Rotate::None => *self,
-            Rotate::Right => match self {
-                Direction::Down => Direction::Left,
-                Direction::Left => Direction::Up,
-                Direction::Up => Direction::Right,
-                Direction::Right => Direction::Down,
-            },
-            Rotate::Left => match self {
-                Direction::Up => Direction::Left,
-                Direction::Left => Direction::Down,
-                Direction::Down => Direction::Right,
-                Direction::Right => Direction::Up,
-            },
-        }
-    }
-
-    fn opposite(&self) -> Self {
-        match self {
-            Direction::Up => Direction::Down,
-            Direction::Left => Direction::Right,
-            Direction::Down => Direction::Up,
-            Direction::Right => Direction::Left,
-        }
-    }
-}
-
-#[derive(Debug)]
-struct Game {
-    map: Vec<Vec<Tile>>,
+/// Map and path of the monkeys' notes, starting on the first open tile of
+/// the top row.
+#[derive(Debug, Clone)]
+pub struct Game {
+    // Padded with a border of `Tile::None`.
+    map: Grid<Tile>,
     moves: Vec<Move>,
     pos: Pos,
     dir: Direction,
 }
 
 impl Game {
-    fn new(map: Vec<Vec<Tile>>, moves: Vec<Move>) -> Self {
+    /// Tiles of the map, padded with a border of `Tile::None`.
+    pub fn map(&self) -> &Grid<Tile> {
+        &self.map
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    fn new(map: Grid<Tile>, moves: Vec<Move>) -> Self {
         Game {
             moves,
             dir: Direction::Right,
-            pos: Pos::new(
-                map[1]
+            pos: Pos::from((
+                map.row(1)
                     .iter()
-                    .enumerate()
-                    .find(|(_, tile)| **tile == Tile::Floor)
-                    .unwrap()
-                    .0,
+                    .position(|tile| *tile == Tile::Floor)
+                    .unwrap(),
                 1,
-            ),
+            )),
             map,
         }
     }
 
-    #[allow(dead_code)]
-    fn print_map(&self) {
-        self.map.iter().for_each(|row| {
-            row.iter().for_each(|t| {
-                print!(
-                    "{}",
-                    match t {
-                        Tile::Floor => ".",
-                        Tile::None => " ",
-                        Tile::Wall => "#",
-                        Tile::Teleport(t) =>
-                            if t.len() == 1 {
-                                "T"
-                            } else {
-                                "X"
-                            },
-                    }
-                )
-            });
-            println!()
-        })
-    }
-
-    fn is_wall(&self, x: usize, y: usize) -> bool {
-        let y = y % self.map.len();
-        let x = x % self.map[y].len();
-        self.map[y][x] == Tile::Wall
+    fn is_wall(&self, p: Pos) -> bool {
+        self.map.get_wrapping(p) == &Tile::Wall
     }
 
-    fn advance(&self, p: &Pos, adv: (i32, i32)) -> (Pos, (i32, i32)) {
-        let mut y = p.y;
-        let mut x = p.x;
-        if adv.0 != 0 && adv.1 != 0 {
-            panic!("both values != 0: {p:?}");
-        }
-        while self.map[y][x] == Tile::None {
-            if adv.1 != 0 {
-                if adv.1 > 0 {
-                    y += 1;
-                } else if y == 0 {
-                    y = self.map.len() - 1;
-                } else {
-                    y -= 1
-                }
-                y %= self.map.len();
-            } else {
-                if adv.0 > 0 {
-                    x += 1;
-                } else if x == 0 {
-                    x = self.map[y].len() - 1;
-                } else {
-                    x -= 1
-                }
-                x %= self.map[y].len();
-            }
+    fn advance(&self, p: Pos, dir: Direction) -> (Pos, Direction) {
+        let mut p = p;
+        while self.map[p] == Tile::None {
+            p = p.wrapping_add(dir.into(), self.map.width(), self.map.height());
         }
-        if let Tile::Teleport(dest) = &self.map[y][x] {
-            let r = dest.get(&Direction::from(adv)).unwrap();
-            return (r.0, r.1.into());
+        if let Tile::Teleport(dest) = &self.map[p] {
+            return dest[&dir];
         }
-        (Pos::new(x, y), adv)
+        (p, dir)
     }
 
-    fn move_xxx(&self, pos: &Pos, steps: usize, dir: (i32, i32)) -> (Pos, (i32, i32)) {
-        let mut pos = *pos;
+    fn move_xxx(&self, pos: Pos, steps: usize, dir: Direction) -> (Pos, Direction) {
+        let mut pos = pos;
         let mut dir = dir;
         for _ in 0..steps {
-            let (new_pos, new_dir) = self.advance(&pos.add(dir), dir);
-            if self.is_wall(new_pos.x, new_pos.y) {
+            let (new_pos, new_dir) = self.advance(pos + dir, dir);
+            if self.is_wall(new_pos) {
                 return (pos, dir);
             }
             pos = new_pos;
@@ -264,19 +123,52 @@ impl Game {
         (pos, dir)
     }
 
-    fn mov(&self, pos: &Pos, dir: &Direction, m: &Move) -> (Pos, Direction) {
-        let p = self.move_xxx(pos, m.steps, <(i32, i32)>::from(*dir));
-        (p.0, Direction::from(p.1).rotate(&m.rotate))
+    fn mov(&self, pos: Pos, dir: Direction, m: &Move) -> (Pos, Direction) {
+        let (pos, dir) = self.move_xxx(pos, m.steps, dir);
+        (pos, m.rotate.map_or(dir, |r| dir.rotate(r)))
     }
 
-    fn play(&self) -> (Pos, Direction) {
+    fn play(&self, ctx: &Context) -> (Pos, Direction) {
+        if ctx.is_visualizing() {
+            return self.play_visualized(ctx);
+        }
         let mut pos = self.pos;
         let mut dir = self.dir;
         for m in &self.moves {
-            (pos, dir) = self.mov(&pos, &dir, m);
+            (pos, dir) = self.mov(pos, dir, m);
         }
         (pos, dir)
     }
+
+    // Same as `play`, a step at a time, showing the path so far after every
+    // move.
+    fn play_visualized(&self, ctx: &Context) -> (Pos, Direction) {
+        let mut trail = self.map.map(|_| None);
+        let mut pos = self.pos;
+        let mut dir = self.dir;
+        trail[pos] = Some(dir);
+        for m in &self.moves {
+            for _ in 0..m.steps {
+                (pos, dir) = self.move_xxx(pos, 1, dir);
+                trail[pos] = Some(dir);
+            }
+            dir = m.rotate.map_or(dir, |r| dir.rotate(r));
+            trail[pos] = Some(dir);
+            ctx.frame(|| self.frame(&trail, pos));
+        }
+        (pos, dir)
+    }
+
+    // The map with the facing of the last visit of every tile on the path.
+    fn frame(&self, trail: &Grid<Option<Direction>>, pos: Pos) -> Frame {
+        let mut frame = self.map.map(|tile| Pixel::from(tile));
+        trail
+            .iter()
+            .filter_map(|(p, dir)| dir.map(|dir| (p, dir)))
+            .for_each(|(p, dir)| frame[p] = Pixel::new(char::from(dir), [90, 200, 90]));
+        frame[pos].rgb = [255, 220, 0];
+        frame
+    }
 }
 
 fn cube_face_side_vector(
@@ -284,6 +176,7 @@ fn cube_face_side_vector(
     cube_size: usize,
     side: Direction,
 ) -> impl Iterator<Item = Pos> {
+    let cube_size = cube_size as i64;
     (0..cube_size).map(move |i| match side {
         Direction::Down => Pos::new(
             cube_face.x * cube_size + i + 1,
@@ -304,6 +197,7 @@ fn cube_face_vector(
     side: Direction,
     reverse: bool,
 ) -> Box<dyn Iterator<Item = Pos>> {
+    let cube_size = cube_size as i64;
     let r = (0..cube_size).map(move |i| match side {
         Direction::Down => Pos::new(
             cube_face.x * cube_size + i + 1,
@@ -324,7 +218,7 @@ fn cube_face_vector(
 }
 
 #[derive(Debug)]
-struct Teleport {
+pub struct Teleport {
     from_pos: Pos,
     from_side: Direction,
     to_pos: Pos,
@@ -341,9 +235,9 @@ impl Teleport {
         to_reverse: bool,
     ) -> Teleport {
         Teleport {
-            from_pos: Pos::new(from_pos.0, from_pos.1),
+            from_pos: Pos::from(from_pos),
             from_side,
-            to_pos: Pos::new(to_pos.0, to_pos.1),
+            to_pos: Pos::from(to_pos),
             to_side,
             to_reverse,
         }
@@ -358,10 +252,10 @@ fn apply_teleport(
     to_side: &Direction,
 ) {
     for (from_pos, to_pos) in from.zip(to) {
-        if let Tile::Teleport(cp) = &mut game.map[from_pos.y][from_pos.x] {
+        if let Tile::Teleport(cp) = &mut game.map[from_pos] {
             cp.insert(*from_side, (to_pos, to_side.opposite()));
         } else {
-            game.map[from_pos.y][from_pos.x] =
+            game.map[from_pos] =
                 Tile::Teleport(HashMap::from([(*from_side, (to_pos, to_side.opposite()))]));
         }
     }
@@ -402,46 +296,71 @@ fn apply_teleports(game: &mut Game, teleports: &[Teleport], cube_size: usize) {
     });
 }
 
-fn solution(filename: &str, p2: bool, cube_size: usize, teleports: &[Teleport]) -> usize {
-    let moves_re = Regex::new(r"(?P<steps>\d+)(?P<rot>[R|L]*)+").unwrap();
-    let input = fs::read_to_string(filename).unwrap();
+fn parse_moves(path: &Line) -> ParseResult<Vec<Move>> {
+    let mut s = path.scanner();
+    let mut moves = Vec::new();
+    while !s.is_empty() {
+        let steps = s.integer()?;
+        let rotate = match s.is_empty() {
+            true => None,
+            false => Some(s.one_of(&[("L", Rotate::Left), ("R", Rotate::Right)])?),
+        };
+        moves.push(Move { steps, rotate });
+    }
+    Ok(moves)
+}
+
+pub fn parse(input: &str) -> ParseResult<Game> {
+    // The map is read by column, so it's kept as it is drawn.
+    let input = &*normalize(input, Normalize { trim: false });
+    let (map, path): (Vec<Line>, Vec<Line>) = lines(input)
+        .filter(|l| !l.text.is_empty())
+        .partition(|l| !l.text.starts_with(|c: char| c.is_ascii_digit()));
+    let [path] = path[..] else {
+        return Err(ParseError::new(
+            input.lines().count(),
+            1,
+            "expected a single path line after the map",
+        ));
+    };
+    if let Some(l) = map.iter().find(|l| l.text.contains(|c| !" .#".contains(c))) {
+        let i = l.text.find(|c| !" .#".contains(c)).unwrap();
+        return Err(l.error_at(i, "invalid tile, expected ' ', '.' or '#'"));
+    }
 
-    let max_width = input
-        .lines()
-        .filter(|l| !(l.is_empty() || l.chars().next().unwrap().is_ascii_digit()))
-        .map(|l| l.len())
+    let max_width = map
+        .iter()
+        .map(|l| l.text.len())
         .max()
-        .unwrap();
-    let mut game = Game::new(
-        iter::once(" ".repeat(max_width).as_str())
-            .chain(
-                input
-                    .lines()
-                    .chain(iter::once(" ".repeat(max_width).as_str())),
-            )
-            .filter(|l| !(l.is_empty() || l.chars().next().unwrap().is_ascii_digit()))
-            .map(|l| {
-                " ".chars()
-                    .chain(l.chars().chain(" ".repeat(max_width + 1 - l.len()).chars()))
-                    .map(Tile::from)
-                    .collect::<Vec<Tile>>()
-            })
-            .collect::<Vec<Vec<Tile>>>(),
-        moves_re
-            .captures_iter(input.as_str())
-            .map(Move::from)
-            .collect::<Vec<Move>>(),
-    );
+        .ok_or_else(|| ParseError::new(1, 1, "missing map"))?;
+    let map = Grid::from_rows(map.iter().map(|l| {
+        l.text
+            .chars()
+            .chain(iter::repeat(' '))
+            .take(max_width)
+            .map(Tile::from)
+            .collect()
+    }));
+    Ok(Game::new(map.padded(1, 1, Tile::None), parse_moves(&path)?))
+}
 
+pub fn solution(
+    game: &Game,
+    p2: bool,
+    cube_size: usize,
+    teleports: &[Teleport],
+    ctx: &Context,
+) -> usize {
+    let mut game = game.clone();
     if p2 {
         apply_teleports(&mut game, teleports, cube_size);
     }
 
-    let (pos, dir) = game.play();
-    1000 * pos.y + 4 * pos.x + usize::from(dir)
+    let (pos, dir) = game.play(ctx);
+    (1000 * pos.y + 4 * pos.x) as usize + facing(dir)
 }
 
-fn get_teleports() -> Vec<Teleport> {
+pub fn get_teleports() -> Vec<Teleport> {
     vec![
         Teleport::from(Direction::Right, (1, 1), Direction::Down, (2, 0), false),
         Teleport::from(Direction::Up, (0, 2), Direction::Left, (1, 1), false),
@@ -453,8 +372,7 @@ fn get_teleports() -> Vec<Teleport> {
     ]
 }
 
-#[allow(dead_code)]
-fn get_sample_teleports() -> Vec<Teleport> {
+pub fn get_sample_teleports() -> Vec<Teleport> {
     vec![
         Teleport::from(Direction::Right, (2, 1), Direction::Up, (3, 2), true),
         Teleport::from(Direction::Right, (2, 0), Direction::Right, (3, 2), true),
@@ -466,48 +384,128 @@ fn get_sample_teleports() -> Vec<Teleport> {
     ]
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution("src/inputs/aoc_22.input", false, 50, &get_teleports()).to_string(),
-        solution("src/inputs/aoc_22.input", true, 50, &get_teleports()).to_string(),
-    )
+#[derive(Constructor)]
+pub struct Day22 {
+    // The cube layout is hardcoded for a given input shape, see
+    // `get_sample_teleports` for the sample input.
+    cube_size: usize,
+    teleports: fn() -> Vec<Teleport>,
+}
+
+impl Default for Day22 {
+    fn default() -> Self {
+        Day22 {
+            cube_size: 50,
+            teleports: get_teleports,
+        }
+    }
+}
+
+impl Solver for Day22 {
+    type Input = Game;
+
+    fn day(&self) -> usize {
+        22
+    }
+
+    fn title(&self) -> &'static str {
+        "Monkey Map"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, game: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(game, false, self.cube_size, &(self.teleports)(), ctx).to_string())
+    }
+
+    fn part2(&self, game: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(game, true, self.cube_size, &(self.teleports)(), ctx).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_22_sample.input")
+            .solver(Day22::new(4, get_sample_teleports))
+            .part1("6032")
+            .part2("5031")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use crate::visualize;
+    use std::fs;
 
     #[test]
     fn test_run_p1() {
-        assert_eq!(
-            solution(
-                "src/inputs/aoc_22_sample.input",
-                false,
-                4,
-                &get_sample_teleports()
-            ),
-            6032
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_22_sample.input").unwrap()).unwrap();
+        let game = parse(&fs::read_to_string("src/inputs/aoc_22.input").unwrap()).unwrap();
+        let ctx = Context::default();
+        assert_answer(
+            22,
+            Part::One,
+            "aoc_22_sample.input",
+            solution(&sample, false, 4, &get_sample_teleports(), &ctx),
         );
-        assert_eq!(
-            solution("src/inputs/aoc_22.input", false, 50, &get_teleports()),
-            27492
+        assert_answer(
+            22,
+            Part::One,
+            "aoc_22.input",
+            solution(&game, false, 50, &get_teleports(), &ctx),
         );
     }
 
     #[test]
     fn test_run_p2() {
-        assert_eq!(
-            solution(
-                "src/inputs/aoc_22_sample.input",
-                true,
-                4,
-                &get_sample_teleports()
-            ),
-            5031
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_22_sample.input").unwrap()).unwrap();
+        let game = parse(&fs::read_to_string("src/inputs/aoc_22.input").unwrap()).unwrap();
+        let ctx = Context::default();
+        assert_answer(
+            22,
+            Part::Two,
+            "aoc_22_sample.input",
+            solution(&sample, true, 4, &get_sample_teleports(), &ctx),
+        );
+        assert_answer(
+            22,
+            Part::Two,
+            "aoc_22.input",
+            solution(&game, true, 50, &get_teleports(), &ctx),
         );
+    }
+
+    #[test]
+    fn test_visualized() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_22_sample.input").unwrap()).unwrap();
+        let mut answer = 0;
+        let frames = visualize::record(22, Part::One, |ctx| {
+            answer = solution(&sample, false, 4, &get_sample_teleports(), ctx);
+        });
+        assert_eq!(answer, 6032);
+        assert_eq!(frames.len(), sample.moves.len());
+        let last = frames.last().unwrap().map(|p| p.glyph).to_string();
         assert_eq!(
-            solution("src/inputs/aoc_22.input", true, 50, &get_teleports()),
-            78291
+            last.lines().map(str::trim_end).collect::<Vec<&str>>(),
+            [
+                "",
+                "         >>v#",
+                "         .#v.",
+                "         #.v.",
+                "         ..v.",
+                " ...#...v..v#",
+                " >>>v...>#.>>",
+                " ..#v...#....",
+                " ...>>>>v..#.",
+                "         ...#....",
+                "         .....#..",
+                "         .#......",
+                "         ......#.",
+                "",
+            ]
         );
     }
 
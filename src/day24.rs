@@ -1,29 +1,18 @@
 extern crate derive_more;
 extern crate num;
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{normalize, Normalize, ParseError, ParseResult};
+use crate::pos::{Direction, Pos};
+use crate::sample;
+use crate::search::{Search, VisitedBy};
+use crate::solver::{PartError, PartResult, Sample, Solver};
+use crate::trace::Event;
+use crate::visualize::{Frame, Pixel};
 use derive_more::Constructor;
 use num::Integer;
 use smallvec::*;
-use std::collections::VecDeque;
-use std::{collections::HashSet, fs, ops::Add};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl From<Direction> for char {
-    fn from(d: Direction) -> char {
-        match d {
-            Direction::Down => 'v',
-            Direction::Left => '<',
-            Direction::Right => '>',
-            Direction::Up => '^',
-        }
-    }
-}
+use std::iter;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum Tile {
@@ -32,99 +21,101 @@ enum Tile {
     Blizzard(SmallVec<[Direction; 4]>),
 }
 
-impl From<char> for Tile {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Tile {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '.' => Tile::Empty,
-            '#' => Tile::Wall,
-            '^' => Tile::Blizzard(smallvec![Direction::Up; 1]),
-            '<' => Tile::Blizzard(smallvec![Direction::Left; 1]),
-            '>' => Tile::Blizzard(smallvec![Direction::Right; 1]),
-            'v' => Tile::Blizzard(smallvec![Direction::Down; 1]),
-            _ => panic!("Invalid tile: {c}"),
+            '.' => Ok(Tile::Empty),
+            '#' => Ok(Tile::Wall),
+            '^' | '<' | '>' | 'v' => Ok(Tile::Blizzard(smallvec![Direction::try_from(c)?; 1])),
+            _ => Err(format!("invalid tile '{c}'")),
         }
     }
 }
 
-impl From<&Tile> for char {
-    fn from(t: &Tile) -> char {
-        match t {
-            Tile::Wall => '#',
-            Tile::Empty => '.',
-            Tile::Blizzard(v) => {
-                if v.len() == 1 {
-                    char::from(v[0])
-                } else {
-                    v.len().to_string().chars().next().unwrap()
-                }
-            }
+// Tiles with more blizzards are brighter.
+impl From<&Tile> for Pixel {
+    fn from(tile: &Tile) -> Self {
+        match tile {
+            Tile::Wall => Pixel::new('#', [128, 128, 128]),
+            Tile::Empty => Pixel::new('.', [0, 0, 0]),
+            Tile::Blizzard(v) if v.len() == 1 => Pixel::new(char::from(v[0]), [90, 140, 200]),
+            Tile::Blizzard(v) => Pixel::new(
+                char::from_digit(v.len() as u32, 10).unwrap(),
+                [130 + 30 * v.len() as u8; 3],
+            ),
         }
     }
 }
 
-#[derive(Debug, Constructor, Copy, Clone, PartialEq, Eq, Hash)]
-struct Pos {
-    x: usize,
-    y: usize,
-}
-
-impl Add<(i32, i32)> for Pos {
-    type Output = Option<Self>;
+const EXPEDITION: Pixel = Pixel::new('E', [255, 160, 0]);
 
-    fn add(self, other: (i32, i32)) -> Option<Self> {
-        let x = self.x as i32 + other.0;
-        let y = self.y as i32 + other.1;
-        if x < 0 || y < 0 {
-            None
-        } else {
-            Some(Pos::new(x as usize, y as usize))
-        }
-    }
-}
-
-#[derive(Debug, Constructor, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Map {
-    grid: Vec<Tile>,
-    width: usize,
-    height: usize,
+    grid: Grid<Tile>,
 }
 
-impl From<&str> for Map {
-    fn from(s: &str) -> Self {
-        Self {
-            grid: s
-                .lines()
-                .flat_map(|l| l.chars().map(Tile::from).collect::<Vec<Tile>>())
-                .collect::<Vec<Tile>>(),
-            width: s.lines().next().unwrap().len(),
-            height: s.lines().count(),
+impl TryFrom<&str> for Map {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> ParseResult<Self> {
+        let width = s.lines().next().map_or(0, str::len);
+        let height = s.lines().count();
+        if width < 3 || height < 3 {
+            return Err(ParseError::new(1, 1, "map must be at least 3x3"));
+        }
+        let grid = Grid::parse(s, Tile::try_from)?;
+        // The walls go around the valley, with a gap for the entrance in the
+        // top row and one for the exit in the bottom row.
+        let (width, height) = (grid.width(), grid.height());
+        let mut gaps = [false; 2];
+        for ((x, y), tile) in grid.iter() {
+            let side = x == 0 || x == width - 1;
+            let error = |message| Err(ParseError::new(y + 1, x + 1, message));
+            match tile {
+                Tile::Blizzard(_) if side || y == 0 || y == height - 1 => {
+                    return error("blizzard outside the valley");
+                }
+                Tile::Empty if side => return error("gap in a side wall"),
+                Tile::Empty if y == 0 || y == height - 1 => {
+                    let (gap, name) = match y {
+                        0 => (&mut gaps[0], "entrance"),
+                        _ => (&mut gaps[1], "exit"),
+                    };
+                    if *gap {
+                        return error(&format!("second {name}"));
+                    }
+                    *gap = true;
+                }
+                _ => {}
+            }
+        }
+        match gaps {
+            [false, _] => Err(ParseError::new(1, 1, "missing entrance")),
+            [_, false] => Err(ParseError::new(height, 1, "missing exit")),
+            _ => Ok(Self { grid }),
         }
     }
 }
 
 impl Map {
-    fn index_to_pos(&self, index: usize) -> Pos {
-        Pos {
-            x: index % self.width,
-            y: index / self.width,
-        }
-    }
-
-    fn pos_to_index(&self, x: usize, y: usize) -> usize {
-        y * self.width + x
+    fn get_tile(&self, p: Pos) -> &Tile {
+        &self.grid[p]
     }
 
-    fn get_tile(&self, x: usize, y: usize) -> &Tile {
-        &self.grid[self.pos_to_index(x, y)]
+    fn get_tile_mut(&mut self, p: Pos) -> &mut Tile {
+        &mut self.grid[p]
     }
 
-    fn get_tile_mut(&mut self, x: usize, y: usize) -> &mut Tile {
-        let index = self.pos_to_index(x, y);
-        self.grid.get_mut(index).unwrap()
+    fn frame(&self, expedition: Pos) -> Frame {
+        let mut frame = self.grid.map(|tile| Pixel::from(tile));
+        frame[expedition] = EXPEDITION;
+        frame
     }
 
     fn reset(&mut self) {
-        for tile in &mut self.grid {
+        for tile in self.grid.iter_mut() {
             if tile != &Tile::Wall {
                 *tile = Tile::Empty;
             }
@@ -132,83 +123,49 @@ impl Map {
     }
 
     fn get_entrance(&self) -> Pos {
-        self.index_to_pos(
-            self.grid
-                .iter()
-                .enumerate()
-                .find(|(_, t)| t == &&Tile::Empty)
-                .unwrap()
-                .0,
-        )
+        Pos::from(self.grid.position(|t| t == &Tile::Empty).unwrap())
     }
 
     fn get_exit(&self) -> Pos {
-        self.index_to_pos(
-            self.grid
-                .iter()
-                .enumerate()
-                .rev()
-                .find(|(_, t)| t == &&Tile::Empty)
-                .unwrap()
-                .0,
-        )
-    }
-
-    #[allow(dead_code)]
-    fn print(&self) {
-        self.grid.iter().enumerate().for_each(|(i, t)| {
-            if i % self.width == 0 {
-                println!();
-            }
-            print!("{}", char::from(t));
-        });
-        println!();
+        Pos::from(self.grid.rposition(|t| t == &Tile::Empty).unwrap())
     }
 
+    // Blizzards reaching a wall, or the entrance or exit, start over from the
+    // other side of the valley.
     fn blizzard_can_move(&self, p: &Pos, d: &Direction) -> Pos {
-        match self.get_tile(p.x, p.y) {
-            Tile::Empty => *p,
-            Tile::Blizzard(_) => *p,
-            Tile::Wall => match d {
+        let (width, height) = (self.grid.width() as i64, self.grid.height() as i64);
+        let inside = (1..width - 1).contains(&p.x) && (1..height - 1).contains(&p.y);
+        match self.get_tile(*p) {
+            Tile::Empty | Tile::Blizzard(_) if inside => *p,
+            _ => match d {
                 Direction::Down => Pos::new(p.x, 1),
-                Direction::Up => Pos::new(p.x, self.height - 2),
-                Direction::Left => Pos::new(self.width - 2, p.y),
+                Direction::Up => Pos::new(p.x, self.grid.height() as i64 - 2),
+                Direction::Left => Pos::new(self.grid.width() as i64 - 2, p.y),
                 Direction::Right => Pos::new(1, p.y),
             },
         }
     }
 
     fn advance_blizzard(&self, p: &Pos, d: &Direction) -> Pos {
-        match d {
-            Direction::Down => self.blizzard_can_move(&p.add((0, 1)).unwrap(), d),
-            Direction::Left => self.blizzard_can_move(&p.add((-1, 0)).unwrap(), d),
-            Direction::Right => self.blizzard_can_move(&p.add((1, 0)).unwrap(), d),
-            Direction::Up => self.blizzard_can_move(&p.add((0, -1)).unwrap(), d),
-        }
+        self.blizzard_can_move(&(*p + *d), d)
     }
 
     fn get_possible_palyer_moves(&self, p: &Pos) -> SmallVec<[Pos; 5]> {
-        [(0, 0), (-1, 0), (0, -1), (1, 0), (0, 1)]
-            .iter()
-            .map(|v| p.add(*v))
-            .filter(|np| {
-                np.is_some_and(|np| {
-                    np.y != self.height && self.get_tile(np.x, np.y) == &Tile::Empty
-                })
-            })
-            .map(|np| np.unwrap())
+        // Waiting, or a step off the entrance or exit row into the map.
+        iter::once(*p)
+            .chain(p.neighbours4())
+            .filter(|np| self.grid.get_pos(*np) == Some(&Tile::Empty))
             .collect()
     }
 }
 
 #[derive(Debug)]
-struct Game {
+pub struct Game {
     lcm: i32,
     maps: Vec<Map>,
-    round: i32,
 }
 
-#[derive(Debug, Constructor, PartialEq, Eq)]
+#[derive(Debug, Clone, Constructor, PartialEq, Eq, Hash)]
 struct GameState {
     round: i32,
     player: Pos,
@@ -216,7 +173,7 @@ struct GameState {
 
 impl Game {
     fn new(map: Map) -> Self {
-        let lcm = (map.height - 2).lcm(&(&map.width - 2)) as i32;
+        let lcm = (map.grid.height() - 2).lcm(&(map.grid.width() - 2)) as i32;
         let mut reset_map = map.clone();
         reset_map.reset();
 
@@ -228,24 +185,19 @@ impl Game {
             map = next_map;
         }
 
-        Game {
-            lcm,
-            maps,
-            round: 0,
-        }
+        Game { lcm, maps }
     }
 
     fn next_blizzard_map(map: &Map, reset_map: &Map) -> Map {
         let mut r = reset_map.clone();
-        map.grid.iter().enumerate().for_each(|(index, t)| {
+        map.grid.iter().for_each(|(pos, t)| {
             if let Tile::Blizzard(dirs) = t {
                 dirs.iter().for_each(|d| {
-                    let p = map.index_to_pos(index);
-                    let new_pos = map.advance_blizzard(&p, d);
-                    if let Tile::Blizzard(b) = r.get_tile_mut(new_pos.x, new_pos.y) {
+                    let new_pos = map.advance_blizzard(&Pos::from(pos), d);
+                    if let Tile::Blizzard(b) = r.get_tile_mut(new_pos) {
                         b.push(*d);
                     } else {
-                        *r.get_tile_mut(new_pos.x, new_pos.y) = Tile::Blizzard(smallvec![*d; 1]);
+                        *r.get_tile_mut(new_pos) = Tile::Blizzard(smallvec![*d; 1]);
                     }
                 });
             }
@@ -253,75 +205,141 @@ impl Game {
         r
     }
 
-    fn wait_until_move_is_possible(&mut self, player: &Pos) -> Option<(SmallVec<[Pos; 5]>, i32)> {
+    fn wait_until_move_is_possible(
+        &self,
+        player: &Pos,
+        round: i32,
+        ctx: &Context,
+    ) -> Option<(SmallVec<[Pos; 5]>, i32)> {
+        let mut round = round;
         let mut rounds = 0;
         loop {
-            let moves =
-                self.maps[(self.round % self.lcm) as usize].get_possible_palyer_moves(player);
+            let moves = self.maps[(round % self.lcm) as usize].get_possible_palyer_moves(player);
             rounds += 1;
             if !moves.is_empty() {
                 return Some((moves, rounds));
             }
-            if let Tile::Blizzard(_) =
-                &self.maps[(self.round % self.lcm) as usize].get_tile(player.x, player.y)
-            {
-                //println!("blizzard moved to the player field");
+            if let Tile::Blizzard(_) = &self.maps[(round % self.lcm) as usize].get_tile(*player) {
+                ctx.trace(|| {
+                    Event::new("caught")
+                        .with("x", player.x)
+                        .with("y", player.y)
+                        .with("round", round)
+                });
                 return None;
             }
-            self.round += 1;
+            round += 1;
         }
     }
 
-    fn play_bfs(&mut self, player: &Pos, to: &Pos) -> i32 {
-        let mut visited: HashSet<(Pos, i32)> = HashSet::with_capacity(256_000);
-        let mut next_moves: VecDeque<GameState> = VecDeque::with_capacity(4096);
-        next_moves.push_back(GameState::new(self.round, *player));
-        loop {
-            if next_moves.is_empty() {
-                panic!("couldn't find path between {player:?} and {to:?}");
-            }
-            let gs = next_moves.pop_front().unwrap();
-            self.round = gs.round;
-            if gs.player == *to {
-                return gs.round - 1;
-            }
-            if !visited.insert((gs.player, gs.round % self.lcm)) {
-                continue;
-            }
+    // Returns the round in which the player reaches `to`, if the blizzards
+    // ever let it through. They repeat every `lcm` rounds, so states are told
+    // apart by the round modulo `lcm`.
+    fn play_bfs(&self, round: i32, player: &Pos, to: &Pos, ctx: &Context) -> Option<i32> {
+        let lcm = self.lcm;
+        let mut search = Search::new([GameState::new(round, *player)])
+            .visited(VisitedBy::new(|gs: &GameState| (gs.player, gs.round % lcm)));
+        if ctx.is_visualizing() {
+            search = search.with_paths();
+        }
+        let found = search.astar(
+            |gs| {
+                let round = gs.round;
+                self.wait_until_move_is_possible(&gs.player, round, ctx)
+                    .into_iter()
+                    .flat_map(move |(moves, wait_rounds)| {
+                        moves
+                            .into_iter()
+                            .map(move |mv| (GameState::new(round + wait_rounds, mv), wait_rounds))
+                    })
+            },
+            |gs| gs.player.manhattan(*to) as i32,
+            |gs| gs.player == *to,
+        )?;
+        self.show_route(&found.path, ctx);
+        Some(found.state.round)
+    }
 
-            if let Some((moves, wait_rounds)) = self.wait_until_move_is_possible(&gs.player) {
-                next_moves.extend(
-                    moves
-                        .into_iter()
-                        .map(|mv| GameState::new(gs.round + wait_rounds, mv)),
-                );
-                next_moves.make_contiguous().sort_by_key(|gs| gs.round);
+    // Shows the blizzards of every round along `route`. Moves from a state are
+    // made among the blizzards of its round, so the expedition stays put
+    // until the round before the next state.
+    fn show_route(&self, route: &[GameState], ctx: &Context) {
+        for (from, to) in route.iter().zip(route.iter().skip(1)) {
+            for round in from.round..to.round {
+                let expedition = match round + 1 == to.round {
+                    true => to.player,
+                    false => from.player,
+                };
+                ctx.frame(|| self.maps[(round % self.lcm) as usize].frame(expedition));
             }
         }
     }
 }
 
-fn solution(filename: &str) -> (i32, i32) {
-    let mut game = Game::new(Map::from(fs::read_to_string(filename).unwrap().as_str()));
+pub fn parse(input: &str) -> ParseResult<Game> {
+    let input = &*normalize(input, Normalize::default());
+    Ok(Game::new(Map::try_from(input)?))
+}
+
+pub fn part1(game: &Game, ctx: &Context) -> Option<i32> {
     let from = game.maps[0].get_entrance();
     let to = game.maps[0].get_exit();
-    let p1 = game.play_bfs(&from, &to);
-    game.play_bfs(&to, &from);
-    (p1, game.play_bfs(&from, &to))
+    Some(game.play_bfs(0, &from, &to, ctx)? - 1)
+}
+
+pub fn part2(game: &Game, ctx: &Context) -> Option<i32> {
+    let from = game.maps[0].get_entrance();
+    let to = game.maps[0].get_exit();
+    let round = game.play_bfs(0, &from, &to, ctx)?;
+    let round = game.play_bfs(round, &to, &from, ctx)?;
+    Some(game.play_bfs(round, &from, &to, ctx)? - 1)
+}
+
+fn no_path() -> PartError {
+    PartError::Unsolvable("couldn't find a path through the blizzards".to_string())
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_24.input");
-    (p1.to_string(), p2.to_string())
+pub struct Day24;
+
+impl Solver for Day24 {
+    type Input = Game;
+
+    fn day(&self) -> usize {
+        24
+    }
+
+    fn title(&self) -> &'static str {
+        "Blizzard Basin"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, game: &Self::Input, ctx: &Context) -> PartResult {
+        part1(game, ctx).map(|n| n.to_string()).ok_or_else(no_path)
+    }
+
+    fn part2(&self, game: &Self::Input, ctx: &Context) -> PartResult {
+        part2(game, ctx).map(|n| n.to_string()).ok_or_else(no_path)
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_24_sample.input").part1("18").part2("54")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use crate::visualize;
+    use std::fs;
 
     #[test]
     fn test_blizzard_moves() {
-        let m = Map::from("#####\n#...#\n#...#\n#...#\n#####");
+        let m = Map::try_from("#.###\n#...#\n#...#\n#...#\n###.#").unwrap();
         assert_eq!(
             m.advance_blizzard(&Pos::new(2, 2), &Direction::Down),
             Pos::new(2, 3)
@@ -357,11 +375,121 @@ mod tests {
             m.advance_blizzard(&Pos::new(3, 2), &Direction::Right),
             Pos::new(1, 2)
         );
+        // Through the exit.
+        assert_eq!(
+            m.advance_blizzard(&Pos::new(3, 3), &Direction::Down),
+            Pos::new(3, 1)
+        );
     }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_24_sample.input"), (18, 54));
-        assert_eq!(solution("src/inputs/aoc_24.input"), (314, 896));
+        let sample = Game::new(
+            Map::try_from(
+                fs::read_to_string("src/inputs/aoc_24_sample.input")
+                    .unwrap()
+                    .as_str(),
+            )
+            .unwrap(),
+        );
+        let game = Game::new(
+            Map::try_from(
+                fs::read_to_string("src/inputs/aoc_24.input")
+                    .unwrap()
+                    .as_str(),
+            )
+            .unwrap(),
+        );
+        let ctx = Context::default();
+        assert_answer(
+            24,
+            Part::One,
+            "aoc_24_sample.input",
+            part1(&sample, &ctx).unwrap(),
+        );
+        assert_answer(
+            24,
+            Part::Two,
+            "aoc_24_sample.input",
+            part2(&sample, &ctx).unwrap(),
+        );
+        assert_answer(24, Part::One, "aoc_24.input", part1(&game, &ctx).unwrap());
+        assert_answer(24, Part::Two, "aoc_24.input", part2(&game, &ctx).unwrap());
+    }
+
+    #[test]
+    fn test_no_path() {
+        let game = parse("#.###\n#.#.#\n###.#").unwrap();
+        assert_eq!(Day24.part1(&game, &Context::default()), Err(no_path()));
+    }
+
+    #[test]
+    fn test_visualized() {
+        let sample = Game::new(
+            Map::try_from(
+                fs::read_to_string("src/inputs/aoc_24_sample.input")
+                    .unwrap()
+                    .as_str(),
+            )
+            .unwrap(),
+        );
+        let frames = visualize::record(24, Part::One, |ctx| {
+            assert_eq!(part1(&sample, ctx), Some(18))
+        });
+        let frames = frames
+            .iter()
+            .map(|frame| frame.map(|p| p.glyph).to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(frames.len(), 19);
+        assert_eq!(
+            frames[0],
+            "#E######\n\
+             #>>.<^<#\n\
+             #.<..<<#\n\
+             #>v.><>#\n\
+             #<^v^^>#\n\
+             ######.#"
+        );
+        assert_eq!(
+            frames[18],
+            "#.######\n\
+             #>2.<.<#\n\
+             #.2v^2<#\n\
+             #>..>2>#\n\
+             #<....>#\n\
+             ######E#"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Map::try_from("#.###\n#.<.x\n###.#").unwrap_err(),
+            ParseError::new(2, 5, "invalid tile 'x'")
+        );
+        assert_eq!(
+            Map::try_from("#.###\n#..#\n###.#").unwrap_err(),
+            ParseError::new(2, 1, "expected 5 tiles")
+        );
+        assert_eq!(
+            Map::try_from("#.#^#\n#...#\n###.#").unwrap_err(),
+            ParseError::new(1, 4, "blizzard outside the valley")
+        );
+        assert_eq!(
+            Map::try_from("#.###\n....#\n###.#").unwrap_err(),
+            ParseError::new(2, 1, "gap in a side wall")
+        );
+        assert_eq!(
+            Map::try_from("#..##\n#...#\n###.#").unwrap_err(),
+            ParseError::new(1, 3, "second entrance")
+        );
+        assert_eq!(
+            Map::try_from("#####\n#...#\n###.#").unwrap_err(),
+            ParseError::new(1, 1, "missing entrance")
+        );
+        assert_eq!(
+            Map::try_from("#.###\n#...#\n#####").unwrap_err(),
+            ParseError::new(3, 1, "missing exit")
+        );
     }
 }
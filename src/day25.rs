@@ -1,26 +1,26 @@
-use std::{collections::VecDeque, fs};
-
-fn snafu_to_dec(snafu: &str) -> i64 {
-    snafu
-        .chars()
-        .rev()
-        .fold((0, 1), |a, c| {
-            (
-                match c {
-                    '2' => a.0 + 2 * a.1,
-                    '1' => a.0 + a.1,
-                    '0' => a.0,
-                    '-' => a.0 - a.1,
-                    '=' => a.0 - 2 * a.1,
-                    _ => panic!("Invalid SNAFU character."),
-                },
-                a.1 * 5,
-            )
-        })
-        .0
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use std::collections::VecDeque;
+
+/// Value of `snafu`, unless it has a character other than `=-012` or doesn't
+/// fit an `i64`.
+pub fn snafu_to_dec(snafu: &str) -> Option<i64> {
+    snafu.chars().try_fold(0i64, |n, c| {
+        let digit = match c {
+            '2' => 2,
+            '1' => 1,
+            '0' => 0,
+            '-' => -1,
+            '=' => -2,
+            _ => return None,
+        };
+        n.checked_mul(5)?.checked_add(digit)
+    })
 }
 
-fn dec_to_snafu(v: i64) -> String {
+pub fn dec_to_snafu(v: i64) -> String {
     let trans = ['=', '-', '0', '1', '2'];
     let mut v = v;
     let mut r = VecDeque::new();
@@ -43,39 +43,109 @@ fn dec_to_snafu(v: i64) -> String {
     r.into_iter().collect::<String>()
 }
 
-fn solution(filename: &str) -> String {
-    dec_to_snafu(
-        fs::read_to_string(filename)
-            .unwrap()
-            .lines()
-            .map(snafu_to_dec)
-            .sum(),
-    )
+pub fn parse(input: &str) -> ParseResult<Vec<i64>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input)
+        .map(|line| match line.text.find(|c| !"=-012".contains(c)) {
+            Some(i) => Err(line.error_at(i, "invalid SNAFU digit, expected =, -, 0, 1 or 2")),
+            None => {
+                snafu_to_dec(line.text).ok_or_else(|| line.error_at(0, "SNAFU number too large"))
+            }
+        })
+        .collect()
+}
+
+pub fn solution(numbers: &[i64]) -> String {
+    dec_to_snafu(numbers.iter().sum())
 }
 
-pub fn run() -> (String, String) {
-    (solution("src/inputs/aoc_25.input"), "".to_string())
+pub struct Day25;
+
+impl Solver for Day25 {
+    type Input = Vec<i64>;
+
+    fn day(&self) -> usize {
+        25
+    }
+
+    fn title(&self) -> &'static str {
+        "Full of Hot Air"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, numbers: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(numbers))
+    }
+
+    fn part2(&self, _numbers: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok("".to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_25_sample.input").part1("2=-1=0")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use crate::parse::ParseError;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Base 5 digits of `v`, lowest first, where 3 and 4 become -2 and -1 by
+    // carrying one into the next digit.
+    fn naive_snafu(mut v: i64) -> String {
+        let mut digits = Vec::new();
+        while v != 0 {
+            let (digit, carry) = match v % 5 {
+                3 => ('=', 1),
+                4 => ('-', 1),
+                d => ((b'0' + d as u8) as char, 0),
+            };
+            digits.push(digit);
+            v = v / 5 + carry;
+        }
+        digits.iter().rev().collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_snafu_matches_naive(
+            numbers in prop::collection::vec(1..1_000_000_000_000i64, 1..10)
+        ) {
+            let input = numbers.iter().map(|n| naive_snafu(*n) + "\n").collect::<String>();
+            prop_assert_eq!(&parse(&input).unwrap(), &numbers);
+            prop_assert_eq!(solution(&numbers), naive_snafu(numbers.iter().sum()));
+        }
+    }
 
     #[test]
     fn test_snafu_to_dec() {
-        assert_eq!(snafu_to_dec("1=-0-2"), 1747);
-        assert_eq!(snafu_to_dec("12111"), 906);
-        assert_eq!(snafu_to_dec("2=0="), 198);
-        assert_eq!(snafu_to_dec("21"), 11);
-        assert_eq!(snafu_to_dec("2=01"), 201);
-        assert_eq!(snafu_to_dec("111"), 31);
-        assert_eq!(snafu_to_dec("20012"), 1257);
-        assert_eq!(snafu_to_dec("112"), 32);
-        assert_eq!(snafu_to_dec("1=-1="), 353);
-        assert_eq!(snafu_to_dec("1-12"), 107);
-        assert_eq!(snafu_to_dec("12"), 7);
-        assert_eq!(snafu_to_dec("1="), 3);
-        assert_eq!(snafu_to_dec("122"), 37);
+        assert_eq!(snafu_to_dec("1=-0-2"), Some(1747));
+        assert_eq!(snafu_to_dec("12111"), Some(906));
+        assert_eq!(snafu_to_dec("2=0="), Some(198));
+        assert_eq!(snafu_to_dec("21"), Some(11));
+        assert_eq!(snafu_to_dec("2=01"), Some(201));
+        assert_eq!(snafu_to_dec("111"), Some(31));
+        assert_eq!(snafu_to_dec("20012"), Some(1257));
+        assert_eq!(snafu_to_dec("112"), Some(32));
+        assert_eq!(snafu_to_dec("1=-1="), Some(353));
+        assert_eq!(snafu_to_dec("1-12"), Some(107));
+        assert_eq!(snafu_to_dec("12"), Some(7));
+        assert_eq!(snafu_to_dec("1="), Some(3));
+        assert_eq!(snafu_to_dec("122"), Some(37));
+        assert_eq!(snafu_to_dec("1x"), None);
+        assert_eq!(snafu_to_dec(&"2".repeat(28)), None);
+        assert_eq!(
+            parse(&"2".repeat(28)).unwrap_err(),
+            ParseError::new(1, 1, "SNAFU number too large")
+        );
     }
 
     #[test]
@@ -99,7 +169,9 @@ mod tests {
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_25_sample.input"), "2=-1=0");
-        assert_eq!(solution("src/inputs/aoc_25.input"), "2=--=0000-1-0-=1=0=2");
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_25_sample.input").unwrap()).unwrap();
+        let numbers = parse(&fs::read_to_string("src/inputs/aoc_25.input").unwrap()).unwrap();
+        assert_answer(25, Part::One, "aoc_25_sample.input", solution(&sample));
+        assert_answer(25, Part::One, "aoc_25.input", solution(&numbers));
     }
 }
@@ -1,11 +1,28 @@
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 use itertools::Itertools;
 use std::collections::HashSet;
-use std::fs;
 
-fn day3_p1() -> u64 {
-    fs::read_to_string("src/inputs/aoc_3.input")
-        .unwrap()
-        .lines()
+pub fn parse(input: &str) -> ParseResult<Vec<String>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input)
+        .map(|line| {
+            if let Some(i) = line.text.find(|c: char| !c.is_ascii_alphabetic()) {
+                Err(line.error_at(i, "invalid item, expected a-z or A-Z"))
+            } else if line.text.len() % 2 != 0 {
+                Err(line.error(line.text, "odd number of items"))
+            } else {
+                Ok(line.text.to_string())
+            }
+        })
+        .collect()
+}
+
+pub fn day3_p1(rucksacks: &[String]) -> u64 {
+    rucksacks
+        .iter()
         .map(|line| {
             HashSet::from_iter(line[..line.len() / 2].bytes())
                 .intersection(&HashSet::<u8>::from_iter(line[line.len() / 2..].bytes()))
@@ -21,10 +38,9 @@ fn day3_p1() -> u64 {
         .sum()
 }
 
-fn day3_p2() -> u64 {
-    fs::read_to_string("src/inputs/aoc_3.input")
-        .unwrap()
-        .lines()
+pub fn day3_p2(rucksacks: &[String]) -> u64 {
+    rucksacks
+        .iter()
         .chunks(3)
         .into_iter()
         .map(|chunk| {
@@ -45,17 +61,81 @@ fn day3_p2() -> u64 {
         .sum()
 }
 
-pub fn run() -> (String, String) {
-    (day3_p1().to_string(), day3_p2().to_string())
+pub struct Day3;
+
+impl Solver for Day3 {
+    type Input = Vec<String>;
+
+    fn day(&self) -> usize {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Rucksack Reorganization"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, rucksacks: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(day3_p1(rucksacks).to_string())
+    }
+
+    fn part2(&self, rucksacks: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(day3_p2(rucksacks).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_3_sample.input").part1("157").part2("70")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    const ITEMS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    // Priorities of the items found in every one of `parts`, each counted once.
+    fn naive_priorities(parts: &[&str]) -> u64 {
+        ITEMS
+            .chars()
+            .zip(1..)
+            .filter(|(item, _)| parts.iter().all(|part| part.contains(*item)))
+            .map(|(_, priority)| priority)
+            .sum()
+    }
+
+    proptest! {
+        #[test]
+        fn test_priorities_match_naive(
+            groups in prop::collection::vec(prop::array::uniform3("([a-zA-Z]{2}){1,8}"), 1..5)
+        ) {
+            let rucksacks = groups.iter().flatten().collect::<Vec<_>>();
+            let input = rucksacks.iter().map(|r| format!("{r}\n")).collect::<String>();
+            let parsed = parse(&input).unwrap();
+            let part1 = rucksacks
+                .iter()
+                .map(|r| naive_priorities(&[&r[..r.len() / 2], &r[r.len() / 2..]]))
+                .sum::<u64>();
+            prop_assert_eq!(day3_p1(&parsed), part1);
+            let part2 = groups
+                .iter()
+                .map(|group| naive_priorities(&group.each_ref().map(String::as_str)))
+                .sum::<u64>();
+            prop_assert_eq!(day3_p2(&parsed), part2);
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(day3_p1(), 7674);
-        assert_eq!(day3_p2(), 2805);
+        let rucksacks = parse(&fs::read_to_string("src/inputs/aoc_3.input").unwrap()).unwrap();
+        assert_answer(3, Part::One, "aoc_3.input", day3_p1(&rucksacks));
+        assert_answer(3, Part::Two, "aoc_3.input", day3_p2(&rucksacks));
     }
 }
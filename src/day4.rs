@@ -1,51 +1,147 @@
-use std::fs;
+use crate::context::Context;
+use crate::parse::{lines, normalize, Line, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 
-fn contained(a: &str, b: &str, x: &str, y: &str) -> bool {
-    let a = a.parse::<u32>().unwrap();
-    let b = b.parse::<u32>().unwrap();
-    let x = x.parse::<u32>().unwrap();
-    let y = y.parse::<u32>().unwrap();
+#[derive(Debug)]
+pub struct Assignment {
+    a: u32,
+    b: u32,
+    x: u32,
+    y: u32,
+}
+
+impl TryFrom<Line<'_>> for Assignment {
+    type Error = ParseError;
+
+    fn try_from(line: Line) -> ParseResult<Self> {
+        fn range<'a>(line: &Line, r: &'a str) -> ParseResult<(&'a str, &'a str)> {
+            r.split_once('-')
+                .ok_or_else(|| line.error(r, format!("invalid range '{r}'")))
+        }
+        let (r1, r2) = line
+            .text
+            .split_once(',')
+            .ok_or_else(|| line.error(line.text, "expected two comma separated ranges"))?;
+        let ((a, b), (x, y)) = (range(&line, r1)?, range(&line, r2)?);
+        Ok(Assignment {
+            a: line.number(a)?,
+            b: line.number(b)?,
+            x: line.number(x)?,
+            y: line.number(y)?,
+        })
+    }
+}
+
+pub fn contained(&Assignment { a, b, x, y }: &Assignment) -> bool {
     (a >= x && b <= y) || (x >= a && y <= b)
 }
 
-fn overlapped(a: &str, b: &str, x: &str, y: &str) -> bool {
-    let a = a.parse::<u32>().unwrap();
-    let b = b.parse::<u32>().unwrap();
-    let x = x.parse::<u32>().unwrap();
-    let y = y.parse::<u32>().unwrap();
+pub fn overlapped(&Assignment { a, b, x, y }: &Assignment) -> bool {
     (a >= x && a <= y) || (b >= x && b <= y) || (x >= a && x <= b) || (y >= a && y <= b)
 }
 
-fn solution<F>(condition: F) -> usize
+pub fn parse(input: &str) -> ParseResult<Vec<Assignment>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input).map(Assignment::try_from).collect()
+}
+
+pub fn solution<F>(assignments: &[Assignment], condition: F) -> usize
 where
-    F: Fn(&str, &str, &str, &str) -> bool,
+    F: Fn(&Assignment) -> bool,
 {
-    fs::read_to_string("src/inputs/aoc_4.input")
-        .unwrap()
-        .lines()
-        .filter(|line| {
-            let p: Vec<&str> = line.split(',').collect();
-            let r1: Vec<&str> = p[0].split('-').collect();
-            let r2: Vec<&str> = p[1].split('-').collect();
-            condition(r1[0], r1[1], r2[0], r2[1])
-        })
-        .count()
+    assignments.iter().filter(|a| condition(a)).count()
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution(contained).to_string(),
-        solution(overlapped).to_string(),
-    )
+pub struct Day4;
+
+impl Solver for Day4 {
+    type Input = Vec<Assignment>;
+
+    fn day(&self) -> usize {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Camp Cleanup"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, assignments: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(assignments, contained).to_string())
+    }
+
+    fn part2(&self, assignments: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(assignments, overlapped).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_4_sample.input").part1("2").part2("4")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+    use std::fs;
+
+    fn range() -> impl Strategy<Value = (u32, u32)> {
+        (0..20u32, 0..20u32).prop_map(|(a, b)| (a.min(b), a.max(b)))
+    }
+
+    proptest! {
+        #[test]
+        fn test_ranges_match_naive(pairs in prop::collection::vec((range(), range()), 1..20)) {
+            let input = pairs
+                .iter()
+                .map(|((a, b), (x, y))| format!("{a}-{b},{x}-{y}\n"))
+                .collect::<String>();
+            let assignments = parse(&input).unwrap();
+            // The sections of both elves of each pair.
+            let sections = pairs
+                .iter()
+                .map(|((a, b), (x, y))| {
+                    (
+                        (*a..=*b).collect::<HashSet<_>>(),
+                        (*x..=*y).collect::<HashSet<_>>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            prop_assert_eq!(
+                solution(&assignments, contained),
+                sections
+                    .iter()
+                    .filter(|(s1, s2)| s1.is_subset(s2) || s2.is_subset(s1))
+                    .count()
+            );
+            prop_assert_eq!(
+                solution(&assignments, overlapped),
+                sections.iter().filter(|(s1, s2)| !s1.is_disjoint(s2)).count()
+            );
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution(contained), 487);
-        assert_eq!(solution(overlapped), 849);
+        let assignments = parse(&fs::read_to_string("src/inputs/aoc_4.input").unwrap()).unwrap();
+        assert_answer(
+            4,
+            Part::One,
+            "aoc_4.input",
+            solution(&assignments, contained),
+        );
+        assert_answer(
+            4,
+            Part::Two,
+            "aoc_4.input",
+            solution(&assignments, overlapped),
+        );
     }
 }
@@ -1,72 +1,288 @@
-use regex::Regex;
-use std::fs;
+use crate::context::Context;
+use crate::parse::{blocks, normalize, Normalize, ParseError, ParseResult, Scanner};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 
 // Let's cheat a little and and assume there is always 9 stacks at most.
 const MAX_CRATES: usize = 9;
 
-fn part1(n: usize, src: usize, dst: usize, stacks: &mut [Vec<char>; MAX_CRATES]) {
+pub fn part1(n: usize, src: usize, dst: usize, stacks: &mut [Vec<char>; MAX_CRATES]) {
     (0..n).for_each(|_| {
         let x = stacks[src].pop().unwrap();
         stacks[dst].push(x);
     });
 }
 
-fn part2(n: usize, src: usize, dst: usize, stacks: &mut [Vec<char>; MAX_CRATES]) {
+pub fn part2(n: usize, src: usize, dst: usize, stacks: &mut [Vec<char>; MAX_CRATES]) {
     let x: Vec<char> = stacks[src].drain((stacks[src].len() - n)..).collect();
     stacks[dst].extend(x);
 }
 
-fn solution<F>(stack_operation: F) -> String
-where
-    F: Fn(usize, usize, usize, &mut [Vec<char>; MAX_CRATES]),
-{
+/// Move of `n` crates between stacks, numbered from 0.
+#[derive(Debug)]
+pub struct Move {
+    pub n: usize,
+    pub src: usize,
+    pub dst: usize,
+}
+
+/// Stacks of crates and the moves of the crane, which `parse` checks never
+/// take more crates than a stack holds.
+#[derive(Debug)]
+pub struct Supplies {
+    stacks: [Vec<char>; MAX_CRATES],
+    moves: Vec<Move>,
+}
+
+impl Supplies {
+    /// Crates of every stack, from the bottom one.
+    pub fn stacks(&self) -> &[Vec<char>] {
+        &self.stacks
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+}
+
+pub fn parse(input: &str) -> ParseResult<Supplies> {
+    // The stacks are read by column, so their drawing is kept as it is.
+    let input = &*normalize(input, Normalize { trim: false });
     // Positions of crates for each stacks.
     let positions: Vec<usize> = (0..MAX_CRATES).map(|x| 4 * x + 1).collect();
-    let move_re = Regex::new(r"^move\s(?P<n>\d*)\sfrom\s(?P<src>\d*)\sto\s(?P<dst>\d*)$").unwrap();
-    let input = fs::read_to_string("src/inputs/aoc_5.input").unwrap();
     let mut stacks: [Vec<char>; MAX_CRATES] = Default::default();
+    let mut blocks = blocks(input);
 
-    input
-        .lines()
-        .filter(|line| line.contains('['))
+    let drawing = blocks.next().unwrap_or_default();
+    if !drawing.iter().any(|line| line.text.contains('[')) {
+        return Err(ParseError::new(1, 1, "missing stacks"));
+    }
+    drawing
+        .iter()
+        .filter(|line| line.text.contains('['))
         .for_each(|line| {
             positions.iter().for_each(|pos| {
-                let x = line.chars().nth(*pos).unwrap();
-                if x != ' ' && x.is_ascii_uppercase() {
+                if let Some(x) = line.text.chars().nth(*pos).filter(char::is_ascii_uppercase) {
                     stacks[(*pos - 1) / 4].push(x);
                 }
             });
         });
     stacks.iter_mut().for_each(|stack| stack.reverse());
 
-    input
-        .lines()
-        .filter(|line| line.starts_with("move"))
-        .for_each(|line| {
-            let mv = move_re.captures(line).unwrap();
-            let n = mv.name("n").unwrap().as_str().parse::<usize>().unwrap();
-            let src = mv.name("src").unwrap().as_str().parse::<usize>().unwrap() - 1;
-            let dst = mv.name("dst").unwrap().as_str().parse::<usize>().unwrap() - 1;
-            stack_operation(n, src, dst, &mut stacks);
-        });
+    let stack = |s: &mut Scanner, prefix| {
+        s.tag(prefix)?;
+        let at = *s;
+        match s.integer::<usize>()? {
+            n @ 1..=MAX_CRATES => Ok(n - 1),
+            n => Err(at.error(format!("invalid stack '{n}'"))),
+        }
+    };
+    // Both parts move as many crates, so the stacks are as high either way.
+    let mut heights = stacks.clone().map(|stack| stack.len());
+    let moves = blocks
+        .next()
+        .ok_or_else(|| ParseError::new(input.lines().count() + 1, 1, "missing moves"))?
+        .iter()
+        .map(|line| {
+            let mut s = line.scanner();
+            s.tag("move ")?;
+            let at = s;
+            let mv = Move {
+                n: s.integer()?,
+                src: stack(&mut s, " from ")?,
+                dst: stack(&mut s, " to ")?,
+            };
+            s.end()?;
+            if mv.n > heights[mv.src] {
+                return Err(at.error(format!(
+                    "can't move {} crates, stack {} holds {}",
+                    mv.n,
+                    mv.src + 1,
+                    heights[mv.src]
+                )));
+            }
+            heights[mv.src] -= mv.n;
+            heights[mv.dst] += mv.n;
+            Ok(mv)
+        })
+        .collect::<ParseResult<Vec<Move>>>()?;
+
+    Ok(Supplies { stacks, moves })
+}
+
+pub fn solution<F>(supplies: &Supplies, stack_operation: F) -> String
+where
+    F: Fn(usize, usize, usize, &mut [Vec<char>; MAX_CRATES]),
+{
+    let mut stacks = supplies.stacks.clone();
+    supplies
+        .moves
+        .iter()
+        .for_each(|mv| stack_operation(mv.n, mv.src, mv.dst, &mut stacks));
 
     stacks
         .iter()
-        .map(|stack| stack.last().unwrap())
+        .filter_map(|stack| stack.last())
         .collect::<String>()
 }
 
-pub fn run() -> (String, String) {
-    (solution(part1), solution(part2))
+pub struct Day5;
+
+impl Solver for Day5 {
+    type Input = Supplies;
+
+    fn day(&self) -> usize {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Supply Stacks"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, supplies: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(supplies, part1))
+    }
+
+    fn part2(&self, supplies: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(solution(supplies, part2))
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_5_sample.input").part1("CMZ").part2("MCD")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use crate::parse::ParseError;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Crates of each stack from the bottom, and moves as (n, src, dst) from 0.
+    type Drawing = (Vec<Vec<char>>, Vec<(usize, usize, usize)>);
+
+    // Moves never take more crates than a stack holds.
+    fn supplies() -> impl Strategy<Value = Drawing> {
+        let stack = prop::collection::vec(prop::char::range('A', 'Z'), 0..5);
+        (
+            prop::collection::vec(stack, 1..=MAX_CRATES).prop_filter("no crates", |stacks| {
+                stacks.iter().any(|stack| !stack.is_empty())
+            }),
+            prop::collection::vec((0..5usize, 0..MAX_CRATES, 0..MAX_CRATES), 1..15),
+        )
+            .prop_map(|(stacks, moves)| {
+                let mut heights = stacks.iter().map(Vec::len).collect::<Vec<_>>();
+                let moves = moves
+                    .into_iter()
+                    .map(|(n, src, dst)| {
+                        let (src, dst) = (src % stacks.len(), dst % stacks.len());
+                        let n = n.min(heights[src]);
+                        heights[src] -= n;
+                        heights[dst] += n;
+                        (n, src, dst)
+                    })
+                    .collect();
+                (stacks, moves)
+            })
+    }
+
+    // The drawing of the puzzle, stacks numbered below their crates.
+    fn drawing(stacks: &[Vec<char>]) -> String {
+        let height = stacks.iter().map(Vec::len).max().unwrap();
+        let mut lines = (0..height)
+            .rev()
+            .map(|level| {
+                let crates = stacks.iter().map(|stack| match stack.get(level) {
+                    Some(c) => format!("[{c}]"),
+                    None => "   ".to_string(),
+                });
+                crates.collect::<Vec<_>>().join(" ")
+            })
+            .collect::<Vec<_>>();
+        lines.push(
+            (1..=stacks.len())
+                .map(|n| format!(" {n} "))
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        lines.join("\n")
+    }
+
+    // Tops of the stacks after moving the crates one at a time, or with the
+    // crane holding several, as if they went through a stack of its own.
+    fn naive_solution(
+        stacks: &[Vec<char>],
+        moves: &[(usize, usize, usize)],
+        several: bool,
+    ) -> String {
+        let mut stacks = stacks.to_vec();
+        for (n, src, dst) in moves {
+            let mut crane = Vec::new();
+            for _ in 0..*n {
+                let c = stacks[*src].pop().unwrap();
+                match several {
+                    true => crane.push(c),
+                    false => stacks[*dst].push(c),
+                }
+            }
+            while let Some(c) = crane.pop() {
+                stacks[*dst].push(c);
+            }
+        }
+        stacks.iter().filter_map(|stack| stack.last()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_crates_match_naive((stacks, moves) in supplies()) {
+            let moves_text = moves
+                .iter()
+                .map(|(n, src, dst)| format!("move {n} from {} to {}\n", src + 1, dst + 1))
+                .collect::<String>();
+            let input = format!("{}\n\n{moves_text}", drawing(&stacks));
+            let supplies = parse(&input).unwrap();
+            prop_assert_eq!(solution(&supplies, part1), naive_solution(&stacks, &moves, false));
+            prop_assert_eq!(solution(&supplies, part2), naive_solution(&stacks, &moves, true));
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution(part1), "BSDMQFLSP");
-        assert_eq!(solution(part2), "PGSQBFLDP");
+        let supplies = parse(&fs::read_to_string("src/inputs/aoc_5.input").unwrap()).unwrap();
+        assert_answer(5, Part::One, "aoc_5.input", solution(&supplies, part1));
+        assert_answer(5, Part::Two, "aoc_5.input", solution(&supplies, part2));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("[A]\n 1 \n\nmove 1 from 1 to 10").unwrap_err(),
+            ParseError::new(4, 18, "invalid stack '10'")
+        );
+        assert_eq!(
+            parse("[A]\n 1 \n\nmove one from 1 to 2")
+                .unwrap_err()
+                .to_string(),
+            "4:6: expected a number"
+        );
+        assert_eq!(
+            parse("[A]\n 1  2 \n\nmove 1 from 1 to 2\nmove 2 from 2 to 1").unwrap_err(),
+            ParseError::new(5, 6, "can't move 2 crates, stack 2 holds 1")
+        );
+        assert_eq!(
+            parse(" 1 \n\nmove 1 from 1 to 2").unwrap_err(),
+            ParseError::new(1, 1, "missing stacks")
+        );
+        assert_eq!(
+            parse("[A]\n 1 \n").unwrap_err(),
+            ParseError::new(3, 1, "missing moves")
+        );
     }
 }
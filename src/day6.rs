@@ -1,31 +1,119 @@
-use std::{collections::HashSet, fs};
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseError, ParseResult};
+use crate::sample;
+use crate::solver::{PartError, PartResult, Sample, Solver};
+use std::collections::HashSet;
 
-fn is_packet_start(s: &str) -> bool {
-    HashSet::<u8>::from_iter(s.bytes()).len() == s.len()
+fn is_packet_start(s: &[u8]) -> bool {
+    HashSet::<&u8>::from_iter(s).len() == s.len()
 }
 
-fn solution(distinct: usize) -> Option<usize> {
-    let input = fs::read_to_string("src/inputs/aoc_6.input").unwrap();
-    (0..input.len() - distinct)
-        .filter(|i| is_packet_start(input.get(*i..*i + distinct).unwrap()))
-        .map(|i| i + distinct)
+pub fn parse(input: &str) -> ParseResult<String> {
+    let input = &*normalize(input, Normalize::default());
+    let line = lines(input)
         .next()
+        .ok_or_else(|| ParseError::new(1, 1, "empty input"))?;
+    match line.text.find(|c: char| !c.is_ascii_lowercase()) {
+        Some(i) => Err(line.error_at(i, "invalid character, expected a-z")),
+        None if line.text.len() < 4 => Err(line.error_at(
+            line.text.len(),
+            "signal too short, expected at least 4 characters",
+        )),
+        None => Ok(line.text.to_string()),
+    }
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution(4).unwrap().to_string(),
-        solution(14).unwrap().to_string(),
-    )
+pub fn solution(input: &str, distinct: usize) -> Option<usize> {
+    input
+        .as_bytes()
+        .windows(distinct)
+        .position(is_packet_start)
+        .map(|i| i + distinct)
+}
+
+pub struct Day6;
+
+impl Solver for Day6 {
+    type Input = String;
+
+    fn day(&self) -> usize {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Tuning Trouble"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input, _ctx: &Context) -> PartResult {
+        solution(input, 4)
+            .map(|n| n.to_string())
+            .ok_or_else(|| PartError::Unsolvable("no start-of-packet marker".to_string()))
+    }
+
+    fn part2(&self, input: &Self::Input, _ctx: &Context) -> PartResult {
+        solution(input, 14)
+            .map(|n| n.to_string())
+            .ok_or_else(|| PartError::Unsolvable("no start-of-message marker".to_string()))
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_6_sample.input").part1("7").part2("19")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // End of the first `distinct` characters that differ pairwise.
+    fn naive_solution(signal: &[u8], distinct: usize) -> Option<usize> {
+        (distinct..=signal.len()).find(|&end| {
+            (end - distinct..end).all(|i| (i + 1..end).all(|j| signal[i] != signal[j]))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_markers_match_naive(signal in "[a-p]{4,60}") {
+            let parsed = parse(&signal).unwrap();
+            prop_assert_eq!(solution(&parsed, 4), naive_solution(signal.as_bytes(), 4));
+            prop_assert_eq!(solution(&parsed, 14), naive_solution(signal.as_bytes(), 14));
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution(4), Some(1275));
-        assert_eq!(solution(14), Some(3605))
+        let input = parse(&fs::read_to_string("src/inputs/aoc_6.input").unwrap()).unwrap();
+        assert_answer(6, Part::One, "aoc_6.input", solution(&input, 4).unwrap());
+        assert_answer(6, Part::Two, "aoc_6.input", solution(&input, 14).unwrap());
+    }
+
+    #[test]
+    fn test_no_marker() {
+        assert_eq!(
+            parse("abc"),
+            Err(ParseError::new(
+                1,
+                4,
+                "signal too short, expected at least 4 characters"
+            ))
+        );
+        let input = parse("abcd").unwrap();
+        assert_eq!(solution(&input, 4), Some(4));
+        assert_eq!(
+            Day6.part2(&input, &Context::default()),
+            Err(PartError::Unsolvable(
+                "no start-of-message marker".to_string()
+            ))
+        );
+        assert_eq!(solution("abab", 4), None);
     }
 }
@@ -1,5 +1,9 @@
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 use regex::Regex;
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
 
 #[derive(Debug)]
 enum Entry {
@@ -27,7 +31,7 @@ impl Entry {
     }
 }
 
-struct Disk {
+pub struct Disk {
     disk: Vec<Entry>,
 }
 
@@ -96,16 +100,15 @@ impl Disk {
     }
 }
 
-fn solution() -> (usize, usize) {
+pub fn parse(input: &str) -> ParseResult<Disk> {
+    let input = &*normalize(input, Normalize::default());
     let file_re = Regex::new(r"^(?P<file_size>\d*)\s(?P<file_name>.*)$").unwrap();
     let mut disk = Disk::new();
     disk.disk.push(Entry::dir_entry(0, 0));
     let mut current = 0;
 
-    fs::read_to_string("src/inputs/aoc_7.input")
-        .unwrap()
-        .lines()
-        .for_each(|line| match line {
+    for line in lines(input) {
+        match line.text {
             "$ cd /" => {
                 current = 0;
             }
@@ -127,24 +130,31 @@ fn solution() -> (usize, usize) {
                 current = disk.add_directory_entry(current, x.get(5..).unwrap().to_string())
             }
             _ => {
-                let fd = file_re.captures(line).unwrap();
-                let file_size = fd
-                    .name("file_size")
-                    .unwrap()
-                    .as_str()
-                    .parse::<usize>()
-                    .unwrap();
+                let fd = file_re
+                    .captures(line.text)
+                    .ok_or_else(|| line.error(line.text, "expected a command or 'SIZE NAME'"))?;
+                let file_size = line.number(fd.name("file_size").unwrap().as_str())?;
                 let file_name = fd.name("file_name").unwrap().as_str();
                 disk.add_file_entry(current, file_name.to_string(), file_size);
             }
-        });
+        }
+    }
 
+    Ok(disk)
+}
+
+pub fn part1(disk: &Disk) -> usize {
     let mut p1 = 0;
-    let total_size = disk.get_dir_size(0, &mut |dir_size| {
+    disk.get_dir_size(0, &mut |dir_size| {
         if dir_size <= 100000 {
             p1 += dir_size;
         }
     });
+    p1
+}
+
+pub fn part2(disk: &Disk) -> usize {
+    let total_size = disk.get_dir_size(0, &mut |_| {});
 
     let unused_space = 70000000 - total_size;
     let must_free = 30000000 - unused_space;
@@ -154,21 +164,116 @@ fn solution() -> (usize, usize) {
             p2 = dir_size
         }
     });
-
-    (p1, p2)
+    p2
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution();
-    (p1.to_string(), p2.to_string())
+pub struct Day7;
+
+impl Solver for Day7 {
+    type Input = Disk;
+
+    fn day(&self) -> usize {
+        7
+    }
+
+    fn title(&self) -> &'static str {
+        "No Space Left On Device"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, disk: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part1(disk).to_string())
+    }
+
+    fn part2(&self, disk: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part2(disk).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_7_sample.input")
+            .part1("95437")
+            .part2("24933642")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Files as the directories leading to them, their name and size.
+    type Files = Vec<(Vec<String>, String, usize)>;
+
+    fn files() -> impl Strategy<Value = Files> {
+        let dirs = prop::collection::vec("[a-c]", 0..4);
+        let size = prop_oneof![1..200_000usize, 1..25_000_000usize];
+        prop::collection::vec((dirs, "[a-c]\\.txt", size), 1..12)
+    }
+
+    // Terminal output listing every file, going from one directory to the
+    // next with `cd ..` as far as needed.
+    fn session(files: &Files) -> String {
+        let mut out = String::from("$ cd /\n");
+        let mut current: &[String] = &[];
+        for (dirs, name, size) in files {
+            let common = current.iter().zip(dirs).take_while(|(a, b)| a == b).count();
+            for _ in common..current.len() {
+                out += "$ cd ..\n";
+            }
+            for dir in &dirs[common..] {
+                out += &format!("$ cd {dir}\n");
+            }
+            out += &format!("$ ls\n{size} {name}\n");
+            current = dirs;
+        }
+        out
+    }
+
+    // Size of every directory, adding each file to all the ones above it.
+    fn naive_sizes(files: &Files) -> Vec<usize> {
+        let latest = files
+            .iter()
+            .map(|(dirs, name, size)| ((dirs, name), *size))
+            .collect::<HashMap<_, _>>();
+        let mut sizes = HashMap::<&[String], usize>::new();
+        for (dirs, _, _) in files {
+            for depth in 0..=dirs.len() {
+                sizes.entry(&dirs[..depth]).or_default();
+            }
+        }
+        for ((dirs, _), size) in latest {
+            for depth in 0..=dirs.len() {
+                *sizes.get_mut(&dirs[..depth]).unwrap() += size;
+            }
+        }
+        sizes.into_values().collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_sizes_match_naive(files in files()) {
+            let disk = parse(&session(&files)).unwrap();
+            let sizes = naive_sizes(&files);
+            prop_assert_eq!(part1(&disk), sizes.iter().filter(|s| **s <= 100000).sum::<usize>());
+            // Part two needs a disk fuller than it can run the update with.
+            let used = *sizes.iter().max().unwrap();
+            if (40_000_000..=70_000_000).contains(&used) {
+                let smallest = sizes.iter().filter(|s| **s >= used - 40_000_000).min().unwrap();
+                prop_assert_eq!(part2(&disk), *smallest);
+            }
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution(), (1432936, 272298));
+        let disk = parse(&fs::read_to_string("src/inputs/aoc_7.input").unwrap()).unwrap();
+        assert_answer(7, Part::One, "aoc_7.input", part1(&disk));
+        assert_answer(7, Part::Two, "aoc_7.input", part2(&disk));
     }
 }
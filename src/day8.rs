@@ -1,142 +1,184 @@
+use crate::context::Context;
+use crate::grid::Grid;
+use crate::parse::{normalize, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 use std::collections::HashSet;
-use std::fs;
 
-fn get_tree_score(map: &[Vec<u8>], tree: (usize, usize)) -> usize {
-    let mut score: usize = 1;
-    let tree_size = map[tree.0][tree.1];
+// Trees seen from `tree` looking along `line`, up to the first one at least
+// as tall.
+fn viewing_distance<'a>(tree: u8, line: impl Iterator<Item = &'a u8>) -> usize {
     let mut visible = 0;
-
-    for i in tree.1 + 1..map[tree.0].len() {
-        visible += 1;
-        if map[tree.0][i] >= tree_size {
-            break;
-        }
-    }
-    score *= visible;
-
-    visible = 0;
-    for i in (0..tree.1).rev() {
-        visible += 1;
-        if map[tree.0][i] >= tree_size {
-            break;
-        }
-    }
-    score *= visible;
-
-    visible = 0;
-    for ct in map.iter().skip(tree.0 + 1) {
-        visible += 1;
-        if ct[tree.1] >= tree_size {
-            break;
-        }
-    }
-    score *= visible;
-
-    visible = 0;
-    for i in (0..tree.0).rev() {
+    for t in line {
         visible += 1;
-        if map[i][tree.1] >= tree_size {
+        if *t >= tree {
             break;
         }
     }
-    score *= visible;
+    visible
+}
 
-    score
+fn get_tree_score(map: &Grid<u8>, (x, y): (usize, usize)) -> usize {
+    let tree = map[(x, y)];
+    let row = map.row(y);
+    viewing_distance(tree, row[x + 1..].iter())
+        * viewing_distance(tree, row[..x].iter().rev())
+        * viewing_distance(tree, map.column(x).skip(y + 1))
+        * viewing_distance(tree, map.column(x).take(y).rev())
 }
 
-fn get_max_tree_score(map: &Vec<Vec<u8>>) -> usize {
-    (0..map[0].len())
-        .map(|i| {
-            (0..map.len())
-                .map(|j| get_tree_score(map, (i, j)))
-                .max()
-                .unwrap()
-        })
+pub fn get_max_tree_score(map: &Grid<u8>) -> usize {
+    map.positions()
+        .map(|tree| get_tree_score(map, tree))
         .max()
         .unwrap()
 }
 
-fn get_visible_trees(map: &Vec<Vec<u8>>) -> usize {
-    // rows
-    let left_right = map.iter().enumerate().flat_map(|(i, row)| {
-        // left side
-        let left = row
-            .iter()
-            .enumerate()
-            .fold((-1, Vec::new()), |mut acc, (j, t)| {
-                if *t as i32 > acc.0 {
-                    acc.1.push((i, j));
-                    (*t as i32, acc.1)
-                } else {
-                    acc
-                }
-            })
-            .1;
-        // right side
-        row.iter()
-            .rev()
-            .enumerate()
-            .fold((-1, left), |mut acc, (j, t)| {
-                if *t as i32 > acc.0 {
-                    acc.1.push((i, row.len() - j - 1));
-                    (*t as i32, acc.1)
-                } else {
-                    acc
-                }
-            })
-            .1
-    });
-
-    // columns
-    let top_bottom = (0..map[0].len()).flat_map(|i| {
-        // top
-        let top = (0..map.len())
-            .fold((-1, Vec::new()), |mut acc, j| {
-                if map[j][i] as i32 > acc.0 {
-                    acc.1.push((j, i));
-                    (map[j][i] as i32, acc.1)
-                } else {
-                    acc
-                }
-            })
-            .1;
-        // bottom
-        (0..map.len())
-            .rev()
-            .fold((-1, top), |mut acc, j| {
-                if map[j][i] as i32 > acc.0 {
-                    acc.1.push((j, i));
-                    (map[j][i] as i32, acc.1)
-                } else {
-                    acc
-                }
-            })
-            .1
-    });
-    HashSet::<(usize, usize)>::from_iter(left_right.chain(top_bottom)).len()
+// Trees visible from the start of `line`, which holds the positions of a row
+// or a column.
+fn visible_trees<'a>(
+    map: &'a Grid<u8>,
+    line: impl Iterator<Item = (usize, usize)> + 'a,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let mut highest = -1;
+    line.filter(move |tree| {
+        let visible = map[*tree] as i32 > highest;
+        highest = highest.max(map[*tree] as i32);
+        visible
+    })
 }
 
-fn solution(filename: &str) -> (usize, usize) {
-    let map = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .map(|line| line.bytes().map(|t| t - 0x30).collect::<Vec<u8>>())
-        .collect::<Vec<Vec<u8>>>();
-    (get_visible_trees(&map), get_max_tree_score(&map))
+pub fn get_visible_trees(map: &Grid<u8>) -> usize {
+    let rows = (0..map.height()).map(|y| (0..map.width()).map(|x| (x, y)).collect::<Vec<_>>());
+    let columns = (0..map.width()).map(|x| (0..map.height()).map(|y| (x, y)).collect::<Vec<_>>());
+    rows.chain(columns)
+        .flat_map(|line| {
+            visible_trees(map, line.clone().into_iter())
+                .chain(visible_trees(map, line.into_iter().rev()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+pub fn parse(input: &str) -> ParseResult<Grid<u8>> {
+    let input = &*normalize(input, Normalize::default());
+    Grid::parse(input, |t| match t {
+        '0'..='9' => Ok(t as u8 - b'0'),
+        _ => Err("invalid tree height, expected 0-9".to_string()),
+    })
 }
 
-pub fn run() -> (String, String) {
-    let (p1, p2) = solution("src/inputs/aoc_8.input");
-    (p1.to_string(), p2.to_string())
+pub struct Day8;
+
+impl Solver for Day8 {
+    type Input = Grid<u8>;
+
+    fn day(&self) -> usize {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Treetop Tree House"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, map: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(get_visible_trees(map).to_string())
+    }
+
+    fn part2(&self, map: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(get_max_tree_score(map).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![sample!("aoc_8_sample.input").part1("21").part2("8")]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Trees from (x, y) to the edge going by (dx, dy), nearest first.
+    fn naive_line(rows: &[Vec<u8>], (x, y): (usize, usize), (dx, dy): (i32, i32)) -> Vec<u8> {
+        let (mut x, mut y) = (x as i32 + dx, y as i32 + dy);
+        let mut trees = Vec::new();
+        while y >= 0 && y < rows.len() as i32 && x >= 0 && x < rows[0].len() as i32 {
+            trees.push(rows[y as usize][x as usize]);
+            (x, y) = (x + dx, y + dy);
+        }
+        trees
+    }
+
+    // Visible trees and best scenic score, looking every way from every tree.
+    fn naive_solution(rows: &[Vec<u8>]) -> (usize, usize) {
+        let mut visible = 0;
+        let mut best = 0;
+        for y in 0..rows.len() {
+            for x in 0..rows[0].len() {
+                let tree = rows[y][x];
+                let lines = [(1, 0), (-1, 0), (0, 1), (0, -1)].map(|d| naive_line(rows, (x, y), d));
+                if lines.iter().any(|line| line.iter().all(|t| *t < tree)) {
+                    visible += 1;
+                }
+                let score = lines
+                    .iter()
+                    .map(|line| match line.iter().position(|t| *t >= tree) {
+                        Some(i) => i + 1,
+                        None => line.len(),
+                    })
+                    .product();
+                best = best.max(score);
+            }
+        }
+        (visible, best)
+    }
+
+    fn forest() -> impl Strategy<Value = Vec<Vec<u8>>> {
+        (1..8usize, 1..8usize).prop_flat_map(|(width, height)| {
+            prop::collection::vec(prop::collection::vec(0..10u8, width), height)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_trees_match_naive(rows in forest()) {
+            let input = rows
+                .iter()
+                .map(|row| row.iter().map(|t| t.to_string()).collect::<String>() + "\n")
+                .collect::<String>();
+            let map = parse(&input).unwrap();
+            let (visible, best) = naive_solution(&rows);
+            prop_assert_eq!(get_visible_trees(&map), visible);
+            prop_assert_eq!(get_max_tree_score(&map), best);
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_8_sample.input"), (21, 8));
-        assert_eq!(solution("src/inputs/aoc_8.input"), (1538, 496125));
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_8_sample.input").unwrap()).unwrap();
+        let map = parse(&fs::read_to_string("src/inputs/aoc_8.input").unwrap()).unwrap();
+        assert_answer(
+            8,
+            Part::One,
+            "aoc_8_sample.input",
+            get_visible_trees(&sample),
+        );
+        assert_answer(
+            8,
+            Part::Two,
+            "aoc_8_sample.input",
+            get_max_tree_score(&sample),
+        );
+        assert_answer(8, Part::One, "aoc_8.input", get_visible_trees(&map));
+        assert_answer(8, Part::Two, "aoc_8.input", get_max_tree_score(&map));
     }
 }
@@ -1,76 +1,168 @@
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseResult};
+use crate::pos::{Direction, Pos};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
+use crate::trace::Event;
 use std::collections::HashSet;
-use std::fs;
 
-fn get_distance(head: (i32, i32), tail: (i32, i32)) -> (i32, i32) {
-    (head.0 - tail.0, head.1 - tail.1)
+fn update_knots(head: Pos, tail: &mut Pos) {
+    if head.chebyshev(*tail) > 1 {
+        *tail += (head - *tail).signum();
+    }
 }
 
-fn update_knots(head: (i32, i32), tail: &mut (i32, i32)) {
-    let distance = get_distance(head, *tail);
-    let dst_abs = (distance.0.abs(), distance.1.abs());
-    match dst_abs {
-        (0, 2) => tail.1 += distance.1 / 2,
-        (2, 0) => tail.0 += distance.0 / 2,
-        (1, 2) => {
-            tail.0 += distance.0;
-            tail.1 += distance.1 / 2;
-        }
-        (2, 1) => {
-            tail.0 += distance.0 / 2;
-            tail.1 += distance.1;
-        }
-        (2, 2) => {
-            tail.0 += distance.0 / 2;
-            tail.1 += distance.1 / 2;
-        }
-        (0, 1) | (1, 0) | (0, 0) | (1, 1) => {}
-        _ => panic!("err: {distance:?} {head:?} {tail:?}"),
-    }
+#[derive(Debug)]
+pub struct Move {
+    direction: Direction,
+    steps: i32,
+}
+
+pub fn parse(input: &str) -> ParseResult<Vec<Move>> {
+    let input = &*normalize(input, Normalize::default());
+    lines(input)
+        .map(|line| match line.text.split_once(' ') {
+            Some((direction @ ("R" | "L" | "U" | "D"), steps)) => Ok(Move {
+                direction: Direction::try_from(direction.as_bytes()[0] as char)
+                    .map_err(|e| line.error(direction, e))?,
+                steps: line.number(steps)?,
+            }),
+            _ => Err(line.error(line.text, "expected a direction (R, L, U, D) and steps")),
+        })
+        .collect()
 }
 
-fn solution(filename: &str, n: usize) -> usize {
-    let mut knots = vec![(0, 0); n + 1];
-    let visited = fs::read_to_string(filename)
-        .unwrap()
-        .lines()
-        .flat_map(|line| {
-            let mv = line[2..].parse::<i32>().unwrap();
-            (0..mv)
+// Traces every step of the head, with where it and the tail end up.
+pub fn solution(moves: &[Move], n: usize, ctx: &Context) -> usize {
+    let mut knots = vec![Pos::default(); n + 1];
+    let visited = moves
+        .iter()
+        .flat_map(|mv| {
+            (0..mv.steps)
                 .map(|_| {
-                    match line.as_bytes().first().unwrap() {
-                        b'R' => knots[0].0 += 1,
-                        b'L' => knots[0].0 -= 1,
-                        b'U' => knots[0].1 += 1,
-                        b'D' => knots[0].1 -= 1,
-                        _ => {}
-                    }
+                    knots[0] += mv.direction;
                     (0..knots.len() - 1).for_each(|i| {
                         update_knots(knots[i], &mut knots[i + 1]);
                     });
+                    ctx.trace(|| {
+                        Event::new("step")
+                            .with("direction", char::from(mv.direction))
+                            .with("head_x", knots[0].x)
+                            .with("head_y", knots[0].y)
+                            .with("tail_x", knots[n].x)
+                            .with("tail_y", knots[n].y)
+                    });
                     knots[n]
                 })
-                .collect::<HashSet<(i32, i32)>>()
+                .collect::<HashSet<Pos>>()
         })
-        .collect::<HashSet<(i32, i32)>>();
+        .collect::<HashSet<Pos>>();
     visited.len()
 }
 
-pub fn run() -> (String, String) {
-    (
-        solution("src/inputs/aoc_9.input", 1).to_string(),
-        solution("src/inputs/aoc_9.input", 9).to_string(),
-    )
+pub struct Day9;
+
+impl Solver for Day9 {
+    type Input = Vec<Move>;
+
+    fn day(&self) -> usize {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Rope Bridge"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, moves: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(moves, 1, ctx).to_string())
+    }
+
+    fn part2(&self, moves: &Self::Input, ctx: &Context) -> PartResult {
+        Ok(solution(moves, 9, ctx).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        vec![
+            sample!("aoc_9_sample.input").part1("13").part2("1"),
+            sample!("aoc_9_sample2.input").part2("36"),
+        ]
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::answers::assert_answer;
+    use crate::cli::Part;
+    use proptest::prelude::*;
+    use std::fs;
+
+    // Moves the knots as (x, y) pairs, each one catching up with the one
+    // before it by at most a step along both axes.
+    fn naive_solution(moves: &[(char, i32)], knots: usize) -> usize {
+        let mut rope = vec![(0i32, 0i32); knots + 1];
+        let mut visited = HashSet::from([(0, 0)]);
+        for (direction, steps) in moves {
+            for _ in 0..*steps {
+                match direction {
+                    'R' => rope[0].0 += 1,
+                    'L' => rope[0].0 -= 1,
+                    'U' => rope[0].1 += 1,
+                    _ => rope[0].1 -= 1,
+                }
+                for i in 1..rope.len() {
+                    let (dx, dy) = (rope[i - 1].0 - rope[i].0, rope[i - 1].1 - rope[i].1);
+                    if dx.abs() > 1 || dy.abs() > 1 {
+                        rope[i].0 += dx.clamp(-1, 1);
+                        rope[i].1 += dy.clamp(-1, 1);
+                    }
+                }
+                visited.insert(rope[knots]);
+            }
+        }
+        visited.len()
+    }
+
+    fn moves() -> impl Strategy<Value = Vec<(char, i32)>> {
+        let direction = prop::sample::select(vec!['R', 'L', 'U', 'D']);
+        prop::collection::vec((direction, 1..6), 1..30)
+    }
+
+    proptest! {
+        #[test]
+        fn test_rope_matches_naive(moves in moves()) {
+            let input = moves.iter().map(|(d, n)| format!("{d} {n}\n")).collect::<String>();
+            let parsed = parse(&input).unwrap();
+            let ctx = Context::default();
+            prop_assert_eq!(solution(&parsed, 1, &ctx), naive_solution(&moves, 1));
+            prop_assert_eq!(solution(&parsed, 9, &ctx), naive_solution(&moves, 9));
+        }
+    }
 
     #[test]
     fn test_run() {
-        assert_eq!(solution("src/inputs/aoc_9_sample.input", 1), 13);
-        assert_eq!(solution("src/inputs/aoc_9_sample2.input", 9), 36);
-        assert_eq!(solution("src/inputs/aoc_9.input", 1), 6339);
-        assert_eq!(solution("src/inputs/aoc_9.input", 9), 2541);
+        let ctx = Context::default();
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_9_sample.input").unwrap()).unwrap();
+        let sample2 =
+            parse(&fs::read_to_string("src/inputs/aoc_9_sample2.input").unwrap()).unwrap();
+        let input = parse(&fs::read_to_string("src/inputs/aoc_9.input").unwrap()).unwrap();
+        assert_answer(
+            9,
+            Part::One,
+            "aoc_9_sample.input",
+            solution(&sample, 1, &ctx),
+        );
+        assert_answer(
+            9,
+            Part::Two,
+            "aoc_9_sample2.input",
+            solution(&sample2, 9, &ctx),
+        );
+        assert_answer(9, Part::One, "aoc_9.input", solution(&input, 1, &ctx));
+        assert_answer(9, Part::Two, "aoc_9.input", solution(&input, 9, &ctx));
     }
 }
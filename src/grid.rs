@@ -0,0 +1,334 @@
+use crate::parse::{lines, ParseError, ParseResult};
+use crate::pos::Pos;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// Rectangular 2D map stored row by row. Cells are addressed by `(x, y)`,
+/// `x` being the column and `y` the row, both counted from the top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+const NEIGHBOURS4: [(i64, i64); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+const NEIGHBOURS8: [(i64, i64); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+];
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Grid made of `rows`, which must all be as wide.
+    pub fn from_rows(rows: impl IntoIterator<Item = Vec<T>>) -> Self {
+        let mut rows = rows.into_iter().peekable();
+        let width = rows.peek().map_or(0, Vec::len);
+        let mut grid = Grid {
+            cells: Vec::new(),
+            width,
+            height: 0,
+        };
+        rows.for_each(|row| grid.push_row(row));
+        grid
+    }
+
+    /// Parses a map with a cell per char, mapped by `cell`. All the lines must
+    /// be as wide as the first one.
+    pub fn parse(s: &str, mut cell: impl FnMut(char) -> Result<T, String>) -> ParseResult<Self> {
+        let width = lines(s).next().map_or(0, |line| line.text.chars().count());
+        if width == 0 {
+            return Err(ParseError::new(1, 1, "empty map"));
+        }
+        let mut grid = Grid {
+            cells: Vec::new(),
+            width,
+            height: 0,
+        };
+        for line in lines(s) {
+            if line.text.chars().count() != width {
+                return Err(line.error(line.text, format!("expected {width} tiles")));
+            }
+            for (i, c) in line.text.char_indices() {
+                grid.cells.push(cell(c).map_err(|e| line.error_at(i, e))?);
+            }
+            grid.height += 1;
+        }
+        Ok(grid)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.1 * self.width + pos.0])
+    }
+
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        match self.contains(pos) {
+            true => Some(&mut self.cells[pos.1 * self.width + pos.0]),
+            false => None,
+        }
+    }
+
+    /// Cell at `pos`, or `None` when it's outside of the grid.
+    pub fn get_pos(&self, pos: Pos) -> Option<&T> {
+        self.get(pos.try_into().ok()?)
+    }
+
+    /// Cell at `pos` wrapped around the edges of the grid.
+    pub fn get_wrapping(&self, pos: Pos) -> &T {
+        &self[Pos::default().wrapping_add(pos, self.width, self.height)]
+    }
+
+    // Positions at the `offsets` from `pos` that are inside the grid.
+    fn offsets(
+        &self,
+        (x, y): (usize, usize),
+        offsets: &'static [(i64, i64)],
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        offsets.iter().filter_map(move |(dx, dy)| {
+            let pos = (
+                usize::try_from(x as i64 + dx).ok()?,
+                usize::try_from(y as i64 + dy).ok()?,
+            );
+            self.contains(pos).then_some(pos)
+        })
+    }
+
+    /// Positions above, right, below and left of `pos`, within the grid.
+    pub fn neighbours4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(pos, &NEIGHBOURS4)
+    }
+
+    /// Positions around `pos`, diagonals included, within the grid.
+    pub fn neighbours8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets(pos, &NEIGHBOURS8)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(x < self.width, "column {x} out of the grid");
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    /// All the positions, row by row.
+    pub fn positions(&self) -> impl DoubleEndedIterator<Item = (usize, usize)> + ExactSizeIterator {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| (i % width, i / width))
+    }
+
+    /// All the cells with their positions, row by row.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = ((usize, usize), &T)> + ExactSizeIterator {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    /// Position of the first cell matching `f`, row by row.
+    pub fn position(&self, mut f: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter().find(|(_, t)| f(t)).map(|(pos, _)| pos)
+    }
+
+    /// Position of the last cell matching `f`, row by row.
+    pub fn rposition(&self, mut f: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.iter().rev().find(|(_, t)| f(t)).map(|(pos, _)| pos)
+    }
+
+    /// Grows the grid by a row at the bottom.
+    pub fn push_row(&mut self, row: Vec<T>) {
+        assert_eq!(row.len(), self.width, "row of a different width");
+        self.cells.extend(row);
+        self.height += 1;
+    }
+
+    /// Grid of the same size with every cell mapped by `f`.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Copy of the grid surrounded by `x` columns of `fill` on the left and
+    /// right, and `y` rows on the top and bottom.
+    pub fn padded(&self, x: usize, y: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        let mut grid = Grid::new(self.width + 2 * x, self.height + 2 * y, fill);
+        self.iter()
+            .for_each(|((cx, cy), t)| grid[(cx + x, cy + y)] = t.clone());
+        grid
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{pos:?} out of the {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{pos:?} out of the {width}x{height} grid"))
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get_pos(pos)
+            .unwrap_or_else(|| panic!("{pos:?} out of the {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        pos.try_into()
+            .ok()
+            .and_then(|pos| self.get_mut(pos))
+            .unwrap_or_else(|| panic!("{pos:?} out of the {width}x{height} grid"))
+    }
+}
+
+/// Rows on separate lines, without a line break after the last one.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            row.iter().try_for_each(|t| write!(f, "{t}"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(s: &str) -> ParseResult<Grid<u32>> {
+        Grid::parse(s, |c| c.to_digit(10).ok_or(format!("invalid digit '{c}'")))
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\n456\n").unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid, Grid::from_rows([vec![1, 2, 3], vec![4, 5, 6]]));
+        assert_eq!(digits(""), Err(ParseError::new(1, 1, "empty map")));
+        assert_eq!(
+            digits("123\n45"),
+            Err(ParseError::new(2, 1, "expected 3 tiles"))
+        );
+        assert_eq!(
+            digits("123\n4x6"),
+            Err(ParseError::new(2, 2, "invalid digit 'x'"))
+        );
+    }
+
+    #[test]
+    fn test_access() {
+        let mut grid = digits("123\n456").unwrap();
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(grid[Pos::new(1, 0)], 2);
+        assert_eq!(grid.get_pos(Pos::new(-1, 0)), None);
+        assert_eq!(grid.get_wrapping(Pos::new(-1, -1)), &6);
+        assert_eq!(grid.get_wrapping(Pos::new(3, 4)), &1);
+        grid[(0, 1)] = 7;
+        assert_eq!(grid.row(1), [7, 5, 6]);
+        assert_eq!(grid.position(|t| *t > 4), Some((0, 1)));
+        assert_eq!(grid.rposition(|t| *t < 3), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbours4((0, 1)).collect::<Vec<_>>(),
+            [(0, 0), (1, 1), (0, 2)]
+        );
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+        assert_eq!(
+            grid.neighbours8((2, 2)).collect::<Vec<_>>(),
+            [(1, 1), (2, 1), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_rows_columns() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(grid.rows().next_back(), Some(&[4, 5, 6][..]));
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(grid.column(2).rev().copied().collect::<Vec<_>>(), [6, 3]);
+        assert_eq!(grid.positions().nth(4), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_grow() {
+        let mut grid = Grid::new(2, 0, '.');
+        grid.push_row(vec!['#', '.']);
+        assert_eq!(grid.height(), 1);
+        assert_eq!(grid.padded(1, 1, ' ').to_string(), "    \n #. \n    ");
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = digits("12\n34").unwrap().map(|d| d * 2);
+        assert_eq!(grid, Grid::from_rows([vec![2, 4], vec![6, 8]]));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(digits("12\n34").unwrap().to_string(), "12\n34");
+    }
+}
@@ -0,0 +1,48 @@
+//! Advent of Code 2022 solutions.
+//!
+//! Every day lives in its own `dayN` module with a `parse` function turning
+//! the puzzle input into the day's model, the functions solving both parts,
+//! and a `DayN` type implementing [`solver::Solver`] on top of them.
+
+pub mod alloc;
+pub mod answers;
+pub mod bench;
+pub mod cli;
+pub mod client;
+pub mod context;
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod grid;
+pub mod parse;
+pub mod pos;
+pub mod report;
+pub mod runner;
+pub mod scaffold;
+pub mod search;
+pub mod solver;
+pub mod submit;
+pub mod trace;
+pub mod visualize;
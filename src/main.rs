@@ -1,81 +1,228 @@
-use rayon::prelude::*;
+use aoc2022::alloc::{self, Counting};
+use aoc2022::answers::{self, Answers};
+use aoc2022::cli::{self, Args, Command, Format, Visualize};
+use aoc2022::client::{self, Client, Fetched};
+use aoc2022::context::Context;
+use aoc2022::report::{self, Status};
+use aoc2022::runner::{self, bench, solve};
+use aoc2022::scaffold;
+use aoc2022::solver::{self, DynSolver};
+use aoc2022::submit::{self, Submissions, Verdict};
+use aoc2022::trace::Trace;
+use aoc2022::visualize::Visualizer;
 use std::env;
-use std::time::Instant;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+fn client(args: &Args) -> Result<Client, String> {
+    let session = client::session_token(
+        env::var(client::SESSION_VAR).ok(),
+        args.session.as_deref().unwrap_or(client::SESSION_FILE),
+    )?;
+    Ok(Client::new(
+        args.base_url.as_deref().unwrap_or(client::BASE_URL),
+        &session,
+    ))
+}
+
+fn fetch(args: &Args) -> Result<(), String> {
+    let client = client(args)?;
+    for day in &args.days {
+        let path = runner::input_path(*day);
+        match client::fetch(&client, *day, &path)? {
+            Fetched::Cached => println!("day{day}: {path} is already there"),
+            Fetched::Downloaded => println!("day{day}: downloaded {path}"),
+        }
+    }
+    Ok(())
+}
+
+// Returns whether the answer was accepted.
+fn submit(args: &Args) -> Result<bool, String> {
+    let day = args.days[0];
+    let part = args.part.expect("submit needs a part");
+    let run = solve(args, solver::registry()[&day].as_ref(), &Context::default());
+    let record = &run.records[0];
+    let answer = record
+        .answer
+        .as_ref()
+        .ok_or_else(|| format!("day{day}: {}", record.error.clone().unwrap_or_default()))?;
+    let client = client(args)?;
+    let mut submissions = Submissions::load(submit::SUBMISSIONS_FILE)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock before 1970")
+        .as_secs();
+    let verdict = submit::submit(&client, &mut submissions, day, part, answer, now)?;
+    println!("day{day} p{}: {answer} is {verdict}", part.number());
+    Ok(matches!(verdict, Verdict::Correct | Verdict::AlreadySolved))
+}
+
+// Only counts with `--memory`, the allocations just go to the system
+// allocator otherwise.
+#[global_allocator]
+static ALLOC: Counting = Counting;
+
+// The slowest days are started first, so they don't end up being the tail of
+// the parallel run.
+const SLOW_DAYS: [usize; 3] = [19, 16, 17];
+
+// How often `--watch` looks at the input files.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
 fn main() {
-    let days = [
-        (19, day19::run as fn() -> (String, String)),
-        (16, day16::run),
-        (17, day17::run),
-        (1, day1::run),
-        (2, day2::run),
-        (3, day3::run),
-        (4, day4::run),
-        (5, day5::run),
-        (6, day6::run),
-        (7, day7::run),
-        (8, day8::run),
-        (9, day9::run),
-        (10, day10::run),
-        (11, day11::run),
-        (12, day12::run),
-        (13, day13::run),
-        (14, day14::run),
-        (15, day15::run),
-        (18, day18::run),
-        (20, day20::run),
-        (21, day21::run),
-        (22, day22::run),
-        (23, day23::run),
-        (24, day24::run),
-        (25, day25::run),
-    ];
+    let args = match cli::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(if e == cli::CliError::Help { 0 } else { 2 });
+        }
+    };
+    match args.command {
+        Command::Fetch => {
+            if let Err(e) = fetch(&args) {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+            return;
+        }
+        Command::Submit => match submit(&args) {
+            Ok(accepted) => process::exit(!accepted as i32),
+            Err(e) => {
+                eprintln!("{e}");
+                process::exit(1);
+            }
+        },
+        Command::Scaffold => {
+            let day = args.days[0];
+            match scaffold::scaffold(Path::new(args.root.as_deref().unwrap_or(".")), day) {
+                Ok(written) => written
+                    .iter()
+                    .for_each(|path| println!("day{day}: wrote {}", path.display())),
+                Err(e) => {
+                    eprintln!("{e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Command::Solve => {}
+    }
+
+    let answers = args
+        .verify
+        .then(|| Answers::load(answers::ANSWERS_FILE))
+        .transpose()
+        .unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(2);
+        });
+    runner::init_pool(&args);
+    if args.memory {
+        alloc::enable();
+    }
     let now = Instant::now();
-    let day = env::args()
-        .nth(1)
-        .unwrap_or("0".to_string())
-        .parse::<usize>()
-        .unwrap_or(0);
-    match day {
-        1..=25 => {
-            let (p1, p2) = days[day - 1].1();
-            println!("day{day} p1: {p1}\nday{day} p2: {p2}");
+    let mut registry = solver::registry();
+    if let Some(runs) = args.bench {
+        // Days are measured one at a time, so they don't compete for the
+        // rayon pool.
+        let mut failed = false;
+        for day in &args.days {
+            match bench(&args, registry[day].as_ref(), runs) {
+                Ok(report) => print!("{report}"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    failed = true;
+                }
+            }
+        }
+        println!("total execution time: {:?}", now.elapsed());
+        process::exit(failed as i32);
+    }
+    let mut selected = args
+        .days
+        .iter()
+        .filter_map(|day| registry.remove(day))
+        .collect::<Vec<Box<dyn DynSolver>>>();
+    selected.sort_by_key(|solver| !SLOW_DAYS.contains(&solver.day()));
+    let timed = selected.len() > 1;
+    // The first Ctrl-C lets the days stop cleanly, the second one doesn't wait.
+    let ctx = Context::default().child(|_| {});
+    let interrupt = ctx.clone();
+    ctrlc::set_handler(move || match interrupt.is_cancelled() {
+        true => process::exit(130),
+        false => interrupt.cancel(),
+    })
+    .expect("can't handle Ctrl-C");
+    let ctx = match &args.trace {
+        Some(path) => match Trace::create(path) {
+            Ok(trace) => ctx.traced(Arc::new(trace)),
+            Err(e) => {
+                eprintln!("can't create {path}: {e}");
+                process::exit(2);
+            }
+        },
+        None => ctx,
+    };
+    let ctx = match &args.visualize {
+        Some(visualize) => match Visualizer::create(visualize) {
+            Ok(visualizer) => ctx.visualized(Arc::new(visualizer)),
+            Err(e) => {
+                eprintln!("can't visualize: {e}");
+                process::exit(2);
+            }
+        },
+        None => ctx,
+    };
+    // The frames shown on the terminal are drawn where the progress would be.
+    let progress =
+        io::stderr().is_terminal() && !matches!(args.visualize, Some(Visualize::Terminal { .. }));
+    if args.watch {
+        runner::watch(
+            &args,
+            |day| solver::registry().remove(&day).expect("unknown day"),
+            &ctx,
+            WATCH_INTERVAL,
+            progress,
+            |run, previous| {
+                if let Some(answers) = &answers {
+                    answers.verify(run);
+                }
+                print!("{}", report::text(run, true));
+                if let Some(previous) = previous {
+                    print!("{}", report::diff(previous, run));
+                }
+            },
+        );
+        process::exit(130);
+    }
+    let mut records = Vec::new();
+    // Errors, wrong answers and timed out days fail the run, missing answers
+    // don't.
+    let mut failed = false;
+    runner::solve_all(&args, selected, &ctx, progress, |mut run| {
+        if let Some(answers) = &answers {
+            answers.verify(&mut run);
+        }
+        failed |= run
+            .records
+            .iter()
+            .any(|r| matches!(r.status, Status::Error | Status::Fail | Status::Timeout));
+        match args.format {
+            Format::Text => print!("{}", report::text(&run, timed)),
+            _ => records.extend(run.records),
         }
-        _ => days.par_iter().for_each(|day| {
-            let now = Instant::now();
-            let (p1, p2) = day.1();
-            println!(
-                "day{day_n} p1: {p1}\nday{day_n} p2: {p2}\nday{day_n} execution time: {:?}",
-                now.elapsed(),
-                day_n = day.0
-            );
-        }),
+    });
+    match args.format {
+        Format::Text => println!("total execution time: {:?}", now.elapsed()),
+        Format::Json => print!("{}", report::json(&records)),
+        Format::Csv => print!("{}", report::csv(&records)),
     }
-    println!("total execution time: {:?}", now.elapsed());
+    process::exit(match ctx.is_cancelled() {
+        true => 130,
+        false => failed as i32,
+    });
 }
@@ -0,0 +1,359 @@
+use std::error::Error;
+use std::fmt;
+use std::iter;
+use std::str::FromStr;
+
+/// Error returned by the day parsers, pointing at the offending spot of the
+/// puzzle input. Lines and columns are counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            file: String::new(),
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    pub fn in_file(self, file: &str) -> Self {
+        ParseError {
+            file: file.to_string(),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.file.is_empty() {
+            write!(f, "{}:", self.file)?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Single line of the puzzle input together with its line number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Error at the byte `index` of the line.
+    pub fn error_at(&self, index: usize, message: impl Into<String>) -> ParseError {
+        let column = self.text.get(..index).map_or(index, |s| s.chars().count()) + 1;
+        ParseError::new(self.number, column, message)
+    }
+
+    /// Error pointing at `token`, which should be a slice of the line. Tokens
+    /// that don't come from the line are reported at its first column.
+    pub fn error(&self, token: &str, message: impl Into<String>) -> ParseError {
+        let start = self.text.as_ptr() as usize;
+        let index = (token.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|index| *index <= self.text.len())
+            .unwrap_or(0);
+        self.error_at(index, message)
+    }
+
+    pub fn number<T>(&self, token: &str) -> ParseResult<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        token
+            .parse()
+            .map_err(|e| self.error(token, format!("invalid number '{token}': {e}")))
+    }
+
+    pub fn scanner(&self) -> Scanner<'a> {
+        Scanner {
+            line: *self,
+            rest: self.text,
+        }
+    }
+}
+
+/// How `normalize` cleans up an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Normalize {
+    /// Whether the whitespace ending the lines is dropped. The whitespace
+    /// starting them is always kept, as the columns of some inputs depend on
+    /// it.
+    pub trim: bool,
+}
+
+impl Default for Normalize {
+    fn default() -> Self {
+        Normalize { trim: true }
+    }
+}
+
+/// `input` without a byte order mark and with LF line endings, so a parser
+/// reads the same lines whatever the file was saved with.
+pub fn normalize(input: &str, options: Normalize) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut out = String::with_capacity(input.len());
+    for line in input.split_inclusive('\n') {
+        let (text, newline) = match line.strip_suffix('\n') {
+            Some(text) => (text.strip_suffix('\r').unwrap_or(text), "\n"),
+            None => (line, ""),
+        };
+        out.push_str(match options.trim {
+            true => text.trim_end(),
+            false => text,
+        });
+        out.push_str(newline);
+    }
+    out
+}
+
+pub fn lines(input: &str) -> impl Iterator<Item = Line<'_>> {
+    input.lines().enumerate().map(|(i, text)| Line {
+        number: i + 1,
+        text,
+    })
+}
+
+/// Groups of consecutive lines, separated by one or more blank lines.
+pub fn blocks(input: &str) -> impl Iterator<Item = Vec<Line<'_>>> {
+    let blank = |line: &Line| line.text.trim().is_empty();
+    let mut lines = lines(input).peekable();
+    iter::from_fn(move || {
+        while lines.next_if(blank).is_some() {}
+        let block = iter::from_fn(|| lines.next_if(|line| !blank(line))).collect::<Vec<_>>();
+        (!block.is_empty()).then_some(block)
+    })
+}
+
+/// Reads the fields of a line from left to right. Errors point at the column
+/// the scanner got to. It's `Copy`, so a copy remembers a position to report
+/// errors at later on.
+#[derive(Debug, Clone, Copy)]
+pub struct Scanner<'a> {
+    line: Line<'a>,
+    rest: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.line.error(self.rest, message)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    /// Skips the text `tag` if the line continues with it.
+    pub fn try_tag(&mut self, tag: &str) -> bool {
+        match self.rest.strip_prefix(tag) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn tag(&mut self, tag: &str) -> ParseResult<()> {
+        match self.try_tag(tag) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected '{tag}'"))),
+        }
+    }
+
+    pub fn whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    /// Letters, digits and underscores, like the names of valves or monkeys.
+    pub fn word(&mut self) -> ParseResult<&'a str> {
+        let len = self
+            .rest
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(self.rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        let (word, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(word)
+    }
+
+    /// Decimal integer with an optional sign.
+    pub fn integer<T>(&mut self) -> ParseResult<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let sign = usize::from(self.rest.starts_with(['-', '+']));
+        let digits = self.rest[sign..]
+            .bytes()
+            .take_while(u8::is_ascii_digit)
+            .count();
+        if digits == 0 {
+            return Err(self.error("expected a number"));
+        }
+        let (token, rest) = self.rest.split_at(sign + digits);
+        let n = self.line.number(token)?;
+        self.rest = rest;
+        Ok(n)
+    }
+
+    /// Integer following the text `prefix`, like `x=` in `x=-3`.
+    pub fn field<T>(&mut self, prefix: &str) -> ParseResult<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.tag(prefix)?;
+        self.integer()
+    }
+
+    /// Value of the first of the `options` the line continues with.
+    pub fn one_of<T: Copy>(&mut self, options: &[(&str, T)]) -> ParseResult<T> {
+        match options.iter().find(|(tag, _)| self.try_tag(tag)) {
+            Some((_, value)) => Ok(*value),
+            None => {
+                let tags = options
+                    .iter()
+                    .map(|(tag, _)| format!("'{tag}'"))
+                    .collect::<Vec<_>>();
+                Err(self.error(format!("expected {}", tags.join(" or "))))
+            }
+        }
+    }
+
+    /// One or more items read by `item`, separated by `separator`.
+    pub fn list<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+    ) -> ParseResult<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.try_tag(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Checks that the whole line was read.
+    pub fn end(&self) -> ParseResult<()> {
+        match self.rest.is_empty() {
+            true => Ok(()),
+            false => Err(self.error(format!("unexpected '{}'", self.rest))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_errors() {
+        let line = lines("noop\naddx 1x").nth(1).unwrap();
+        assert_eq!(line.number, 2);
+        assert_eq!(
+            line.number::<i32>(&line.text[5..]),
+            Err(line.error_at(5, "invalid number '1x': invalid digit found in string"))
+        );
+        assert_eq!(line.error(&line.text[5..], "x").column, 6);
+        assert_eq!(line.error("elsewhere", "x").column, 1);
+        assert_eq!(line.number::<i32>(&line.text[5..6]), Ok(1));
+    }
+
+    #[test]
+    fn test_scanner() {
+        let line = lines("Sensor at x=-2, y=18: beacons AB, CD")
+            .next()
+            .unwrap();
+        let mut s = line.scanner();
+        assert_eq!(s.field::<i32>("Sensor at x="), Ok(-2));
+        assert_eq!(s.field::<i32>(", y="), Ok(18));
+        assert_eq!(s.one_of(&[(": beacon ", 1), (": beacons ", 2)]), Ok(2));
+        assert_eq!(s.list(", ", Scanner::word), Ok(vec!["AB", "CD"]));
+        assert_eq!(s.end(), Ok(()));
+
+        let line = lines("move x to 300").next().unwrap();
+        let mut s = line.scanner();
+        assert_eq!(s.tag("mov"), Ok(()));
+        assert_eq!(s.tag(" "), Err(ParseError::new(1, 4, "expected ' '")));
+        assert_eq!(
+            s.end(),
+            Err(ParseError::new(1, 4, "unexpected 'e x to 300'"))
+        );
+        s.tag("e ").unwrap();
+        assert_eq!(
+            s.integer::<i32>(),
+            Err(ParseError::new(1, 6, "expected a number"))
+        );
+        assert_eq!(s.word(), Ok("x"));
+        assert_eq!(
+            s.one_of(&[("to", ()), ("from", ())]),
+            Err(ParseError::new(1, 7, "expected 'to' or 'from'"))
+        );
+        s.whitespace();
+        s.tag("to ").unwrap();
+        assert_eq!(
+            s.integer::<u8>(),
+            Err(ParseError::new(
+                1,
+                11,
+                "invalid number '300': number too large to fit in target type"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let blocks = blocks("a\nb\n\n\n c\n\n").collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(
+            blocks[0].iter().map(|l| l.text).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(blocks[1][0].number, 5);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let input = "\u{feff}    [D]    \r\n[N] [C]\t\r\n\r\nmove 1 from 2 to 1 ";
+        assert_eq!(
+            normalize(input, Normalize::default()),
+            "    [D]\n[N] [C]\n\nmove 1 from 2 to 1"
+        );
+        assert_eq!(
+            normalize(input, Normalize { trim: false }),
+            "    [D]    \n[N] [C]\t\n\nmove 1 from 2 to 1 "
+        );
+        assert_eq!(normalize("a\n\n", Normalize::default()), "a\n\n");
+    }
+
+    #[test]
+    fn test_display() {
+        let e = ParseError::new(3, 7, "unknown valve 'ZZ'");
+        assert_eq!(e.to_string(), "3:7: unknown valve 'ZZ'");
+        assert_eq!(
+            e.in_file("src/inputs/aoc_16.input").to_string(),
+            "src/inputs/aoc_16.input:3:7: unknown valve 'ZZ'"
+        );
+    }
+}
@@ -0,0 +1,249 @@
+use derive_more::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::num::TryFromIntError;
+use std::ops;
+
+/// Point, or vector between two points, on a 2D map. `x` grows to the right
+/// and `y` downwards, like the rows of a puzzle input.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Add,
+    AddAssign,
+    Sub,
+    SubAssign,
+    Neg,
+    Mul,
+)]
+pub struct Pos {
+    pub x: i64,
+    pub y: i64,
+}
+
+const DIAGONALS: [Pos; 4] = [
+    Pos::new(-1, -1),
+    Pos::new(1, -1),
+    Pos::new(1, 1),
+    Pos::new(-1, 1),
+];
+
+impl Pos {
+    pub const fn new(x: i64, y: i64) -> Self {
+        Pos { x, y }
+    }
+
+    pub fn manhattan(self, other: Pos) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(self, other: Pos) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// Vector with both coordinates reduced to -1, 0 or 1.
+    pub fn signum(self) -> Pos {
+        Pos::new(self.x.signum(), self.y.signum())
+    }
+
+    /// `self + other`, unless that falls left of or above the origin.
+    pub fn checked_add(self, other: Pos) -> Option<Pos> {
+        let pos = self + other;
+        (pos.x >= 0 && pos.y >= 0).then_some(pos)
+    }
+
+    /// `self + other`, wrapped around the edges of a `width` by `height` map.
+    pub fn wrapping_add(self, other: Pos, width: usize, height: usize) -> Pos {
+        let pos = self + other;
+        Pos::new(
+            pos.x.rem_euclid(width as i64),
+            pos.y.rem_euclid(height as i64),
+        )
+    }
+
+    /// Positions above, right, below and left of this one.
+    pub fn neighbours4(self) -> impl Iterator<Item = Pos> {
+        Direction::all().map(move |d| self + d)
+    }
+
+    /// Positions around this one, diagonals included.
+    pub fn neighbours8(self) -> impl Iterator<Item = Pos> {
+        self.neighbours4()
+            .chain(DIAGONALS.into_iter().map(move |d| self + d))
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((x, y): (usize, usize)) -> Self {
+        Pos::new(x as i64, y as i64)
+    }
+}
+
+impl TryFrom<Pos> for (usize, usize) {
+    type Error = TryFromIntError;
+
+    fn try_from(pos: Pos) -> Result<Self, Self::Error> {
+        Ok((usize::try_from(pos.x)?, usize::try_from(pos.y)?))
+    }
+}
+
+impl ops::Add<Direction> for Pos {
+    type Output = Pos;
+
+    fn add(self, d: Direction) -> Pos {
+        self + Pos::from(d)
+    }
+}
+
+impl ops::AddAssign<Direction> for Pos {
+    fn add_assign(&mut self, d: Direction) {
+        *self = *self + d;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotate {
+    Left,
+    Right,
+}
+
+impl TryFrom<char> for Rotate {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'L' => Ok(Rotate::Left),
+            'R' => Ok(Rotate::Right),
+            _ => Err(format!("invalid rotation '{c}', expected L or R")),
+        }
+    }
+}
+
+/// Direction on the map, the variants going clockwise from `Up`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn all() -> impl Iterator<Item = Direction> {
+        Direction::ALL.into_iter()
+    }
+
+    pub fn rotate(self, r: Rotate) -> Self {
+        let turns = match r {
+            Rotate::Right => 1,
+            Rotate::Left => 3,
+        };
+        Direction::ALL[(self as usize + turns) % 4]
+    }
+
+    pub fn opposite(self) -> Self {
+        Direction::ALL[(self as usize + 2) % 4]
+    }
+}
+
+impl From<Direction> for Pos {
+    fn from(d: Direction) -> Self {
+        match d {
+            Direction::Up => Pos::new(0, -1),
+            Direction::Right => Pos::new(1, 0),
+            Direction::Down => Pos::new(0, 1),
+            Direction::Left => Pos::new(-1, 0),
+        }
+    }
+}
+
+impl From<Direction> for char {
+    fn from(d: Direction) -> char {
+        match d {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+/// Arrows, or the initials of the directions.
+impl TryFrom<char> for Direction {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '^' | 'U' => Ok(Direction::Up),
+            '>' | 'R' => Ok(Direction::Right),
+            'v' | 'D' => Ok(Direction::Down),
+            '<' | 'L' => Ok(Direction::Left),
+            _ => Err(format!("invalid direction '{c}'")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let p = Pos::new(2, 3);
+        assert_eq!(p + Pos::new(-3, 1), Pos::new(-1, 4));
+        assert_eq!(p - Pos::new(2, 5), Pos::new(0, -2));
+        assert_eq!(-p * 2, Pos::new(-4, -6));
+        assert_eq!(p + Direction::Up, Pos::new(2, 2));
+        assert_eq!(p.checked_add(Pos::new(-2, -3)), Some(Pos::new(0, 0)));
+        assert_eq!(p.checked_add(Pos::new(-3, 0)), None);
+        assert_eq!(p.wrapping_add(Pos::new(-3, 1), 4, 4), Pos::new(3, 0));
+        assert_eq!(Pos::new(5, -7).signum(), Pos::new(1, -1));
+        assert_eq!(<(usize, usize)>::try_from(p), Ok((2, 3)));
+        assert!(<(usize, usize)>::try_from(Pos::new(0, -1)).is_err());
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(Pos::new(1, -2).manhattan(Pos::new(-2, 2)), 7);
+        assert_eq!(Pos::new(1, -2).chebyshev(Pos::new(-2, 2)), 4);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let p = Pos::new(0, 0);
+        assert_eq!(
+            p.neighbours4().collect::<Vec<_>>(),
+            [
+                Pos::new(0, -1),
+                Pos::new(1, 0),
+                Pos::new(0, 1),
+                Pos::new(-1, 0)
+            ]
+        );
+        assert_eq!(p.neighbours8().filter(|n| n.chebyshev(p) == 1).count(), 8);
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(Direction::Up.rotate(Rotate::Right), Direction::Right);
+        assert_eq!(Direction::Up.rotate(Rotate::Left), Direction::Left);
+        assert_eq!(Direction::Left.rotate(Rotate::Right), Direction::Up);
+        assert_eq!(Direction::Down.opposite(), Direction::Up);
+        assert!(Direction::all().all(|d| Pos::from(d) == -Pos::from(d.opposite())));
+        assert!(Direction::all().all(|d| Direction::try_from(char::from(d)) == Ok(d)));
+        assert_eq!(Direction::try_from('U'), Ok(Direction::Up));
+        assert!(Direction::try_from('x').is_err());
+    }
+}
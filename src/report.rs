@@ -0,0 +1,452 @@
+use crate::alloc::AllocStats;
+use crate::cli::Part;
+use crate::context::Cancelled;
+use std::fmt::Write;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Error,
+    Pass,
+    Fail,
+    Missing,
+    Timeout,
+    Cancelled,
+}
+
+impl Status {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Error => "error",
+            Status::Pass => "pass",
+            Status::Fail => "fail",
+            Status::Missing => "missing",
+            Status::Timeout => "timeout",
+            Status::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Result of a single part of a day.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: usize,
+    pub part: Part,
+    pub status: Status,
+    pub answer: Option<String>,
+    pub time: Duration,
+    pub error: Option<String>,
+    pub expected: Option<String>,
+    /// Allocations of the part, when counted with `--memory`.
+    pub alloc: Option<AllocStats>,
+}
+
+impl Record {
+    pub fn answer(day: usize, part: Part, answer: String, time: Duration) -> Self {
+        Record {
+            day,
+            part,
+            status: Status::Ok,
+            answer: Some(answer),
+            time,
+            error: None,
+            expected: None,
+            alloc: None,
+        }
+    }
+
+    pub fn error(day: usize, part: Part, error: String) -> Self {
+        Record {
+            day,
+            part,
+            status: Status::Error,
+            answer: None,
+            time: Duration::ZERO,
+            error: Some(error),
+            expected: None,
+            alloc: None,
+        }
+    }
+
+    pub fn timeout(day: usize, part: Part, error: String) -> Self {
+        Record {
+            status: Status::Timeout,
+            ..Record::error(day, part, error)
+        }
+    }
+
+    pub fn cancelled(day: usize, part: Part) -> Self {
+        Record {
+            status: Status::Cancelled,
+            ..Record::error(day, part, Cancelled.to_string())
+        }
+    }
+
+    /// Compares the answer with the `expected` one. Records without an answer
+    /// stay errors.
+    pub fn verify(&mut self, expected: Option<&str>) {
+        let Some(answer) = &self.answer else {
+            return;
+        };
+        self.status = match expected {
+            Some(expected) if expected == answer => Status::Pass,
+            Some(_) => Status::Fail,
+            None => Status::Missing,
+        };
+        self.expected = expected.map(str::to_string);
+    }
+}
+
+/// All the records of a single day, together with what it took to get them.
+#[derive(Debug)]
+pub struct DayRun {
+    pub day: usize,
+    pub title: &'static str,
+    pub input: String,
+    pub parse_time: Duration,
+    pub total_time: Duration,
+    pub records: Vec<Record>,
+    /// Allocations of the parser, when counted with `--memory`.
+    pub parse_alloc: Option<AllocStats>,
+}
+
+pub fn text(run: &DayRun, timed: bool) -> String {
+    let mut out = String::new();
+    let mut errors = Vec::new();
+    for record in &run.records {
+        match (&record.answer, &record.error) {
+            (Some(answer), _) => {
+                let verdict = match (record.status, &record.expected) {
+                    (Status::Pass, _) => " (pass)".to_string(),
+                    (Status::Fail, Some(expected)) => format!(" (FAIL, expected {expected})"),
+                    (Status::Missing, _) => " (missing)".to_string(),
+                    _ => String::new(),
+                };
+                writeln!(
+                    out,
+                    "day{} p{}: {answer}{verdict}",
+                    run.day,
+                    record.part.number()
+                )
+                .unwrap()
+            }
+            (None, Some(error)) if !errors.contains(&error) => {
+                writeln!(out, "day{}: {error}", run.day).unwrap();
+                errors.push(error);
+            }
+            _ => {}
+        }
+    }
+    if timed && errors.is_empty() {
+        let timings = run
+            .records
+            .iter()
+            .fold(format!("parse {:?}", run.parse_time), |timings, record| {
+                format!("{timings}, p{} {:?}", record.part.number(), record.time)
+            });
+        writeln!(
+            out,
+            "day{} ({}) execution time: {:?} ({timings})",
+            run.day, run.title, run.total_time
+        )
+        .unwrap();
+    }
+    if let (Some(parse), true) = (run.parse_alloc, errors.is_empty()) {
+        let (total, parts) = run.records.iter().fold(
+            (parse, format!("parse {parse}")),
+            |(total, parts), record| {
+                let alloc = record.alloc.unwrap_or_default();
+                (
+                    total.then(alloc),
+                    format!("{parts}; p{} {alloc}", record.part.number()),
+                )
+            },
+        );
+        writeln!(out, "day{} memory: {total} ({parts})", run.day).unwrap();
+    }
+    out
+}
+
+/// Changes of the answers and the execution time of `run` since the
+/// `previous` run of the same day.
+pub fn diff(previous: &DayRun, run: &DayRun) -> String {
+    // Answer of a part, or its error.
+    let outcome = |r: &Record| r.answer.clone().or(r.error.clone()).unwrap_or_default();
+    let mut out = String::new();
+    for record in &run.records {
+        let new = outcome(record);
+        match previous.records.iter().find(|r| r.part == record.part) {
+            Some(before) if outcome(before) == new => {
+                writeln!(out, "day{} p{}: unchanged", run.day, record.part.number()).unwrap()
+            }
+            Some(before) => writeln!(
+                out,
+                "day{} p{}: {} -> {new}",
+                run.day,
+                record.part.number(),
+                outcome(before)
+            )
+            .unwrap(),
+            None => {}
+        }
+    }
+    writeln!(
+        out,
+        "day{} execution time: {:?} -> {:?}",
+        run.day, previous.total_time, run.total_time
+    )
+    .unwrap();
+    out
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_option(s: &Option<String>) -> String {
+    s.as_deref().map_or("null".to_string(), json_string)
+}
+
+// Allocation counts of `r`, as values of the allocations, allocated_bytes and
+// peak_bytes fields.
+fn alloc_fields(r: &Record) -> [Option<String>; 3] {
+    match r.alloc {
+        Some(a) => [a.allocations, a.bytes, a.peak].map(|n| Some(n.to_string())),
+        None => [None, None, None],
+    }
+}
+
+/// JSON array with one object per record, one record per line.
+pub fn json(records: &[Record]) -> String {
+    let objects = records
+        .iter()
+        .map(|r| {
+            let [allocations, bytes, peak] =
+                alloc_fields(r).map(|n| n.unwrap_or_else(|| "null".to_string()));
+            format!(
+                r#"  {{"day": {}, "part": {}, "status": "{}", "answer": {}, "expected": {}, "time_ns": {}, "error": {}, "allocations": {allocations}, "allocated_bytes": {bytes}, "peak_bytes": {peak}}}"#,
+                r.day,
+                r.part.number(),
+                r.status.as_str(),
+                json_option(&r.answer),
+                json_option(&r.expected),
+                r.time.as_nanos(),
+                json_option(&r.error)
+            )
+        })
+        .collect::<Vec<String>>();
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn csv(records: &[Record]) -> String {
+    records.iter().fold(
+        "day,part,status,answer,expected,time_ns,error,allocations,allocated_bytes,peak_bytes\n"
+            .to_string(),
+        |mut out, r| {
+            let [allocations, bytes, peak] = alloc_fields(r).map(Option::unwrap_or_default);
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{},{allocations},{bytes},{peak}",
+                r.day,
+                r.part.number(),
+                r.status.as_str(),
+                csv_field(r.answer.as_deref().unwrap_or_default()),
+                csv_field(r.expected.as_deref().unwrap_or_default()),
+                r.time.as_nanos(),
+                csv_field(r.error.as_deref().unwrap_or_default())
+            )
+            .unwrap();
+            out
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record::answer(10, Part::One, "16480".to_string(), Duration::from_micros(3)),
+            Record::answer(
+                10,
+                Part::Two,
+                "\n#..#\n\"x\"".to_string(),
+                Duration::from_nanos(7),
+            ),
+            Record::error(
+                16,
+                Part::One,
+                "aoc_16.input:1:50: unknown valve 'BB'".to_string(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            json(&records()),
+            r#"[
+  {"day": 10, "part": 1, "status": "ok", "answer": "16480", "expected": null, "time_ns": 3000, "error": null, "allocations": null, "allocated_bytes": null, "peak_bytes": null},
+  {"day": 10, "part": 2, "status": "ok", "answer": "\n#..#\n\"x\"", "expected": null, "time_ns": 7, "error": null, "allocations": null, "allocated_bytes": null, "peak_bytes": null},
+  {"day": 16, "part": 1, "status": "error", "answer": null, "expected": null, "time_ns": 0, "error": "aoc_16.input:1:50: unknown valve 'BB'", "allocations": null, "allocated_bytes": null, "peak_bytes": null}
+]
+"#
+        );
+        assert_eq!(json_string("a\u{1}b"), r#""a\u0001b""#);
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            csv(&records()),
+            "day,part,status,answer,expected,time_ns,error,allocations,allocated_bytes,peak_bytes
+10,1,ok,16480,,3000,,,,
+10,2,ok,\"
+#..#
+\"\"x\"\"\",,7,,,,
+16,1,error,,,0,aoc_16.input:1:50: unknown valve 'BB',,,
+"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let mut run = DayRun {
+            day: 10,
+            title: "Cathode-Ray Tube",
+            input: "src/inputs/aoc_10.input".to_string(),
+            parse_time: Duration::from_micros(1),
+            total_time: Duration::from_micros(5),
+            records: records()[..1].to_vec(),
+            parse_alloc: None,
+        };
+        assert_eq!(text(&run, false), "day10 p1: 16480\n");
+        assert_eq!(
+            text(&run, true),
+            "day10 p1: 16480\nday10 (Cathode-Ray Tube) execution time: 5µs (parse 1µs, p1 3µs)\n"
+        );
+        run.records = vec![
+            Record::error(10, Part::One, "bad".to_string()),
+            Record::error(10, Part::Two, "bad".to_string()),
+        ];
+        assert_eq!(text(&run, true), "day10: bad\n");
+    }
+
+    #[test]
+    fn test_text_memory() {
+        let stats = |allocations, peak, retained| AllocStats {
+            allocations,
+            bytes: 2048,
+            peak,
+            retained,
+        };
+        let mut records = records()[..2].to_vec();
+        records[0].alloc = Some(stats(3, 1024, 0));
+        records[1].alloc = Some(stats(5, 100, 0));
+        let run = DayRun {
+            day: 10,
+            title: "Cathode-Ray Tube",
+            input: "src/inputs/aoc_10.input".to_string(),
+            parse_time: Duration::ZERO,
+            total_time: Duration::ZERO,
+            records,
+            parse_alloc: Some(stats(2, 2048, 512)),
+        };
+        assert_eq!(
+            text(&run, false).lines().last(),
+            Some(
+                "day10 memory: 10 allocs, 6.0 KiB allocated, 2.0 KiB peak \
+                 (parse 2 allocs, 2.0 KiB allocated, 2.0 KiB peak; \
+                 p1 3 allocs, 2.0 KiB allocated, 1.0 KiB peak; \
+                 p2 5 allocs, 2.0 KiB allocated, 100 B peak)"
+            )
+        );
+        assert!(csv(&run.records).contains("\n10,1,ok,16480,,3000,,3,2048,1024\n"));
+        assert!(json(&run.records)
+            .contains(r#""allocations": 3, "allocated_bytes": 2048, "peak_bytes": 1024}"#));
+    }
+
+    #[test]
+    fn test_diff() {
+        let run = |answer: &str, micros| DayRun {
+            day: 1,
+            title: "Calorie Counting",
+            input: "src/inputs/aoc_1_sample.input".to_string(),
+            parse_time: Duration::ZERO,
+            total_time: Duration::from_micros(micros),
+            records: vec![
+                Record::answer(1, Part::One, answer.to_string(), Duration::ZERO),
+                Record::answer(1, Part::Two, "45000".to_string(), Duration::ZERO),
+            ],
+            parse_alloc: None,
+        };
+        assert_eq!(
+            diff(&run("24000", 7), &run("25000", 5)),
+            "day1 p1: 24000 -> 25000\nday1 p2: unchanged\nday1 execution time: 7µs -> 5µs\n"
+        );
+        let mut broken = run("", 1);
+        broken.records[0] = Record::error(1, Part::One, "bad".to_string());
+        assert!(diff(&run("24000", 7), &broken).starts_with("day1 p1: 24000 -> bad\n"));
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut records = records();
+        records[0].verify(Some("16480"));
+        records[1].verify(Some("\n#..#"));
+        records[2].verify(Some("1873"));
+        assert_eq!(
+            records.iter().map(|r| r.status).collect::<Vec<Status>>(),
+            vec![Status::Pass, Status::Fail, Status::Error]
+        );
+        records[0].verify(None);
+        assert_eq!(records[0].status, Status::Missing);
+        assert_eq!(
+            csv(&records[1..2]),
+            "day,part,status,answer,expected,time_ns,error,allocations,allocated_bytes,peak_bytes
+10,2,fail,\"
+#..#
+\"\"x\"\"\",\"
+#..#\",7,,,,
+"
+        );
+        let run = DayRun {
+            day: 10,
+            title: "Cathode-Ray Tube",
+            input: "src/inputs/aoc_10.input".to_string(),
+            parse_time: Duration::ZERO,
+            total_time: Duration::ZERO,
+            records: records[..2].to_vec(),
+            parse_alloc: None,
+        };
+        assert_eq!(
+            text(&run, false),
+            "day10 p1: 16480 (missing)\nday10 p2: \n#..#\n\"x\" (FAIL, expected \n#..#)\n"
+        );
+    }
+}
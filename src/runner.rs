@@ -0,0 +1,786 @@
+use crate::alloc::Span;
+use crate::bench::{self, BenchError};
+use crate::cli::{Args, Part};
+use crate::context::{Context, Progress};
+use crate::report::{DayRun, Record};
+use crate::solver::{DynSolver, PartError};
+use rayon::ThreadPoolBuilder;
+use std::any::Any;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Reads the file at `path`, or stdin for `-`.
+pub fn read_input(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+/// Default location of the input of `day`.
+pub fn input_path(day: usize) -> String {
+    format!("src/inputs/aoc_{day}.input")
+}
+
+/// Location of the sample input of `day`, the one from the puzzle text.
+pub fn sample_path(day: usize) -> String {
+    format!("src/inputs/aoc_{day}_sample.input")
+}
+
+/// Returns the name of the input file of `day` together with its content, or a
+/// message why it couldn't be read.
+pub fn load_input(args: &Args, day: usize) -> Result<(String, String), String> {
+    match &args.input {
+        Some(path) if path == "-" => read_input(path).map(|input| ("<stdin>".to_string(), input)),
+        Some(path) => read_input(path).map(|input| (path.clone(), input)),
+        None => {
+            let path = input_path(day);
+            read_input(&path).map(|input| (path, input))
+        }
+    }
+    .map_err(|e| format!("can't read input: {e}"))
+}
+
+// Solves `part` of the parsed `model`, unless `ctx` is cancelled first.
+fn solve_part(solver: &dyn DynSolver, model: &dyn Any, part: Part, ctx: &Context) -> Record {
+    let ctx = &ctx.in_part(solver.day(), part);
+    let span = Span::start();
+    let now = Instant::now();
+    let answer = ctx
+        .check()
+        .map_err(PartError::from)
+        .and_then(|()| match part {
+            Part::One => solver.part1(model, ctx),
+            Part::Two => solver.part2(model, ctx),
+        });
+    let record = match answer {
+        Ok(answer) => Record::answer(solver.day(), part, answer, now.elapsed()),
+        Err(PartError::Cancelled) => Record::cancelled(solver.day(), part),
+        Err(e @ PartError::Unsolvable(_)) => Record::error(solver.day(), part, e.to_string()),
+    };
+    Record {
+        alloc: span.map(Span::stop),
+        ..record
+    }
+}
+
+// Solves the samples of `solver` instead of its input, checking the parts
+// selected by `args` on every sample with an answer for them. The run's input
+// lists the samples used.
+fn solve_samples(args: &Args, solver: &dyn DynSolver, ctx: &Context) -> DayRun {
+    let day = solver.day();
+    let start = Instant::now();
+    let mut run = DayRun {
+        day,
+        title: solver.title(),
+        input: String::new(),
+        parse_time: Duration::ZERO,
+        total_time: Duration::ZERO,
+        records: Vec::new(),
+        parse_alloc: None,
+    };
+    let mut names = Vec::new();
+    for sample in solver.samples() {
+        let parts = [Part::One, Part::Two]
+            .into_iter()
+            .filter(|part| args.runs_part(*part) && sample.answer(*part).is_some())
+            .collect::<Vec<Part>>();
+        if parts.is_empty() {
+            continue;
+        }
+        names.push(sample.name);
+        let solver = sample.solver.as_deref().unwrap_or(solver);
+        let now = Instant::now();
+        let model = solver
+            .parse(sample.input)
+            .map_err(|e| e.in_file(sample.name).to_string());
+        run.parse_time += now.elapsed();
+        for part in parts {
+            let mut record = match &model {
+                Ok(model) => solve_part(solver, model.as_ref(), part, ctx),
+                Err(e) => Record::error(day, part, e.clone()),
+            };
+            record.verify(sample.answer(part));
+            run.records.push(record);
+        }
+    }
+    if names.is_empty() {
+        run.records = [Part::One, Part::Two]
+            .into_iter()
+            .filter(|part| args.runs_part(*part))
+            .map(|part| Record::error(day, part, "no sample with an answer".to_string()))
+            .collect();
+    }
+    run.input = names.join(", ");
+    run.total_time = start.elapsed();
+    run
+}
+
+/// Solves the parts of `solver` selected by `args`, timing each step. Parts
+/// not done by the time `ctx` is cancelled are recorded as cancelled. With
+/// `--sample`, the day's samples are solved instead of its input.
+pub fn solve(args: &Args, solver: &dyn DynSolver, ctx: &Context) -> DayRun {
+    if args.sample {
+        return solve_samples(args, solver, ctx);
+    }
+    let day = solver.day();
+    let start = Instant::now();
+    let parts = [Part::One, Part::Two]
+        .into_iter()
+        .filter(|part| args.runs_part(*part));
+    let mut run = DayRun {
+        day,
+        title: solver.title(),
+        input: String::new(),
+        parse_time: Duration::ZERO,
+        total_time: Duration::ZERO,
+        records: Vec::new(),
+        parse_alloc: None,
+    };
+    let model = load_input(args, day).and_then(|(file, input)| {
+        run.input = file.clone();
+        let span = Span::start();
+        let now = Instant::now();
+        let model = solver
+            .parse(&input)
+            .map_err(|e| e.in_file(&file).to_string());
+        run.parse_time = now.elapsed();
+        run.parse_alloc = span.map(Span::stop);
+        model
+    });
+    run.records = match model {
+        Ok(model) => parts
+            .map(|part| solve_part(solver, model.as_ref(), part, ctx))
+            .collect(),
+        Err(e) => parts
+            .map(|part| Record::error(day, part, e.clone()))
+            .collect(),
+    };
+    run.total_time = start.elapsed();
+    run
+}
+
+/// Benchmarks `solver` and formats the report, or why it couldn't be run.
+pub fn bench(args: &Args, solver: &dyn DynSolver, runs: usize) -> Result<String, String> {
+    let day = solver.day();
+    let (file, input) = load_input(args, day).map_err(|e| format!("day{day}: {e}"))?;
+    match bench::bench(solver, &input, runs, args) {
+        Ok(report) => {
+            let mut out = format!("day{day} ({}), {} runs\n", solver.title(), report.runs);
+            out += &format!("  parse: {}\n", report.parse);
+            if let Some(stats) = report.part1 {
+                out += &format!("  p1:    {stats}\n");
+            }
+            if let Some(stats) = report.part2 {
+                out += &format!("  p2:    {stats}\n");
+            }
+            Ok(out)
+        }
+        Err(BenchError::Parse(e)) => Err(format!("day{day}: {}", e.in_file(&file))),
+        Err(BenchError::Part(e)) => Err(format!("day{day}: {e}")),
+    }
+}
+
+/// Bounds rayon's global pool, which runs the days' own parallel loops, to
+/// `--jobs` threads. Has to happen before anything uses the pool.
+pub fn init_pool(args: &Args) {
+    if let Some(jobs) = args.jobs {
+        ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .expect("can't start the thread pool");
+    }
+}
+
+// The flag of a started day is set once by whoever settles it first: its
+// worker when it's done, or `solve_all` when it times out, leaving the worker
+// to stop after it since a new one took its place.
+enum Event {
+    Started(usize, Instant, Context, Arc<AtomicBool>),
+    Progress(usize, Progress),
+    Done(DayRun),
+}
+
+// Run standing in for a day that didn't give one, with `record` for each of
+// its parts.
+fn stand_in(
+    args: &Args,
+    day: usize,
+    title: &'static str,
+    record: impl Fn(usize, Part) -> Record,
+) -> DayRun {
+    DayRun {
+        day,
+        title,
+        input: String::new(),
+        parse_time: Duration::ZERO,
+        total_time: Duration::ZERO,
+        records: [Part::One, Part::Two]
+            .into_iter()
+            .filter(|part| args.runs_part(*part))
+            .map(|part| record(day, part))
+            .collect(),
+        parse_alloc: None,
+    }
+}
+
+type Queue = Arc<Mutex<VecDeque<Box<dyn DynSolver>>>>;
+
+// Worker solving the queued days one after another, each with its own child
+// of `ctx`. Days run their parallel loops on the rayon pool, but the days
+// themselves don't, so a day waiting for its loop can't pick up another day.
+fn spawn_worker(args: Arc<Args>, ctx: Context, queue: Queue, tx: Sender<Event>) {
+    thread::spawn(move || loop {
+        let Some(solver) = queue.lock().unwrap().pop_front() else {
+            return;
+        };
+        let day = solver.day();
+        let progress = tx.clone();
+        let ctx = ctx.child(move |p| {
+            let _ = progress.send(Event::Progress(day, p));
+        });
+        let settled = Arc::new(AtomicBool::new(false));
+        let _ = tx.send(Event::Started(
+            day,
+            Instant::now(),
+            ctx.clone(),
+            settled.clone(),
+        ));
+        // A panicking day fails on its own rather than taking its worker with
+        // it, which would leave the run waiting for it.
+        let run = panic::catch_unwind(AssertUnwindSafe(|| solve(&args, solver.as_ref(), &ctx)))
+            .unwrap_or_else(|payload| {
+                let message = format!("panicked: {}", panic_message(payload.as_ref()));
+                stand_in(&args, day, solver.title(), |day, part| {
+                    Record::error(day, part, message.clone())
+                })
+            });
+        let _ = tx.send(Event::Done(run));
+        if settled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+    });
+}
+
+// Message a day panicked with.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload
+            .downcast_ref::<String>()
+            .map_or("no message", String::as_str),
+    }
+}
+
+// How often the progress line is redrawn at most.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// Latest progress of the running days, on a single line.
+fn progress_line(progress: &BTreeMap<usize, Progress>) -> String {
+    progress
+        .iter()
+        .map(|(day, p)| format!("day{day}: {p}"))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+// Path of the input of `day` read by `load_input`.
+fn input_file(args: &Args, day: usize) -> String {
+    args.input.clone().unwrap_or_else(|| input_path(day))
+}
+
+// Modification time and length of the file at `path`, telling whether it
+// changed, or nothing while it's missing.
+fn file_stamp(path: &str) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Solves the days of `args`, then solves them again every time their input
+/// file changes, checking the files every `interval` until `ctx` is cancelled.
+/// `report` gets each run along with the previous run of the same day.
+pub fn watch(
+    args: &Args,
+    solver: impl Fn(usize) -> Box<dyn DynSolver>,
+    ctx: &Context,
+    interval: Duration,
+    show_progress: bool,
+    mut report: impl FnMut(&mut DayRun, Option<&DayRun>),
+) {
+    let files = args
+        .days
+        .iter()
+        .map(|day| (*day, input_file(args, *day)))
+        .collect::<BTreeMap<usize, String>>();
+    let mut stamps = BTreeMap::new();
+    let mut runs: BTreeMap<usize, DayRun> = BTreeMap::new();
+    let mut changed = args.days.clone();
+    while !ctx.is_cancelled() {
+        if !changed.is_empty() {
+            // Taken before solving, so edits made meanwhile are seen next time.
+            for day in &changed {
+                stamps.insert(*day, file_stamp(&files[day]));
+            }
+            let solvers = changed.iter().map(|day| solver(*day)).collect();
+            solve_all(args, solvers, ctx, show_progress, |mut run| {
+                let day = run.day;
+                report(&mut run, runs.get(&day));
+                runs.insert(day, run);
+            });
+        }
+        thread::sleep(interval);
+        changed = files
+            .iter()
+            .filter(|(day, file)| stamps[*day] != file_stamp(file))
+            .map(|(day, _)| *day)
+            .collect();
+    }
+}
+
+/// Solves `solvers` on `--jobs` workers, starting them in the given order,
+/// and hands the runs to `report` in day order, each as soon as the days
+/// before it are done. Days still running after `--timeout` are cancelled and
+/// reported as timed out, with a new worker taking over their queue. Once
+/// `ctx` is cancelled, the remaining parts are reported as cancelled. With
+/// `show_progress`, the progress of the running days is kept on a line of
+/// stderr.
+pub fn solve_all(
+    args: &Args,
+    solvers: Vec<Box<dyn DynSolver>>,
+    ctx: &Context,
+    show_progress: bool,
+    mut report: impl FnMut(DayRun),
+) {
+    let shared = Arc::new(args.clone());
+    // Days not reported yet.
+    let pending = solvers
+        .iter()
+        .map(|solver| (solver.day(), solver.title()))
+        .collect::<BTreeMap<usize, &'static str>>();
+    let queue = Arc::new(Mutex::new(VecDeque::from(solvers)));
+    let (tx, rx) = mpsc::channel();
+    let spawn = || spawn_worker(shared.clone(), ctx.clone(), queue.clone(), tx.clone());
+    // Allocations are counted globally, so with `--memory` the days take turns.
+    let workers = match args.memory {
+        true => 1,
+        false => args.jobs.unwrap_or_else(rayon::current_num_threads),
+    };
+    (0..workers.min(pending.len())).for_each(|_| spawn());
+
+    let mut pending = pending;
+    let mut running: BTreeMap<usize, (Instant, Context, Arc<AtomicBool>)> = BTreeMap::new();
+    let mut progress = BTreeMap::new();
+    let mut shown: Option<Instant> = None;
+    let mut done = BTreeMap::new();
+    while !pending.is_empty() {
+        let deadline = args
+            .timeout
+            .and_then(|timeout| running.values().map(|(start, ..)| *start + timeout).min());
+        let event = match deadline {
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Ok(Event::Started(day, start, ctx, settled)) => {
+                running.insert(day, (start, ctx, settled));
+            }
+            Ok(Event::Progress(day, p)) => {
+                if running.contains_key(&day) {
+                    progress.insert(day, p);
+                }
+            }
+            Ok(Event::Done(run)) => {
+                progress.remove(&run.day);
+                if running.remove(&run.day).is_some() {
+                    done.insert(run.day, run);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let timeout = args.timeout.expect("deadline without a timeout");
+                let message = format!("timed out after {timeout:?}");
+                running.retain(|day, (start, ctx, settled)| {
+                    // A day settled by its worker is done, its run on the way.
+                    if start.elapsed() < timeout || settled.swap(true, Ordering::SeqCst) {
+                        return true;
+                    }
+                    ctx.cancel();
+                    progress.remove(day);
+                    let run = stand_in(args, *day, pending[day], |day, part| {
+                        Record::timeout(day, part, message.clone())
+                    });
+                    done.insert(*day, run);
+                    spawn();
+                    false
+                });
+            }
+            Err(RecvTimeoutError::Disconnected) => panic!("a day stopped without a result"),
+        }
+        let mut stderr = io::stderr();
+        while let Some(entry) = pending.first_entry() {
+            match done.remove(entry.key()) {
+                Some(run) => {
+                    entry.remove();
+                    if shown.take().is_some() {
+                        let _ = write!(stderr, "\r\x1b[K");
+                    }
+                    report(run);
+                }
+                None => break,
+            }
+        }
+        if show_progress && shown.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL) {
+            let _ = write!(stderr, "\r\x1b[K{}", progress_line(&progress));
+            shown = Some(Instant::now());
+        }
+    }
+    if shown.is_some() {
+        let _ = write!(io::stderr(), "\r\x1b[K");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Counter;
+    use crate::parse::ParseResult;
+    use crate::report::Status;
+    use crate::solver::{PartResult, Sample, Solver};
+
+    // Day taking `.1` to solve each part, in steps of 10ms.
+    struct Slow(usize, Duration);
+
+    impl Solver for Slow {
+        type Input = ();
+
+        fn day(&self) -> usize {
+            self.0
+        }
+
+        fn title(&self) -> &'static str {
+            "Slow"
+        }
+
+        fn parse(&self, _: &str) -> ParseResult<Self::Input> {
+            Ok(())
+        }
+
+        fn part1(&self, _: &Self::Input, ctx: &Context) -> PartResult {
+            let steps = Counter::new(ctx, "steps", None, 1);
+            for _ in 0..self.1.as_millis() / 10 {
+                thread::sleep(Duration::from_millis(10));
+                steps.add(1)?;
+            }
+            Ok("done".to_string())
+        }
+
+        fn part2(&self, _: &Self::Input, ctx: &Context) -> PartResult {
+            Solver::part1(self, &(), ctx)
+        }
+    }
+
+    fn run(args: &str, days: &[(usize, u64)], ctx: &Context) -> Vec<(usize, Status)> {
+        let args = crate::cli::parse(args.split_whitespace().map(String::from)).unwrap();
+        let solvers = days
+            .iter()
+            .map(|(day, ms)| Box::new(Slow(*day, Duration::from_millis(*ms))) as Box<dyn DynSolver>)
+            .collect();
+        let mut reported = Vec::new();
+        solve_all(&args, solvers, ctx, false, |run| {
+            reported.push((run.day, run.records[0].status))
+        });
+        reported
+    }
+
+    #[test]
+    fn test_solve_all_order() {
+        let ctx = Context::default();
+        assert_eq!(
+            run("--jobs 3 --part 1", &[(3, 0), (1, 200), (2, 0)], &ctx),
+            vec![(1, Status::Ok), (2, Status::Ok), (3, Status::Ok)]
+        );
+    }
+
+    #[test]
+    fn test_solve_all_timeout() {
+        let ctx = Context::default();
+        assert_eq!(
+            run("--jobs 2 --timeout 0.3", &[(1, 2000), (2, 0), (3, 0)], &ctx),
+            vec![(1, Status::Timeout), (2, Status::Ok), (3, Status::Ok)]
+        );
+        // Day 2 gets a new worker once day 1 times out.
+        assert_eq!(
+            run("--jobs 1 --timeout 0.2", &[(1, 2000), (2, 0)], &ctx),
+            vec![(1, Status::Timeout), (2, Status::Ok)]
+        );
+    }
+
+    // Day 1 runs until it's cancelled, the others take 50ms, counting how
+    // many of them run at once.
+    struct Gauge(usize, Arc<Mutex<(usize, usize)>>);
+
+    impl Solver for Gauge {
+        type Input = ();
+
+        fn day(&self) -> usize {
+            self.0
+        }
+
+        fn title(&self) -> &'static str {
+            "Gauge"
+        }
+
+        fn parse(&self, _: &str) -> ParseResult<Self::Input> {
+            Ok(())
+        }
+
+        fn part1(&self, _: &Self::Input, ctx: &Context) -> PartResult {
+            if self.0 == 1 {
+                loop {
+                    thread::sleep(Duration::from_millis(10));
+                    ctx.check()?;
+                }
+            }
+            {
+                let mut gauge = self.1.lock().unwrap();
+                gauge.0 += 1;
+                gauge.1 = gauge.1.max(gauge.0);
+            }
+            thread::sleep(Duration::from_millis(50));
+            self.1.lock().unwrap().0 -= 1;
+            Ok("done".to_string())
+        }
+
+        fn part2(&self, _: &Self::Input, ctx: &Context) -> PartResult {
+            Solver::part1(self, &(), ctx)
+        }
+    }
+
+    #[test]
+    fn test_solve_all_timeout_jobs() {
+        let args = crate::cli::parse(
+            "--jobs 1 --timeout 0.1 --part 1"
+                .split(' ')
+                .map(String::from),
+        )
+        .unwrap();
+        let gauge = Arc::new(Mutex::new((0, 0)));
+        let solvers = (1..=4)
+            .map(|day| Box::new(Gauge(day, gauge.clone())) as Box<dyn DynSolver>)
+            .collect();
+        let mut reported = Vec::new();
+        solve_all(&args, solvers, &Context::default(), false, |run| {
+            reported.push(run.records[0].status)
+        });
+        assert_eq!(
+            reported,
+            [Status::Timeout, Status::Ok, Status::Ok, Status::Ok]
+        );
+        // The worker of the timed out day stopped, leaving the queue to the
+        // one replacing it.
+        assert_eq!(gauge.lock().unwrap().1, 1);
+    }
+
+    // Day whose first part panics.
+    struct Panic;
+
+    impl Solver for Panic {
+        type Input = ();
+
+        fn day(&self) -> usize {
+            2
+        }
+
+        fn title(&self) -> &'static str {
+            "Panic"
+        }
+
+        fn parse(&self, _: &str) -> ParseResult<Self::Input> {
+            Ok(())
+        }
+
+        fn part1(&self, _: &Self::Input, _: &Context) -> PartResult {
+            panic!("out of {}", "bounds")
+        }
+
+        fn part2(&self, _: &Self::Input, _: &Context) -> PartResult {
+            Ok("unreached".to_string())
+        }
+    }
+
+    #[test]
+    fn test_solve_all_panic() {
+        let args = crate::cli::parse("--jobs 1".split(' ').map(String::from)).unwrap();
+        let solvers = vec![
+            Box::new(Slow(1, Duration::ZERO)) as Box<dyn DynSolver>,
+            Box::new(Panic),
+            Box::new(Slow(3, Duration::ZERO)),
+        ];
+        let mut reported = Vec::new();
+        solve_all(&args, solvers, &Context::default(), false, |run| {
+            reported.extend(run.records.into_iter().map(|r| (r.day, r.status, r.error)))
+        });
+        let panicked = Some("panicked: out of bounds".to_string());
+        assert_eq!(
+            reported,
+            vec![
+                (1, Status::Ok, None),
+                (1, Status::Ok, None),
+                (2, Status::Error, panicked.clone()),
+                (2, Status::Error, panicked),
+                (3, Status::Ok, None),
+                (3, Status::Ok, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve_all_cancel() {
+        let ctx = Context::default().child(|_| {});
+        let cancel = ctx.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            cancel.cancel();
+        });
+        assert_eq!(
+            run("--jobs 1", &[(1, 2000), (2, 0)], &ctx),
+            vec![(1, Status::Cancelled), (2, Status::Cancelled)]
+        );
+    }
+
+    #[test]
+    fn test_watch() {
+        let input = crate::client::mock::temp_path("watch.input");
+        fs::write(&input, "1\n2\n\n3\n\n").unwrap();
+        let args = crate::cli::parse(
+            ["1", "--watch", "--input", input.to_str().unwrap()].map(String::from),
+        )
+        .unwrap();
+        let ctx = Context::default().child(|_| {});
+        let mut answers = Vec::new();
+        watch(
+            &args,
+            |day| crate::solver::registry().remove(&day).unwrap(),
+            &ctx,
+            Duration::from_millis(10),
+            false,
+            |run, previous| {
+                answers.push((run.records[0].answer.clone(), previous.is_some()));
+                match previous {
+                    None => fs::write(&input, "10\n\n").unwrap(),
+                    Some(_) => ctx.cancel(),
+                }
+            },
+        );
+        assert_eq!(
+            answers,
+            vec![
+                (Some("3".to_string()), false),
+                (Some("10".to_string()), true)
+            ]
+        );
+    }
+
+    // Day answering its input and the input's length.
+    struct Echo;
+
+    impl Solver for Echo {
+        type Input = String;
+
+        fn day(&self) -> usize {
+            1
+        }
+
+        fn title(&self) -> &'static str {
+            "Echo"
+        }
+
+        fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+            Ok(input.to_string())
+        }
+
+        fn part1(&self, input: &Self::Input, _ctx: &Context) -> PartResult {
+            Ok(input.clone())
+        }
+
+        fn part2(&self, input: &Self::Input, _ctx: &Context) -> PartResult {
+            Ok(input.len().to_string())
+        }
+
+        fn samples(&self) -> Vec<Sample> {
+            vec![
+                Sample::new("abc", "abc").part1("abc").part2("4"),
+                Sample::new("none", "unchecked"),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_solve_samples() {
+        let args = |s: &str| crate::cli::parse(s.split_whitespace().map(String::from)).unwrap();
+        let ctx = Context::default();
+        let outcome = |run: &DayRun| {
+            run.records
+                .iter()
+                .map(|r| (r.part, r.status, r.answer.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        let run = solve(&args("1 --sample"), &Echo, &ctx);
+        assert_eq!(run.input, "abc");
+        assert_eq!(
+            outcome(&run),
+            vec![
+                (Part::One, Status::Pass, Some("abc".to_string())),
+                (Part::Two, Status::Fail, Some("3".to_string()))
+            ]
+        );
+
+        let registry = crate::solver::registry();
+        let run = solve(&args("9 --sample --part 2"), registry[&9].as_ref(), &ctx);
+        assert_eq!(run.input, "aoc_9_sample.input, aoc_9_sample2.input");
+        assert_eq!(
+            outcome(&run),
+            vec![
+                (Part::Two, Status::Pass, Some("1".to_string())),
+                (Part::Two, Status::Pass, Some("36".to_string()))
+            ]
+        );
+        // Solved with the row of the sample rather than the input's.
+        let run = solve(&args("15 --sample --part 1"), registry[&15].as_ref(), &ctx);
+        assert_eq!(run.records[0].status, Status::Pass);
+
+        let run = solve(&args("1 --sample"), &Slow(1, Duration::ZERO), &ctx);
+        assert_eq!(
+            run.records[0].error.as_deref(),
+            Some("no sample with an answer")
+        );
+    }
+
+    #[test]
+    fn test_progress_line() {
+        let progress = |done, total, best| Progress {
+            unit: "rows",
+            done,
+            total,
+            best,
+        };
+        let running = BTreeMap::from([
+            (15, progress(100, Some(400), None)),
+            (16, progress(7, None, Some(1651))),
+        ]);
+        assert_eq!(
+            progress_line(&running),
+            "day15: 100/400 rows | day16: 7 rows, best 1651"
+        );
+    }
+}
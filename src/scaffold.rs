@@ -0,0 +1,297 @@
+use crate::runner;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TEMPLATE: &str = include_str!("template.rs");
+const MAX_WIDTH: usize = 100;
+
+/// Source of the `dayN` module made from `src/template.rs`.
+pub fn render(day: usize) -> String {
+    TEMPLATE.replace("XX", &day.to_string())
+}
+
+fn module_name(line: &str) -> Option<&str> {
+    line.strip_prefix("pub mod ")?.strip_suffix(';')
+}
+
+fn join_lines(lines: &[String], trailing_newline: bool) -> String {
+    let mut s = lines.join("\n");
+    if trailing_newline {
+        s.push('\n');
+    }
+    s
+}
+
+// Adds `pub mod dayN;` to the sorted module list of lib.rs.
+fn register_module(lib: &str, day: usize) -> Result<String, String> {
+    let name = format!("day{day}");
+    let mut lines = lib.lines().map(String::from).collect::<Vec<String>>();
+    if lines.iter().any(|l| module_name(l) == Some(&name)) {
+        return Err(format!("{name} is already declared in src/lib.rs"));
+    }
+    let at = match lines.iter().position(|l| module_name(l) > Some(&name)) {
+        Some(at) => at,
+        None => {
+            lines
+                .iter()
+                .rposition(|l| module_name(l).is_some())
+                .ok_or("no module list in src/lib.rs")?
+                + 1
+        }
+    };
+    lines.insert(at, format!("pub mod {name};"));
+    Ok(join_lines(&lines, lib.ends_with('\n')))
+}
+
+// Import of `items` from the crate root, wrapped the way rustfmt does.
+fn use_crate(items: &[String]) -> Vec<String> {
+    let single = format!("use crate::{{{}}};", items.join(", "));
+    if single.len() <= MAX_WIDTH {
+        return vec![single];
+    }
+    let mut lines = vec!["use crate::{".to_string()];
+    let mut line = String::new();
+    for item in items {
+        if !line.is_empty() && 4 + line.len() + 1 + item.len() + 1 > MAX_WIDTH {
+            lines.push(format!("    {line}"));
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(item);
+        line.push(',');
+    }
+    lines.push(format!("    {line}"));
+    lines.push("};".to_string());
+    lines
+}
+
+fn registered_day(line: &str) -> Option<usize> {
+    let rest = line.trim_start().strip_prefix("Box::new(day")?;
+    rest[..rest.find("::")?].parse().ok()
+}
+
+// Imports the `dayN` module in solver.rs and adds `DayN` to the registry.
+fn register_solver(solver: &str, day: usize) -> Result<String, String> {
+    let mut lines = solver.lines().map(String::from).collect::<Vec<String>>();
+    if lines.iter().any(|l| registered_day(l) == Some(day)) {
+        return Err(format!(
+            "day{day} is already in the registry of src/solver.rs"
+        ));
+    }
+    let from = lines
+        .iter()
+        .position(|l| l.starts_with("use crate::{"))
+        .ok_or("no import of the days in src/solver.rs")?;
+    let to = from
+        + lines[from..]
+            .iter()
+            .position(|l| l.ends_with("};"))
+            .ok_or("unterminated import of the days in src/solver.rs")?;
+    let mut items = lines[from..=to]
+        .join(" ")
+        .trim_start_matches("use crate::{")
+        .trim_end_matches("};")
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect::<Vec<String>>();
+    items.push(format!("day{day}"));
+    items.sort();
+    lines.splice(from..=to, use_crate(&items));
+
+    let at = match lines
+        .iter()
+        .position(|l| registered_day(l).is_some_and(|d| d > day))
+    {
+        Some(at) => at,
+        None => {
+            lines
+                .iter()
+                .rposition(|l| registered_day(l).is_some())
+                .ok_or("no registry in src/solver.rs")?
+                + 1
+        }
+    };
+    lines.insert(at, format!("        Box::new(day{day}::Day{day}),"));
+    Ok(join_lines(&lines, solver.ends_with('\n')))
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("can't read {}: {e}", path.display()))
+}
+
+fn write(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content).map_err(|e| format!("can't write {}: {e}", path.display()))
+}
+
+/// Creates the `dayN` module of `day` in the crate at `root` and registers it
+/// with the runner, along with empty input and sample files unless they're
+/// there already. Returns the files it wrote.
+pub fn scaffold(root: &Path, day: usize) -> Result<Vec<PathBuf>, String> {
+    let module = root.join(format!("src/day{day}.rs"));
+    if module.exists() {
+        return Err(format!("{} already exists", module.display()));
+    }
+    let lib = root.join("src/lib.rs");
+    let solver = root.join("src/solver.rs");
+    // Both are checked before anything is written, so a failure leaves the
+    // crate as it was.
+    let lib_content = register_module(&read(&lib)?, day)?;
+    let solver_content = register_solver(&read(&solver)?, day)?;
+    write(&module, &render(day))?;
+    write(&lib, &lib_content)?;
+    write(&solver, &solver_content)?;
+    let mut written = vec![module, lib, solver];
+    for input in [runner::input_path(day), runner::sample_path(day)] {
+        let input = root.join(input);
+        if !input.exists() {
+            write(&input, "")?;
+            written.push(input);
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    const LIB: &str = include_str!("lib.rs");
+    const SOLVER: &str = include_str!("solver.rs");
+
+    // The crate sources without `day`.
+    fn without_day(day: usize) -> (String, String) {
+        let lib = LIB.replace(&format!("pub mod day{day};\n"), "");
+        let solver = SOLVER
+            .replace(&format!(" day{day},"), "")
+            .replace(&format!("        Box::new(day{day}::Day{day}),\n"), "");
+        (lib, solver)
+    }
+
+    #[test]
+    fn test_render() {
+        let source = render(26);
+        assert!(source.contains("pub struct Day26;"));
+        assert!(source.contains("\"src/inputs/aoc_26_sample.input\""));
+        assert!(!source.contains("XX"));
+    }
+
+    #[test]
+    fn test_register() {
+        for day in [1, 9, 14, 25] {
+            let (lib, solver) = without_day(day);
+            assert_ne!(lib, LIB);
+            assert_eq!(register_module(&lib, day).unwrap(), LIB);
+            assert_eq!(register_solver(&solver, day).unwrap(), SOLVER);
+        }
+        assert!(register_module(LIB, 3).is_err());
+        assert!(register_solver(SOLVER, 3).is_err());
+    }
+
+    #[test]
+    fn test_scaffold() {
+        let root = std::env::temp_dir().join(format!("aoc2022-scaffold-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/inputs")).unwrap();
+        let (lib, solver) = without_day(7);
+        fs::write(root.join("src/lib.rs"), lib).unwrap();
+        fs::write(root.join("src/solver.rs"), solver).unwrap();
+        fs::write(root.join("src/inputs/aoc_7.input"), "$ ls\n").unwrap();
+
+        assert_eq!(scaffold(&root, 7).unwrap().len(), 4);
+        assert_eq!(
+            fs::read_to_string(root.join("src/day7.rs")).unwrap(),
+            render(7)
+        );
+        assert_eq!(fs::read_to_string(root.join("src/lib.rs")).unwrap(), LIB);
+        assert_eq!(
+            fs::read_to_string(root.join("src/solver.rs")).unwrap(),
+            SOLVER
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("src/inputs/aoc_7.input")).unwrap(),
+            "$ ls\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("src/inputs/aoc_7_sample.input")).unwrap(),
+            ""
+        );
+        assert!(scaffold(&root, 7)
+            .unwrap_err()
+            .ends_with("day7.rs already exists"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    // Copies `from` to `to`, along with its subdirectories.
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let path = entry.unwrap().path();
+            let target = to.join(path.file_name().unwrap());
+            match path.is_dir() {
+                true => copy_dir(&path, &target),
+                false => drop(fs::copy(&path, &target).unwrap()),
+            }
+        }
+    }
+
+    // Day 7 scaffolded again in a copy of the crate without it builds and
+    // passes its own tests, along with the ones going through every day. Run
+    // with `cargo test -- --ignored scaffold`.
+    #[test]
+    #[ignore = "builds a copy of the crate, needs its dependencies offline"]
+    fn test_scaffolded_day_passes() {
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let root = std::env::temp_dir().join(format!("aoc2022-crate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        copy_dir(&manifest.join("src"), &root.join("src"));
+        for file in ["Cargo.toml", "Cargo.lock"] {
+            if manifest.join(file).exists() {
+                fs::copy(manifest.join(file), root.join(file)).unwrap();
+            }
+        }
+        let (lib, solver) = without_day(7);
+        fs::write(root.join("src/lib.rs"), lib).unwrap();
+        fs::write(root.join("src/solver.rs"), solver).unwrap();
+        fs::remove_file(root.join("src/day7.rs")).unwrap();
+        for input in [runner::input_path(7), runner::sample_path(7)] {
+            fs::remove_file(root.join(input)).unwrap();
+        }
+        let answers = root.join("src/inputs/answers.txt");
+        let kept = fs::read_to_string(&answers)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("7 "))
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        fs::write(&answers, kept).unwrap();
+        scaffold(&root, 7).unwrap();
+
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let output = Command::new(cargo)
+            .args([
+                "test",
+                "--offline",
+                "--lib",
+                "--",
+                "day7::",
+                "solver::tests",
+            ])
+            .current_dir(&root)
+            // Kept between runs, so only the copy of the crate is built again.
+            .env("CARGO_TARGET_DIR", manifest.join("target/scaffold"))
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,321 @@
+use crate::grid::Grid;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::mem;
+use std::ops::Add;
+
+/// Remembers the states a search went through, so each is expanded once.
+pub trait Visited<S> {
+    /// Marks `state` as visited, returning false if it already was.
+    fn visit(&mut self, state: &S) -> bool;
+}
+
+impl<S: Clone + Eq + Hash> Visited<S> for HashSet<S> {
+    fn visit(&mut self, state: &S) -> bool {
+        self.insert(state.clone())
+    }
+}
+
+/// Flags of the visited cells of a map, cheaper than hashing the positions.
+impl Visited<(usize, usize)> for Grid<bool> {
+    fn visit(&mut self, pos: &(usize, usize)) -> bool {
+        !mem::replace(&mut self[*pos], true)
+    }
+}
+
+/// Visited set telling states apart by their `key` only, like positions at
+/// the same point of a cycle.
+pub struct VisitedBy<K, F> {
+    seen: HashSet<K>,
+    key: F,
+}
+
+impl<K, F> VisitedBy<K, F> {
+    pub fn new(key: F) -> Self {
+        VisitedBy {
+            seen: HashSet::new(),
+            key,
+        }
+    }
+}
+
+impl<S, K: Eq + Hash, F: FnMut(&S) -> K> Visited<S> for VisitedBy<K, F> {
+    fn visit(&mut self, state: &S) -> bool {
+        self.seen.insert((self.key)(state))
+    }
+}
+
+/// Goal state a search got to, with the cost of getting there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Found<S, C> {
+    pub state: S,
+    pub cost: C,
+    /// States from a start to `state`, both included, for searches made
+    /// `with_paths`. Empty otherwise.
+    pub path: Vec<S>,
+}
+
+// States reached by a search, each with the index of its predecessor.
+struct Trail<S> {
+    nodes: Vec<(S, Option<usize>)>,
+    paths: bool,
+}
+
+impl<S: Clone> Trail<S> {
+    fn push(&mut self, state: S, parent: Option<usize>) -> usize {
+        self.nodes.push((state, parent));
+        self.nodes.len() - 1
+    }
+
+    fn found<C>(&self, node: usize, cost: C) -> Found<S, C> {
+        let mut path = Vec::new();
+        let mut next = Some(node).filter(|_| self.paths);
+        while let Some(i) = next {
+            path.push(self.nodes[i].0.clone());
+            next = self.nodes[i].1;
+        }
+        path.reverse();
+        Found {
+            state: self.nodes[node].0.clone(),
+            cost,
+            path,
+        }
+    }
+}
+
+/// Search through states of type `S`, from one or more starts. Visited states
+/// are kept in a `HashSet` unless the search is given another `Visited`.
+pub struct Search<S, V = HashSet<S>> {
+    starts: Vec<S>,
+    visited: V,
+    paths: bool,
+}
+
+impl<S: Clone + Eq + Hash> Search<S> {
+    pub fn new(starts: impl IntoIterator<Item = S>) -> Self {
+        Search {
+            starts: starts.into_iter().collect(),
+            visited: HashSet::new(),
+            paths: false,
+        }
+    }
+}
+
+impl<S: Clone, V: Visited<S>> Search<S, V> {
+    pub fn visited<W: Visited<S>>(self, visited: W) -> Search<S, W> {
+        Search {
+            starts: self.starts,
+            visited,
+            paths: self.paths,
+        }
+    }
+
+    /// Makes the search reconstruct the path to the goal it finds.
+    pub fn with_paths(self) -> Self {
+        Search {
+            paths: true,
+            ..self
+        }
+    }
+
+    fn trail(&self) -> Trail<S> {
+        Trail {
+            nodes: Vec::new(),
+            paths: self.paths,
+        }
+    }
+
+    fn run_bfs<I>(
+        &mut self,
+        mut successors: impl FnMut(&S) -> I,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, usize>>
+    where
+        I: IntoIterator<Item = S>,
+    {
+        let mut trail = self.trail();
+        let mut queue = VecDeque::new();
+        for start in mem::take(&mut self.starts) {
+            if self.visited.visit(&start) {
+                queue.push_back((trail.push(start, None), 0));
+            }
+        }
+        while let Some((node, steps)) = queue.pop_front() {
+            let state = trail.nodes[node].0.clone();
+            if goal(&state) {
+                return Some(trail.found(node, steps));
+            }
+            for next in successors(&state) {
+                if self.visited.visit(&next) {
+                    queue.push_back((trail.push(next, Some(node)), steps + 1));
+                }
+            }
+        }
+        None
+    }
+
+    /// Nearest state matching `goal`, counting the `successors` of a state as
+    /// one step away from it.
+    pub fn bfs<I>(
+        mut self,
+        successors: impl FnMut(&S) -> I,
+        goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, usize>>
+    where
+        I: IntoIterator<Item = S>,
+    {
+        self.run_bfs(successors, goal)
+    }
+
+    /// Visited set holding every state reachable from the starts.
+    pub fn reachable<I>(mut self, successors: impl FnMut(&S) -> I) -> V
+    where
+        I: IntoIterator<Item = S>,
+    {
+        self.run_bfs(successors, |_| false);
+        self.visited
+    }
+
+    /// Cheapest state matching `goal`, `successors` giving the states next to
+    /// a state along with the cost of moving there.
+    pub fn dijkstra<C, I>(
+        self,
+        successors: impl FnMut(&S) -> I,
+        goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, C>>
+    where
+        C: Copy + Ord + Default + Add<Output = C>,
+        I: IntoIterator<Item = (S, C)>,
+    {
+        self.astar(successors, |_| C::default(), goal)
+    }
+
+    /// Like `dijkstra`, but going first through the states whose cost plus
+    /// `heuristic` is the lowest. The heuristic mustn't overestimate the cost
+    /// left to a goal, nor drop by more than the cost of a move.
+    pub fn astar<C, I>(
+        mut self,
+        mut successors: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> C,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, C>>
+    where
+        C: Copy + Ord + Default + Add<Output = C>,
+        I: IntoIterator<Item = (S, C)>,
+    {
+        // The heap refers to the states of the trail, so they needn't be `Ord`.
+        let mut trail = self.trail();
+        let mut heap = BinaryHeap::new();
+        for start in mem::take(&mut self.starts) {
+            let estimate = heuristic(&start);
+            heap.push(Reverse((estimate, C::default(), trail.push(start, None))));
+        }
+        while let Some(Reverse((_, cost, node))) = heap.pop() {
+            let state = trail.nodes[node].0.clone();
+            if !self.visited.visit(&state) {
+                continue;
+            }
+            if goal(&state) {
+                return Some(trail.found(node, cost));
+            }
+            for (next, step) in successors(&state) {
+                let cost = cost + step;
+                let estimate = cost + heuristic(&next);
+                heap.push(Reverse((estimate, cost, trail.push(next, Some(node)))));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pos::Pos;
+
+    fn maze() -> Grid<char> {
+        Grid::parse("..#....\n.##.##.\n....#..\n.##...#", Ok).unwrap()
+    }
+
+    fn open(maze: &Grid<char>, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        maze.neighbours4(pos).filter(|p| maze[*p] == '.')
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze();
+        let found = Search::new([(0, 0)])
+            .with_paths()
+            .bfs(|p| open(&maze, *p), |p| *p == (6, 0))
+            .unwrap();
+        assert_eq!(found.cost, 10);
+        assert_eq!(found.path.len(), 11);
+        assert_eq!(found.path.first(), Some(&(0, 0)));
+        assert!(found
+            .path
+            .windows(2)
+            .all(|w| open(&maze, w[0]).any(|p| p == w[1])));
+
+        let found = Search::new([(0, 0), (5, 3)])
+            .visited(Grid::new(maze.width(), maze.height(), false))
+            .bfs(|p| open(&maze, *p), |p| *p == (6, 0))
+            .unwrap();
+        assert_eq!(found.cost, 4);
+        assert!(found.path.is_empty());
+        assert_eq!(
+            Search::new([(0, 0)]).bfs(|p| open(&maze, *p), |p| *p == (2, 0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reachable() {
+        let maze = maze();
+        let reached = Search::new([(3, 0)]).reachable(|p| open(&maze, *p));
+        assert_eq!(
+            reached.len(),
+            maze.iter().filter(|(_, c)| **c == '.').count()
+        );
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Going through 1 costs 5, around it through 2 and 3 costs 3.
+        let edges = [(0, 1, 1), (1, 4, 4), (0, 2, 1), (2, 3, 1), (3, 4, 1)];
+        let found = Search::new([0])
+            .with_paths()
+            .dijkstra(
+                |n| {
+                    let n = *n;
+                    edges.iter().filter(move |e| e.0 == n).map(|e| (e.1, e.2))
+                },
+                |n| *n == 4,
+            )
+            .unwrap();
+        assert_eq!((found.cost, found.path), (3, vec![0, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_astar() {
+        let maze = maze();
+        let goal = Pos::new(6, 0);
+        let found = Search::new([(0, 0)])
+            .astar(
+                |p| open(&maze, *p).map(|p| (p, 1)),
+                |p| Pos::from(*p).manhattan(goal),
+                |p| *p == (6, 0),
+            )
+            .unwrap();
+        assert_eq!(found.cost, 10);
+    }
+
+    #[test]
+    fn test_visited_by() {
+        // Counting up modulo 5, states are the same when their values are.
+        let found = Search::new([(0, 0)])
+            .visited(VisitedBy::new(|s: &(usize, usize)| s.1))
+            .bfs(|s| [(s.0 + 1, (s.1 + 1) % 5)], |s| s.0 == 7);
+        assert_eq!(found, None);
+    }
+}
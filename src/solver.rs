@@ -0,0 +1,278 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::cli::Part;
+use crate::context::{Cancelled, Context};
+use crate::parse::ParseResult;
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day20, day21,
+    day22, day23, day24, day25, day3, day4, day5, day6, day7, day8, day9,
+};
+
+/// Why a part has no answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartError {
+    Cancelled,
+    /// The input parsed, but the puzzle has no answer for it, like a day 6
+    /// signal without a marker.
+    Unsolvable(String),
+}
+
+impl From<Cancelled> for PartError {
+    fn from(_: Cancelled) -> Self {
+        PartError::Cancelled
+    }
+}
+
+impl fmt::Display for PartError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PartError::Cancelled => write!(f, "{Cancelled}"),
+            PartError::Unsolvable(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+/// Answer of a part, unless its run was cancelled first or the input has
+/// none.
+pub type PartResult = Result<String, PartError>;
+
+/// Example input from a puzzle text, with the answers it's given for. Parts
+/// without an answer aren't checked on it.
+pub struct Sample {
+    pub name: &'static str,
+    pub input: &'static str,
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+    /// Solver for days solving their samples with other parameters than
+    /// their input.
+    pub solver: Option<Box<dyn DynSolver>>,
+}
+
+impl Sample {
+    pub fn new(name: &'static str, input: &'static str) -> Sample {
+        Sample {
+            name,
+            input,
+            part1: None,
+            part2: None,
+            solver: None,
+        }
+    }
+
+    pub fn part1(self, answer: &'static str) -> Sample {
+        Sample {
+            part1: Some(answer),
+            ..self
+        }
+    }
+
+    pub fn part2(self, answer: &'static str) -> Sample {
+        Sample {
+            part2: Some(answer),
+            ..self
+        }
+    }
+
+    pub fn solver(self, solver: impl DynSolver + 'static) -> Sample {
+        Sample {
+            solver: Some(Box::new(solver)),
+            ..self
+        }
+    }
+
+    pub fn answer(&self, part: Part) -> Option<&'static str> {
+        match part {
+            Part::One => self.part1,
+            Part::Two => self.part2,
+        }
+    }
+}
+
+/// `Sample` of the file `src/inputs/$name`, embedded in the binary.
+#[macro_export]
+macro_rules! sample {
+    ($name:literal) => {
+        $crate::solver::Sample::new($name, include_str!(concat!("inputs/", $name)))
+    };
+}
+
+/// A single day of the puzzle. `parse` turns the raw puzzle input into the
+/// day's model, which is then shared by both parts. Malformed input is
+/// reported as a `ParseError` instead of a panic. Parts get a `Context` to
+/// report progress on and to find out their run was cancelled. `samples`
+/// are the examples of the puzzle text, checked with `--sample`. Each day's
+/// parser cleans its input up with `parse::normalize` first, so it reads the
+/// same lines however the file was saved.
+pub trait Solver {
+    type Input;
+
+    fn day(&self) -> usize;
+    fn title(&self) -> &'static str;
+    fn parse(&self, input: &str) -> ParseResult<Self::Input>;
+    fn part1(&self, input: &Self::Input, ctx: &Context) -> PartResult;
+    fn part2(&self, input: &Self::Input, ctx: &Context) -> PartResult;
+
+    fn samples(&self) -> Vec<Sample> {
+        Vec::new()
+    }
+}
+
+/// Type erased `Solver`, so days with different input models can live in one
+/// registry.
+pub trait DynSolver: Send + Sync {
+    fn day(&self) -> usize;
+    fn title(&self) -> &'static str;
+    fn parse(&self, input: &str) -> ParseResult<Box<dyn Any>>;
+    fn part1(&self, input: &dyn Any, ctx: &Context) -> PartResult;
+    fn part2(&self, input: &dyn Any, ctx: &Context) -> PartResult;
+    fn samples(&self) -> Vec<Sample>;
+}
+
+impl<S> DynSolver for S
+where
+    S: Solver + Send + Sync,
+    S::Input: 'static,
+{
+    fn day(&self) -> usize {
+        Solver::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Solver::title(self)
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Box<dyn Any>> {
+        Ok(Box::new(Solver::parse(self, input)?))
+    }
+
+    fn part1(&self, input: &dyn Any, ctx: &Context) -> PartResult {
+        Solver::part1(
+            self,
+            input.downcast_ref().expect("input parsed by another day"),
+            ctx,
+        )
+    }
+
+    fn part2(&self, input: &dyn Any, ctx: &Context) -> PartResult {
+        Solver::part2(
+            self,
+            input.downcast_ref().expect("input parsed by another day"),
+            ctx,
+        )
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        Solver::samples(self)
+    }
+}
+
+pub type Registry = BTreeMap<usize, Box<dyn DynSolver>>;
+
+pub fn registry() -> Registry {
+    let solvers: Vec<Box<dyn DynSolver>> = vec![
+        Box::new(day1::Day1),
+        Box::new(day2::Day2),
+        Box::new(day3::Day3),
+        Box::new(day4::Day4),
+        Box::new(day5::Day5),
+        Box::new(day6::Day6),
+        Box::new(day7::Day7),
+        Box::new(day8::Day8),
+        Box::new(day9::Day9),
+        Box::new(day10::Day10),
+        Box::new(day11::Day11),
+        Box::new(day12::Day12),
+        Box::new(day13::Day13),
+        Box::new(day14::Day14),
+        Box::new(day15::Day15::default()),
+        Box::new(day16::Day16),
+        Box::new(day17::Day17::default()),
+        Box::new(day18::Day18),
+        Box::new(day19::Day19),
+        Box::new(day20::Day20),
+        Box::new(day21::Day21),
+        Box::new(day22::Day22::default()),
+        Box::new(day23::Day23),
+        Box::new(day24::Day24),
+        Box::new(day25::Day25),
+    ];
+    solvers
+        .into_iter()
+        .map(|solver| (solver.day(), solver))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let registry = registry();
+        assert_eq!(registry.len(), 25);
+        registry
+            .iter()
+            .for_each(|(day, solver)| assert_eq!(*day, solver.day()));
+        assert_eq!(registry[&1].title(), "Calorie Counting");
+        assert_eq!(registry[&19].title(), "Not Enough Minerals");
+    }
+
+    #[test]
+    fn test_samples() {
+        for (day, solver) in registry() {
+            let samples = solver.samples();
+            assert!(!samples.is_empty(), "day{day} has no samples");
+            for sample in samples {
+                assert!(sample.name.starts_with(&format!("aoc_{day}_sample")));
+                // A scaffolded day starts with an empty sample and no answers.
+                if sample.part1.is_some() || sample.part2.is_some() {
+                    assert!(!sample.input.is_empty());
+                }
+            }
+        }
+    }
+
+    // The samples as saved on Windows, with a byte order mark, CRLF line
+    // endings and, unless the day keeps it, whitespace ending the lines.
+    #[test]
+    fn test_normalized_samples() {
+        let ctx = Context::default();
+        // Day19 takes too long to solve its sample twice.
+        for (n, day) in registry().into_iter().filter(|(day, _)| *day != 19) {
+            // Days 5 and 22 read their drawings by column.
+            let trailing = match n {
+                5 | 22 => "",
+                _ => " \t",
+            };
+            for sample in day.samples() {
+                let solver = sample.solver.as_deref().unwrap_or(day.as_ref());
+                let input = "\u{feff}".to_string()
+                    + &sample
+                        .input
+                        .split('\n')
+                        .collect::<Vec<_>>()
+                        .join(&format!("{trailing}\r\n"));
+                let model = solver.parse(&input).unwrap();
+                if let Some(answer) = sample.part1 {
+                    assert_eq!(solver.part1(model.as_ref(), &ctx).unwrap(), answer);
+                }
+                if let Some(answer) = sample.part2 {
+                    assert_eq!(solver.part2(model.as_ref(), &ctx).unwrap(), answer);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parts_run_independently() {
+        let day1 = &registry()[&1];
+        let input = day1
+            .parse("1000\n2000\n3000\n\n4000\n\n5000\n6000\n\n7000\n8000\n9000\n\n10000\n\n")
+            .unwrap();
+        let ctx = Context::default();
+        assert_eq!(day1.part2(input.as_ref(), &ctx), Ok("45000".to_string()));
+        assert_eq!(day1.part1(input.as_ref(), &ctx), Ok("24000".to_string()));
+    }
+}
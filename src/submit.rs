@@ -0,0 +1,350 @@
+use crate::cli::Part;
+use crate::client::Client;
+use crate::parse::{lines, Line, ParseError, ParseResult};
+use regex::Regex;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+pub const SUBMISSIONS_FILE: &str = ".submissions";
+
+// The site makes you wait at least this long after a wrong answer.
+const WAIT_AFTER_WRONG: u64 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    TooHigh,
+    TooLow,
+    Wrong,
+    AlreadySolved,
+    /// Seconds left until the next answer is accepted.
+    RateLimited(u64),
+}
+
+impl Verdict {
+    /// Reads the verdict off the page returned for a submitted answer.
+    pub fn from_page(page: &str) -> Option<Self> {
+        if page.contains("That's the right answer") {
+            Some(Verdict::Correct)
+        } else if page.contains("That's not the right answer") {
+            if page.contains("your answer is too high") {
+                Some(Verdict::TooHigh)
+            } else if page.contains("your answer is too low") {
+                Some(Verdict::TooLow)
+            } else {
+                Some(Verdict::Wrong)
+            }
+        } else if page.contains("You gave an answer too recently") {
+            let wait = Regex::new(r"You have (?:(?P<m>\d+)m )?(?P<s>\d+)s left to wait").unwrap();
+            let seconds = wait.captures(page).map_or(WAIT_AFTER_WRONG, |c| {
+                let number = |name| c.name(name).map_or(0, |m| m.as_str().parse().unwrap_or(0));
+                number("m") * 60 + number("s")
+            });
+            Some(Verdict::RateLimited(seconds))
+        } else if page.contains("Did you already complete it") {
+            Some(Verdict::AlreadySolved)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::TooHigh | Verdict::TooLow | Verdict::Wrong)
+    }
+
+    // Single word form used in the submissions file.
+    fn token(&self) -> String {
+        match self {
+            Verdict::Correct => "correct".to_string(),
+            Verdict::TooHigh => "too-high".to_string(),
+            Verdict::TooLow => "too-low".to_string(),
+            Verdict::Wrong => "wrong".to_string(),
+            Verdict::AlreadySolved => "already-solved".to_string(),
+            Verdict::RateLimited(seconds) => format!("rate-limited:{seconds}"),
+        }
+    }
+
+    fn parse(line: &Line, token: &str) -> ParseResult<Self> {
+        match token.split_once(':') {
+            Some(("rate-limited", seconds)) => Ok(Verdict::RateLimited(line.number(seconds)?)),
+            _ => match token {
+                "correct" => Ok(Verdict::Correct),
+                "too-high" => Ok(Verdict::TooHigh),
+                "too-low" => Ok(Verdict::TooLow),
+                "wrong" => Ok(Verdict::Wrong),
+                "already-solved" => Ok(Verdict::AlreadySolved),
+                _ => Err(line.error(token, format!("unknown verdict '{token}'"))),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "correct"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Wrong => write!(f, "wrong"),
+            Verdict::AlreadySolved => write!(f, "already solved"),
+            Verdict::RateLimited(seconds) => write!(f, "rate-limited, wait {seconds}s"),
+        }
+    }
+}
+
+/// Answer sent to the site, with the Unix time it was sent at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub time: u64,
+    pub day: usize,
+    pub part: usize,
+    pub verdict: Verdict,
+    pub answer: String,
+}
+
+impl TryFrom<Line<'_>> for Submission {
+    type Error = ParseError;
+
+    fn try_from(line: Line) -> ParseResult<Self> {
+        let fields = line.text.splitn(5, ' ').collect::<Vec<&str>>();
+        let [time, day, part, verdict, answer] = fields[..] else {
+            return Err(line.error_at(line.text.len(), "expected TIME DAY PART VERDICT ANSWER"));
+        };
+        Ok(Submission {
+            time: line.number(time)?,
+            day: line.number(day)?,
+            part: line.number(part)?,
+            verdict: Verdict::parse(&line, verdict)?,
+            answer: answer.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Submission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.time,
+            self.day,
+            self.part,
+            self.verdict.token(),
+            self.answer
+        )
+    }
+}
+
+/// Log of the answers sent so far, kept in a file with one submission per
+/// line.
+#[derive(Debug)]
+pub struct Submissions {
+    path: String,
+    entries: Vec<Submission>,
+}
+
+impl Submissions {
+    /// Reads the log at `path`, which doesn't need to exist yet.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let s = match fs::read_to_string(path) {
+            Ok(s) => s,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("can't read {path}: {e}")),
+        };
+        let entries = lines(&s)
+            .filter(|line| !line.text.is_empty())
+            .map(Submission::try_from)
+            .collect::<ParseResult<_>>()
+            .map_err(|e| e.in_file(path).to_string())?;
+        Ok(Submissions {
+            path: path.to_string(),
+            entries,
+        })
+    }
+
+    /// Earlier submission proving `answer` wrong, either the same answer or
+    /// one beyond a known too high or too low answer.
+    pub fn known_wrong(&self, day: usize, part: usize, answer: &str) -> Option<&Submission> {
+        let number = |s: &str| s.parse::<i128>().ok();
+        self.entries
+            .iter()
+            .filter(|s| s.day == day && s.part == part && s.verdict.is_wrong())
+            .find(|s| {
+                s.answer == answer
+                    || match (s.verdict, number(&s.answer), number(answer)) {
+                        (Verdict::TooHigh, Some(high), Some(n)) => n >= high,
+                        (Verdict::TooLow, Some(low), Some(n)) => n <= low,
+                        _ => false,
+                    }
+            })
+    }
+
+    /// Seconds to wait at `now` before the site accepts another answer.
+    pub fn wait(&self, now: u64) -> Option<u64> {
+        let last = self.entries.iter().max_by_key(|s| s.time)?;
+        let until = match last.verdict {
+            Verdict::RateLimited(seconds) => last.time + seconds,
+            v if v.is_wrong() => last.time + WAIT_AFTER_WRONG,
+            _ => return None,
+        };
+        until.checked_sub(now).filter(|wait| *wait > 0)
+    }
+
+    pub fn record(&mut self, submission: Submission) -> Result<(), String> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{submission}"))
+            .map_err(|e| format!("can't write {}: {e}", self.path))?;
+        self.entries.push(submission);
+        Ok(())
+    }
+}
+
+/// Submits `answer` unless it's already known to be wrong or the site is
+/// still making us wait, and records the verdict.
+pub fn submit(
+    client: &Client,
+    submissions: &mut Submissions,
+    day: usize,
+    part: Part,
+    answer: &str,
+    now: u64,
+) -> Result<Verdict, String> {
+    let part = part.number();
+    if answer.is_empty() || answer.contains('\n') {
+        return Err(format!(
+            "day {day} part {part} has no single line answer to submit"
+        ));
+    }
+    if let Some(s) = submissions.known_wrong(day, part, answer) {
+        return Err(format!(
+            "not submitting {answer}, {} was already {}",
+            s.answer, s.verdict
+        ));
+    }
+    if let Some(wait) = submissions.wait(now) {
+        return Err(format!("wait {wait}s before submitting again"));
+    }
+    let page = client.answer(day, part, answer)?;
+    let verdict = Verdict::from_page(&page).ok_or("can't find the verdict in the response")?;
+    submissions.record(Submission {
+        time: now,
+        day,
+        part,
+        verdict,
+        answer: answer.to_string(),
+    })?;
+    Ok(verdict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::mock::{serve, temp_path};
+
+    const TOO_HIGH: &str = "<article><p>That's not the right answer; your answer is too high.  \
+        If you're stuck, make sure you're using the full input data; please wait one minute \
+        before trying again. [<a href=\"/2022/day/1\">Return to Day 1</a>]</p></article>";
+    const TOO_RECENT: &str = "<article><p>You gave an answer too recently; you have to wait \
+        after submitting an answer before trying again.  You have 1m 13s left to wait. \
+        [<a href=\"/2022/day/1\">Return to Day 1</a>]</p></article>";
+    const CORRECT: &str = "<article><p>That's the right answer!  You are one gold star \
+        closer to collecting enough star fruit.</p></article>";
+
+    fn load(name: &str, log: &str) -> Submissions {
+        let path = temp_path(name);
+        fs::write(&path, log).unwrap();
+        Submissions::load(path.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_verdict() {
+        assert_eq!(Verdict::from_page(CORRECT), Some(Verdict::Correct));
+        assert_eq!(Verdict::from_page(TOO_HIGH), Some(Verdict::TooHigh));
+        assert_eq!(
+            Verdict::from_page(&TOO_HIGH.replace("high", "low")),
+            Some(Verdict::TooLow)
+        );
+        assert_eq!(
+            Verdict::from_page("That's not the right answer.  If you're stuck"),
+            Some(Verdict::Wrong)
+        );
+        assert_eq!(
+            Verdict::from_page(TOO_RECENT),
+            Some(Verdict::RateLimited(73))
+        );
+        assert_eq!(
+            Verdict::from_page(
+                "You don't seem to be solving the right level.  Did you already complete it?"
+            ),
+            Some(Verdict::AlreadySolved)
+        );
+        assert_eq!(Verdict::from_page("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_submissions() {
+        let log = load(
+            "submissions",
+            "100 1 1 too-high 5000\n200 1 1 too-low 4000\n300 1 2 wrong ABC\n",
+        );
+        assert_eq!(log.known_wrong(1, 1, "5000").unwrap().time, 100);
+        assert_eq!(log.known_wrong(1, 1, "6000").unwrap().time, 100);
+        assert_eq!(log.known_wrong(1, 1, "3999").unwrap().time, 200);
+        assert_eq!(log.known_wrong(1, 1, "4500"), None);
+        assert_eq!(log.known_wrong(1, 2, "ABC").unwrap().time, 300);
+        assert_eq!(log.known_wrong(2, 1, "5000"), None);
+        assert_eq!(log.wait(330), Some(30));
+        assert_eq!(log.wait(360), None);
+        assert_eq!(
+            Submissions::load(temp_path("none").to_str().unwrap())
+                .unwrap()
+                .entries,
+            vec![]
+        );
+        let path = temp_path("bad");
+        fs::write(&path, "100 1 1 maybe 5000\n").unwrap();
+        let path = path.to_str().unwrap();
+        assert_eq!(
+            Submissions::load(path).unwrap_err(),
+            format!("{path}:1:9: unknown verdict 'maybe'")
+        );
+    }
+
+    #[test]
+    fn test_submit() {
+        let (url, server) = serve(vec![(200, TOO_HIGH), (200, TOO_RECENT), (200, CORRECT)]);
+        let client = Client::new(&url, "abc123");
+        let mut log = load("submit", "");
+        assert_eq!(
+            submit(&client, &mut log, 1, Part::One, "72018", 1000),
+            Ok(Verdict::TooHigh)
+        );
+        // Refused without asking the site.
+        assert!(submit(&client, &mut log, 1, Part::One, "72018", 2000).is_err());
+        assert!(submit(&client, &mut log, 1, Part::One, "80000", 2000).is_err());
+        assert_eq!(
+            submit(&client, &mut log, 1, Part::One, "72017", 1030),
+            Err("wait 30s before submitting again".to_string())
+        );
+        assert!(submit(&client, &mut log, 10, Part::Two, "\n##..\n", 2000).is_err());
+        assert_eq!(
+            submit(&client, &mut log, 1, Part::One, "72017", 1060),
+            Ok(Verdict::RateLimited(73))
+        );
+        assert!(submit(&client, &mut log, 1, Part::One, "72017", 1100).is_err());
+        assert_eq!(
+            submit(&client, &mut log, 1, Part::One, "72017", 1200),
+            Ok(Verdict::Correct)
+        );
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /2022/day/1/answer HTTP/1.1"));
+        assert!(requests[0].ends_with("level=1&answer=72018"));
+        assert_eq!(
+            fs::read_to_string(&log.path).unwrap(),
+            "1000 1 1 too-high 72018\n1060 1 1 rate-limited:73 72017\n1200 1 1 correct 72017\n"
+        );
+    }
+}
@@ -1,14 +1,65 @@
-use std::fs;
+use crate::context::Context;
+use crate::parse::{lines, normalize, Normalize, ParseResult};
+use crate::sample;
+use crate::solver::{PartResult, Sample, Solver};
 
-fn solution(filename: &str) -> i32{
-    fs::read_to_string(filename).unwrap().lines().count() as i32
+pub fn parse(input: &str) -> ParseResult<Vec<String>> {
+    let input = &*normalize(input, Normalize::default());
+    Ok(lines(input).map(|line| line.text.to_string()).collect())
 }
 
-#[test]
-fn test_run() {
-    assert_eq!(solution("src/inputs/aoc_XX_sample.input"), 0);
+pub fn part1(lines: &[String]) -> usize {
+    lines.len()
 }
 
-pub fn run() {
-    println!("dayXX p1: {}", solution("src/inputs/aoc_XX.input"));
-}
\ No newline at end of file
+pub fn part2(lines: &[String]) -> usize {
+    lines.len()
+}
+
+pub struct DayXX;
+
+impl Solver for DayXX {
+    type Input = Vec<String>;
+
+    fn day(&self) -> usize {
+        XX
+    }
+
+    fn title(&self) -> &'static str {
+        "Day XX"
+    }
+
+    fn parse(&self, input: &str) -> ParseResult<Self::Input> {
+        parse(input)
+    }
+
+    fn part1(&self, lines: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part1(lines).to_string())
+    }
+
+    fn part2(&self, lines: &Self::Input, _ctx: &Context) -> PartResult {
+        Ok(part2(lines).to_string())
+    }
+
+    fn samples(&self) -> Vec<Sample> {
+        // Add the answers given in the puzzle text with `part1` and `part2`.
+        vec![sample!("aoc_XX_sample.input")]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::answers::assert_known_answer;
+    use crate::cli::Part;
+    use std::fs;
+
+    // Checked once the answers are in src/inputs/answers.txt, then switch to
+    // `assert_answer`.
+    #[test]
+    fn test_run() {
+        let sample = parse(&fs::read_to_string("src/inputs/aoc_XX_sample.input").unwrap()).unwrap();
+        assert_known_answer(XX, Part::One, "aoc_XX_sample.input", part1(&sample));
+        assert_known_answer(XX, Part::Two, "aoc_XX_sample.input", part2(&sample));
+    }
+}
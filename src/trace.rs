@@ -0,0 +1,150 @@
+use crate::cli::Part;
+use crate::report::json_string;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, LineWriter, Write};
+use std::sync::Mutex;
+
+/// Value of an event field, written as a JSON number or string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Number(String),
+    Text(String),
+}
+
+macro_rules! number_values {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(n: $t) -> Self {
+                Value::Number(n.to_string())
+            }
+        })*
+    };
+}
+
+number_values!(i32, i64, u32, u64, usize);
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Text(s)
+    }
+}
+
+impl From<char> for Value {
+    fn from(c: char) -> Self {
+        Value::Text(c.to_string())
+    }
+}
+
+/// Intermediate step of a day, like a monkey throwing an item: what happened
+/// and the values it happened with, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    pub kind: &'static str,
+    pub fields: Vec<(&'static str, Value)>,
+}
+
+impl Event {
+    pub fn new(kind: &'static str) -> Event {
+        Event {
+            kind,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Into<Value>) -> Event {
+        self.fields.push((name, value.into()));
+        self
+    }
+}
+
+/// Where the traced events go, one JSON object per line tagged with the day
+/// and part it comes from.
+pub struct Trace(Mutex<Box<dyn Write + Send>>);
+
+impl Trace {
+    pub fn new(out: impl Write + Send + 'static) -> Trace {
+        Trace(Mutex::new(Box::new(out)))
+    }
+
+    /// Trace written to the file at `path`, a line at a time so it's complete
+    /// however the run ends.
+    pub fn create(path: &str) -> io::Result<Trace> {
+        Ok(Trace::new(LineWriter::new(File::create(path)?)))
+    }
+
+    pub fn write(&self, day: usize, part: Part, event: &Event) {
+        let mut line = format!(
+            "{{\"day\":{day},\"part\":{},\"event\":{}",
+            part.number(),
+            json_string(event.kind)
+        );
+        for (name, value) in &event.fields {
+            let value = match value {
+                Value::Number(n) => n.clone(),
+                Value::Text(s) => json_string(s),
+            };
+            write!(line, ",{}:{value}", json_string(name)).unwrap();
+        }
+        line.push_str("}\n");
+        // The trace only helps debugging, so failing to write it doesn't fail
+        // the run.
+        let _ = self.0.lock().unwrap().write_all(line.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use std::sync::Arc;
+
+    // Writer keeping what's written, shared with the test.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let buffer = Buffer::default();
+        let ctx = Context::default().traced(Arc::new(Trace::new(buffer.clone())));
+        // Events outside of a part have nowhere to go.
+        ctx.trace(|| panic!("traced outside of a part"));
+        let ctx = ctx.child(|_| {}).in_part(11, Part::Two);
+        assert!(ctx.is_tracing());
+        ctx.trace(|| {
+            Event::new("throw")
+                .with("monkey", 0)
+                .with("worry", 1501u64)
+                .with("to", 3usize)
+        });
+        ctx.trace(|| Event::new("open").with("valve", "\"DD\""));
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+            "{\"day\":11,\"part\":2,\"event\":\"throw\",\"monkey\":0,\"worry\":1501,\"to\":3}\n\
+             {\"day\":11,\"part\":2,\"event\":\"open\",\"valve\":\"\\\"DD\\\"\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_untraced() {
+        let ctx = Context::default().in_part(9, Part::One);
+        assert!(!ctx.is_tracing());
+        ctx.trace(|| panic!("traced without a trace"));
+    }
+}
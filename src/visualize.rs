@@ -0,0 +1,298 @@
+use crate::cli::{ImageFormat, Part, Visualize};
+use crate::grid::Grid;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Cell of a frame: the char drawing it on the terminal and its colour in
+/// the images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pixel {
+    pub glyph: char,
+    pub rgb: [u8; 3],
+}
+
+impl Pixel {
+    pub const fn new(glyph: char, rgb: [u8; 3]) -> Pixel {
+        Pixel { glyph, rgb }
+    }
+
+    // Luma of the colour, by ITU-R BT.601.
+    fn grey(&self) -> u8 {
+        let [r, g, b] = self.rgb.map(u32::from);
+        ((299 * r + 587 * g + 114 * b) / 1000) as u8
+    }
+}
+
+/// Picture of a grid day at one of its steps.
+pub type Frame = Grid<Pixel>;
+
+/// Takes the frames of the parts being visualised, in order.
+pub trait FrameSink: Send {
+    fn frame(&mut self, day: usize, part: Part, number: usize, frame: &Frame) -> io::Result<()>;
+}
+
+/// Most frames a second `--visualize terminal:FPS` takes.
+pub const MAX_FPS: usize = 1000;
+
+/// Animates the frames in place on a terminal, at most `fps` a second.
+pub struct Terminal {
+    out: Box<dyn Write + Send>,
+    delay: Duration,
+    next: Option<Instant>,
+}
+
+impl Terminal {
+    /// Terminal showing `fps` frames a second, from 1 to `MAX_FPS`.
+    pub fn new(out: impl Write + Send + 'static, fps: usize) -> Terminal {
+        Terminal {
+            out: Box::new(out),
+            delay: Duration::from_secs(1) / fps as u32,
+            next: None,
+        }
+    }
+}
+
+impl FrameSink for Terminal {
+    fn frame(&mut self, day: usize, part: Part, number: usize, frame: &Frame) -> io::Result<()> {
+        // The first frame clears the screen, the next ones are drawn over it
+        // from the top left corner, clearing what's left of the previous one.
+        let mut screen = match self.next {
+            Some(_) => String::from("\x1b[H"),
+            None => String::from("\x1b[2J\x1b[H"),
+        };
+        write!(
+            screen,
+            "day {day} part {} frame {number}\x1b[K",
+            part.number()
+        )
+        .unwrap();
+        for row in frame.rows() {
+            screen.push('\n');
+            screen.extend(row.iter().map(|p| p.glyph));
+            screen.push_str("\x1b[K");
+        }
+        screen.push_str("\n\x1b[J");
+        if let Some(next) = self.next {
+            thread::sleep(next.saturating_duration_since(Instant::now()));
+        }
+        self.out.write_all(screen.as_bytes())?;
+        self.out.flush()?;
+        self.next = Some(Instant::now() + self.delay);
+        Ok(())
+    }
+}
+
+/// Encodes `frame` as a binary PPM, or PGM, image.
+pub fn encode(format: ImageFormat, frame: &Frame) -> Vec<u8> {
+    let magic = match format {
+        ImageFormat::Ppm => "P6",
+        ImageFormat::Pgm => "P5",
+    };
+    let mut image = format!("{magic}\n{} {}\n255\n", frame.width(), frame.height()).into_bytes();
+    for pixel in frame.rows().flatten() {
+        match format {
+            ImageFormat::Ppm => image.extend(pixel.rgb),
+            ImageFormat::Pgm => image.push(pixel.grey()),
+        }
+    }
+    image
+}
+
+/// Writes the frames to `dir` as numbered images, like
+/// `day14_part1_00042.ppm`.
+pub struct Images {
+    dir: PathBuf,
+    format: ImageFormat,
+}
+
+impl Images {
+    pub fn new(dir: impl Into<PathBuf>, format: ImageFormat) -> Images {
+        Images {
+            dir: dir.into(),
+            format,
+        }
+    }
+}
+
+impl FrameSink for Images {
+    fn frame(&mut self, day: usize, part: Part, number: usize, frame: &Frame) -> io::Result<()> {
+        let extension = match self.format {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+        };
+        let name = format!("day{day}_part{}_{number:05}.{extension}", part.number());
+        fs::write(self.dir.join(name), encode(self.format, frame))
+    }
+}
+
+/// Numbers the frames of every part and hands them to a sink.
+pub struct Visualizer {
+    sink: Mutex<Box<dyn FrameSink>>,
+    frames: Mutex<HashMap<(usize, usize), usize>>,
+}
+
+impl Visualizer {
+    pub fn new(sink: impl FrameSink + 'static) -> Visualizer {
+        Visualizer {
+            sink: Mutex::new(Box::new(sink)),
+            frames: Mutex::default(),
+        }
+    }
+
+    /// Visualizer sending the frames where `--visualize` asks, creating the
+    /// directory of the images.
+    pub fn create(visualize: &Visualize) -> io::Result<Visualizer> {
+        match visualize {
+            // On stderr, so the answers on stdout stay as they're formatted.
+            Visualize::Terminal { fps } => Ok(Visualizer::new(Terminal::new(io::stderr(), *fps))),
+            Visualize::Images { format, dir } => {
+                fs::create_dir_all(dir)?;
+                Ok(Visualizer::new(Images::new(dir, *format)))
+            }
+        }
+    }
+
+    pub fn show(&self, day: usize, part: Part, frame: &Frame) {
+        let number = {
+            let mut frames = self.frames.lock().unwrap();
+            let number = frames.entry((day, part.number())).or_default();
+            *number += 1;
+            *number
+        };
+        // Like the trace, the frames only help looking into a day, so failing
+        // to show them doesn't fail the run.
+        let _ = self.sink.lock().unwrap().frame(day, part, number, frame);
+    }
+}
+
+#[cfg(test)]
+pub(crate) use tests::record;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::Context;
+    use std::sync::Arc;
+
+    // Keeps the frames it's shown.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<Frame>>>);
+
+    impl FrameSink for Recorder {
+        fn frame(&mut self, _: usize, _: Part, _: usize, frame: &Frame) -> io::Result<()> {
+            self.0.lock().unwrap().push(frame.clone());
+            Ok(())
+        }
+    }
+
+    /// Frames shown by `solve` when solving `part` of `day` with the context
+    /// it's given.
+    pub(crate) fn record(day: usize, part: Part, solve: impl FnOnce(&Context)) -> Vec<Frame> {
+        let recorder = Recorder::default();
+        let visualizer = Visualizer::new(recorder.clone());
+        solve(
+            &Context::default()
+                .visualized(Arc::new(visualizer))
+                .in_part(day, part),
+        );
+        let frames = recorder.0.lock().unwrap().clone();
+        frames
+    }
+
+    // Writer keeping what's written, shared with the test.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    const ROCK: Pixel = Pixel::new('#', [255, 0, 0]);
+    const AIR: Pixel = Pixel::new('.', [0, 0, 255]);
+
+    fn frame() -> Frame {
+        Grid::from_rows([vec![ROCK, AIR], vec![AIR, AIR]])
+    }
+
+    #[test]
+    fn test_terminal() {
+        let buffer = Buffer::default();
+        let ctx = Context::default().visualized(Arc::new(Visualizer::new(Terminal::new(
+            buffer.clone(),
+            1000,
+        ))));
+        // Frames outside of a part have nowhere to go.
+        ctx.frame(|| panic!("shown outside of a part"));
+        let ctx = ctx.child(|_| {}).in_part(14, Part::One);
+        assert!(ctx.is_visualizing());
+        ctx.frame(frame);
+        ctx.frame(|| Grid::from_rows([vec![AIR]]));
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+            "\x1b[2J\x1b[Hday 14 part 1 frame 1\x1b[K\n#.\x1b[K\n..\x1b[K\n\x1b[J\
+             \x1b[Hday 14 part 1 frame 2\x1b[K\n.\x1b[K\n\x1b[J"
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            encode(ImageFormat::Ppm, &frame()),
+            b"P6\n2 2\n255\n\xff\x00\x00\x00\x00\xff\x00\x00\xff\x00\x00\xff"
+        );
+        assert_eq!(
+            encode(ImageFormat::Pgm, &frame()),
+            b"P5\n2 2\n255\n\x4c\x1d\x1d\x1d"
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let dir = std::env::temp_dir().join(format!("aoc2022-frames-{}", std::process::id()));
+        let visualizer = Visualizer::create(&Visualize::Images {
+            format: ImageFormat::Pgm,
+            dir: dir.to_string_lossy().to_string(),
+        })
+        .unwrap();
+        visualizer.show(23, Part::Two, &frame());
+        visualizer.show(23, Part::Two, &frame());
+        visualizer.show(23, Part::One, &frame());
+        let mut names = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<String>>();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "day23_part1_00001.pgm",
+                "day23_part2_00001.pgm",
+                "day23_part2_00002.pgm"
+            ]
+        );
+        assert_eq!(
+            fs::read(dir.join("day23_part1_00001.pgm")).unwrap(),
+            encode(ImageFormat::Pgm, &frame())
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_not_visualizing() {
+        let ctx = Context::default().in_part(17, Part::One);
+        assert!(!ctx.is_visualizing());
+        ctx.frame(|| panic!("shown without a visualizer"));
+    }
+}